@@ -1,15 +1,19 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::gas_keystore::{GasKeystore, SavedAccount, WorkloadAccounts};
 use crate::util::{make_pay_tx, UpdatedAndNewlyMintedGasCoins};
 use crate::workloads::payload::Payload;
 use crate::workloads::workload::{Workload, WorkloadBuilder};
 use crate::workloads::{Gas, GasCoinConfig};
 use crate::ValidatorProxy;
-use anyhow::{Error, Result};
+use anyhow::{anyhow, bail, Error, Result};
+use futures::future::try_join_all;
+use futures::StreamExt;
 use itertools::Itertools;
 use move_core_types::language_storage::TypeTag;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
 use std::sync::Arc;
 use sui_types::base_types::{ObjectRef, SuiAddress};
 use sui_types::crypto::AccountKeyPair;
@@ -19,6 +23,153 @@ use sui_types::messages::{
 };
 use sui_types::utils::to_sender_signed_transaction;
 use sui_types::{coin, SUI_FRAMEWORK_OBJECT_ID};
+use tracing::warn;
+
+/// Gas budget of the transactions splitting the pay coin at `gas_price`
+fn split_coin_gas_budget(gas_price: u64) -> Result<u64> {
+    500_000_000u64
+        .checked_mul(gas_price)
+        .ok_or_else(|| anyhow!("Gas budget overflows at gas price {}", gas_price))
+}
+
+/// Checks that the coin configs of workload `index` are all sent to the addresses its builder
+/// reported, if any, as those are the only ones provisioned for it
+fn check_required_addresses(
+    index: usize,
+    required: &HashSet<SuiAddress>,
+    coin_configs: &[GasCoinConfig],
+) -> Result<()> {
+    if required.is_empty() {
+        return Ok(());
+    }
+    if let Some(config) = coin_configs
+        .iter()
+        .find(|config| !required.contains(&config.address))
+    {
+        bail!(
+            "Workload {} funds {}, which is not among the addresses it requires",
+            index,
+            config.address
+        );
+    }
+    Ok(())
+}
+
+/// Name the accounts of the workload of `builder` are saved under: the type of the builder, which
+/// leads its debug output, numbered if `names` already has workloads of that type
+fn workload_name(
+    builder: &dyn WorkloadBuilder<dyn Payload>,
+    names: &mut HashMap<String, usize>,
+) -> String {
+    let debug = format!("{:?}", builder);
+    let type_name = debug
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()
+        .unwrap_or_default()
+        .to_owned();
+    let count = names.entry(type_name.clone()).or_default();
+    *count += 1;
+    match *count {
+        1 => type_name,
+        count => format!("{}#{}", type_name, count),
+    }
+}
+
+/// Accounts saved for a workload by an earlier run, still to be matched to its coin configs
+#[derive(Debug)]
+enum SavedAccounts {
+    /// Matched to the configs in order, for builders creating a keypair for every config
+    ByPosition(VecDeque<SavedAccount>),
+    /// Matched to the configs sent to their address, for builders fixing their addresses
+    ByAddress(HashMap<SuiAddress, VecDeque<SavedAccount>>),
+    /// No longer reused, only kept in the keystore
+    Skipped(Vec<SavedAccount>),
+}
+
+impl SavedAccounts {
+    fn new(accounts: Vec<SavedAccount>, by_address: bool) -> Self {
+        if !by_address {
+            return SavedAccounts::ByPosition(accounts.into());
+        }
+        let mut by_address: HashMap<SuiAddress, VecDeque<SavedAccount>> = HashMap::new();
+        for account in accounts {
+            by_address
+                .entry(account.address())
+                .or_default()
+                .push_back(account);
+        }
+        SavedAccounts::ByAddress(by_address)
+    }
+
+    fn none() -> Self {
+        SavedAccounts::Skipped(vec![])
+    }
+
+    fn is_skipped(&self) -> bool {
+        matches!(self, SavedAccounts::Skipped(_))
+    }
+
+    /// Saved accounts for `coin_configs`, in the order of the configs, or `None` if some config
+    /// has no saved account, in which case none are taken
+    fn take(&mut self, coin_configs: &[GasCoinConfig]) -> Option<Vec<SavedAccount>> {
+        match self {
+            SavedAccounts::ByPosition(accounts) => (accounts.len() >= coin_configs.len())
+                .then(|| accounts.drain(..coin_configs.len()).collect()),
+            SavedAccounts::ByAddress(accounts) => {
+                let mut needed: HashMap<SuiAddress, usize> = HashMap::new();
+                for config in coin_configs {
+                    *needed.entry(config.address).or_default() += 1;
+                }
+                if needed
+                    .iter()
+                    .any(|(address, n)| accounts.get(address).map_or(0, VecDeque::len) < *n)
+                {
+                    return None;
+                }
+                Some(
+                    coin_configs
+                        .iter()
+                        .map(|config| {
+                            accounts
+                                .get_mut(&config.address)
+                                .and_then(VecDeque::pop_front)
+                                .unwrap()
+                        })
+                        .collect(),
+                )
+            }
+            SavedAccounts::Skipped(_) => None,
+        }
+    }
+
+    /// Stops reusing the accounts, keeping those not taken yet
+    fn skip(&mut self) {
+        let remaining = std::mem::replace(self, SavedAccounts::none()).into_remaining();
+        *self = SavedAccounts::Skipped(remaining);
+    }
+
+    fn into_remaining(self) -> Vec<SavedAccount> {
+        match self {
+            SavedAccounts::ByPosition(accounts) => accounts.into(),
+            SavedAccounts::ByAddress(accounts) => accounts.into_values().flatten().collect(),
+            SavedAccounts::Skipped(accounts) => accounts,
+        }
+    }
+}
+
+/// Total of `split_amounts`, which are all split out of the same coin, so their total has to fit
+/// a balance
+fn total_split_amount(split_amounts: &[u64]) -> Result<u64> {
+    split_amounts
+        .iter()
+        .try_fold(0u64, |total, amount| total.checked_add(*amount))
+        .ok_or_else(|| {
+            anyhow!(
+                "Total amount of {} gas coins overflows, reduce the chunk size",
+                split_amounts.len()
+            )
+        })
+}
 
 /// Bank is used for generating gas for running the benchmark. It is initialized with two gas coins i.e.
 /// `pay_coin` which is split into smaller gas coins and `primary_gas` which is the gas coin used
@@ -30,6 +181,8 @@ pub struct BenchmarkBank {
     pub primary_gas: Gas,
     // Coin to use for splitting and generating small gas coins
     pub pay_coin: Gas,
+    // File the funded accounts are saved to, or reused from if it exists
+    pub gas_keystore: Option<PathBuf>,
 }
 
 impl BenchmarkBank {
@@ -42,64 +195,177 @@ impl BenchmarkBank {
             proxy,
             primary_gas,
             pay_coin,
+            gas_keystore: None,
         }
     }
+    /// Saves the accounts funded by `generate` to `path`, or if there is a file there already,
+    /// reuses the accounts saved in it instead of funding new ones
+    pub fn with_gas_keystore(mut self, path: PathBuf) -> Self {
+        self.gas_keystore = Some(path);
+        self
+    }
+    /// Gas coins for the coin configs of every builder, and the workloads built with them.
+    ///
+    /// With a gas keystore, the accounts saved for a workload by an earlier run are reused for
+    /// its configs: in order for builders creating a keypair for every config, and by address
+    /// for builders reporting `required_addresses`, whose keys are held by the builder. Once
+    /// saved accounts cannot be reused for a workload, its remaining configs are funded anew.
+    /// The keystore is saved as soon as each workload is funded, and keeps any saved accounts
+    /// the run did not need.
     pub async fn generate(
         &mut self,
         builders: Vec<Box<dyn WorkloadBuilder<dyn Payload>>>,
         gas_price: u64,
         chunk_size: u64,
     ) -> Result<Vec<Box<dyn Workload<dyn Payload>>>> {
-        let mut coin_configs = VecDeque::new();
-        for builder in builders.iter() {
-            let init_gas_config = builder.generate_coin_config_for_init().await;
-            let payload_gas_config = builder.generate_coin_config_for_payloads().await;
-            coin_configs.push_back(init_gas_config);
-            coin_configs.push_back(payload_gas_config);
-        }
-        let mut all_coin_configs = vec![];
-        coin_configs
-            .iter()
-            .for_each(|v| all_coin_configs.extend(v.clone()));
-
-        let mut new_gas_coins: Vec<Gas> = vec![];
-        let chunked_coin_configs = all_coin_configs.chunks(chunk_size as usize);
-        eprintln!("Number of gas requests = {}", chunked_coin_configs.len());
-        for chunk in chunked_coin_configs {
-            let (updated_primary_gas, updated_coin, gas_coins) =
-                self.split_coin_and_pay(chunk, gas_price).await?;
-            self.primary_gas = updated_primary_gas;
-            self.pay_coin = updated_coin;
-            new_gas_coins.extend(gas_coins);
-        }
+        let saved = match &self.gas_keystore {
+            Some(path) => GasKeystore::load(path)?,
+            None => None,
+        };
+        let has_saved = saved.is_some();
+        let mut keystore = saved.unwrap_or_default();
+        let mut names = HashMap::new();
+        let mut num_gas_requests = 0;
         let mut workloads = vec![];
-        for builder in builders.iter() {
-            let init_gas_config = coin_configs.pop_front().unwrap();
-            let payload_gas_config = coin_configs.pop_front().unwrap();
-            let init_gas: Vec<Gas> = init_gas_config
-                .iter()
-                .map(|c| {
-                    let (index, _) = new_gas_coins
-                        .iter()
-                        .find_position(|g| g.1 == c.address)
-                        .unwrap();
-                    new_gas_coins.remove(index)
-                })
-                .collect();
-            let payload_gas: Vec<Gas> = payload_gas_config
-                .iter()
-                .map(|c| {
-                    let (index, _) = new_gas_coins
-                        .iter()
-                        .find_position(|g| g.1 == c.address)
-                        .unwrap();
-                    new_gas_coins.remove(index)
-                })
-                .collect();
+        for (index, builder) in builders.iter().enumerate() {
+            let name = workload_name(builder.as_ref(), &mut names);
+            let required: HashSet<SuiAddress> = builder.required_addresses().into_iter().collect();
+            let by_address = !required.is_empty();
+            let (mut saved_init, mut saved_payloads) = match keystore.take_workload(&name) {
+                Some(accounts) => (
+                    SavedAccounts::new(accounts.init, by_address),
+                    SavedAccounts::new(accounts.payloads, by_address),
+                ),
+                None => {
+                    if has_saved {
+                        warn!("No saved accounts for workload {}, funding new ones", name);
+                    }
+                    (SavedAccounts::none(), SavedAccounts::none())
+                }
+            };
+            let mut init_gas: Vec<Gas> = vec![];
+            let init_gas_config = builder.generate_coin_config_for_init().await;
+            check_required_addresses(index, &required, &init_gas_config)?;
+            for chunk in init_gas_config.chunks(chunk_size as usize) {
+                init_gas.extend(
+                    self.reuse_or_pay(
+                        &name,
+                        &mut saved_init,
+                        chunk,
+                        gas_price,
+                        &mut num_gas_requests,
+                    )
+                    .await?,
+                );
+            }
+            // Payload coin configs can be numerous, only keep one chunk of them at a time
+            let mut payload_gas: Vec<Gas> = vec![];
+            let mut payload_gas_config = builder
+                .stream_coin_config_for_payloads()
+                .await
+                .chunks(chunk_size as usize);
+            while let Some(chunk) = payload_gas_config.next().await {
+                check_required_addresses(index, &required, &chunk)?;
+                payload_gas.extend(
+                    self.reuse_or_pay(
+                        &name,
+                        &mut saved_payloads,
+                        &chunk,
+                        gas_price,
+                        &mut num_gas_requests,
+                    )
+                    .await?,
+                );
+            }
+            if let Some(path) = &self.gas_keystore {
+                let save = |gas: &Gas| {
+                    if by_address {
+                        SavedAccount::without_keypair(gas)
+                    } else {
+                        SavedAccount::new(gas)
+                    }
+                };
+                let mut accounts = WorkloadAccounts {
+                    init: init_gas.iter().map(save).collect(),
+                    payloads: payload_gas.iter().map(save).collect(),
+                };
+                // Accounts this run did not need still hold funded coins
+                accounts.init.extend(saved_init.into_remaining());
+                accounts.payloads.extend(saved_payloads.into_remaining());
+                keystore.insert(name, accounts);
+                keystore.save(path)?;
+            }
             workloads.push(builder.build(init_gas, payload_gas).await);
         }
+        eprintln!("Number of gas requests = {}", num_gas_requests);
+        if let Some(path) = &self.gas_keystore {
+            eprintln!("Saved funded accounts to {}", path.display());
+        }
         Ok(workloads)
     }
+    /// Gas coins for `coin_configs`, reused from `saved` if it has an account for every config
+    /// whose coin is still funded, or else paid from the pay coin, in which case no more saved
+    /// accounts are reused for the workload
+    async fn reuse_or_pay(
+        &mut self,
+        name: &str,
+        saved: &mut SavedAccounts,
+        coin_configs: &[GasCoinConfig],
+        gas_price: u64,
+        num_gas_requests: &mut u64,
+    ) -> Result<Vec<Gas>> {
+        if !saved.is_skipped() {
+            let reused = match saved.take(coin_configs) {
+                Some(accounts) => {
+                    try_join_all(
+                        accounts
+                            .iter()
+                            .zip(coin_configs)
+                            .map(|(account, config)| account.load(&self.proxy, config)),
+                    )
+                    .await
+                }
+                None => Err(anyhow!(
+                    "{} coin configs have no matching saved account",
+                    coin_configs.len()
+                )),
+            };
+            match reused {
+                Ok(gas) => return Ok(gas),
+                Err(e) => {
+                    warn!(
+                        "Not reusing the saved accounts of workload {} any further, funding new \
+                        ones: {}",
+                        name, e
+                    );
+                    saved.skip();
+                }
+            }
+        }
+        *num_gas_requests += 1;
+        self.pay_coin_configs(coin_configs, gas_price).await
+    }
+    /// Creates gas coins for `coin_configs`, returned in the same order as the configs
+    async fn pay_coin_configs(
+        &mut self,
+        coin_configs: &[GasCoinConfig],
+        gas_price: u64,
+    ) -> Result<Vec<Gas>> {
+        let (updated_primary_gas, updated_coin, mut gas_coins) =
+            self.split_coin_and_pay(coin_configs, gas_price).await?;
+        self.primary_gas = updated_primary_gas;
+        self.pay_coin = updated_coin;
+        Ok(coin_configs
+            .iter()
+            .map(|c| {
+                let (index, _) = gas_coins
+                    .iter()
+                    .find_position(|g| g.1 == c.address)
+                    .unwrap();
+                gas_coins.remove(index)
+            })
+            .collect())
+    }
     fn make_split_coin_tx(
         &self,
         split_amounts: Vec<u64>,
@@ -107,6 +373,7 @@ impl BenchmarkBank {
         keypair: &AccountKeyPair,
     ) -> Result<VerifiedTransaction> {
         let gas_price = gas_price.unwrap_or(DUMMY_GAS_PRICE);
+        let gas_budget = split_coin_gas_budget(gas_price)?;
         let split_coin = TransactionData::new_move_call(
             self.primary_gas.1,
             SUI_FRAMEWORK_OBJECT_ID,
@@ -118,7 +385,7 @@ impl BenchmarkBank {
                 CallArg::Object(ObjectArg::ImmOrOwnedObject(self.pay_coin.0)),
                 CallArg::Pure(bcs::to_bytes(&split_amounts).unwrap()),
             ],
-            500_000_000 * gas_price,
+            gas_budget,
             gas_price,
         )?;
         let verified_tx = to_sender_signed_transaction(split_coin, keypair);
@@ -131,6 +398,7 @@ impl BenchmarkBank {
     ) -> Result<UpdatedAndNewlyMintedGasCoins> {
         // split one coin into smaller coins of different amounts and send them to recipients
         let split_amounts: Vec<u64> = coin_configs.iter().map(|c| c.amount).collect();
+        total_split_amount(&split_amounts)?;
         // TODO: Instead of splitting the coin and then using pay tx to transfer it to recipients,
         // we can do both in one tx with pay_sui which will split the coin out for us before
         // transferring it to recipients
@@ -205,3 +473,158 @@ impl BenchmarkBank {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workloads::workload::MAX_GAS_FOR_TESTING;
+    use sui_types::base_types::random_object_ref;
+    use sui_types::crypto::get_key_pair;
+
+    #[test]
+    fn test_total_split_amount_overflow() {
+        assert_eq!(
+            total_split_amount(&[MAX_GAS_FOR_TESTING; 3]).unwrap(),
+            3 * MAX_GAS_FOR_TESTING
+        );
+        // A chunk of coins whose amounts add up to more than a balance can hold
+        let num_coins = u64::MAX / MAX_GAS_FOR_TESTING + 1;
+        let split_amounts = vec![MAX_GAS_FOR_TESTING; num_coins as usize];
+        let err = total_split_amount(&split_amounts).unwrap_err();
+        assert!(err.to_string().contains("overflows"), "{}", err);
+        assert!(total_split_amount(&[u64::MAX, 1]).is_err());
+    }
+
+    #[test]
+    fn test_check_required_addresses() {
+        let configs: Vec<_> = (0..3)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect();
+        // Builders reporting no addresses fund whichever addresses their configs name
+        assert!(check_required_addresses(0, &HashSet::new(), &configs).is_ok());
+        let mut required: HashSet<_> = configs.iter().map(|config| config.address).collect();
+        assert!(check_required_addresses(0, &required, &configs).is_ok());
+        required.remove(&configs[1].address);
+        let err = check_required_addresses(0, &required, &configs).unwrap_err();
+        assert!(
+            err.to_string().contains(&configs[1].address.to_string()),
+            "{}",
+            err
+        );
+    }
+
+    fn coin_configs(addresses: &[SuiAddress]) -> Vec<GasCoinConfig> {
+        addresses
+            .iter()
+            .map(|address| GasCoinConfig {
+                amount: MAX_GAS_FOR_TESTING,
+                address: *address,
+                keypair: Arc::new(get_key_pair().1),
+            })
+            .collect()
+    }
+
+    fn saved_account(address: SuiAddress) -> SavedAccount {
+        SavedAccount::without_keypair(&(random_object_ref(), address, Arc::new(get_key_pair().1)))
+    }
+
+    #[test]
+    fn test_saved_accounts_by_address() {
+        let (a, b) = (
+            SuiAddress::random_for_testing_only(),
+            SuiAddress::random_for_testing_only(),
+        );
+        let mut saved = SavedAccounts::new(
+            vec![saved_account(b), saved_account(a), saved_account(a)],
+            true,
+        );
+        // Matched by address whatever the order they were saved in
+        let taken = saved.take(&coin_configs(&[a, b])).unwrap();
+        assert_eq!(
+            taken.iter().map(SavedAccount::address).collect::<Vec<_>>(),
+            vec![a, b]
+        );
+        // Nothing is taken unless every config has an account
+        assert!(saved.take(&coin_configs(&[a, b])).is_none());
+        saved.skip();
+        assert!(saved.is_skipped());
+        assert!(saved.take(&coin_configs(&[a])).is_none());
+        // The account left over is kept
+        let remaining = saved.into_remaining();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].address(), a);
+    }
+
+    #[test]
+    fn test_saved_accounts_by_position() {
+        let addresses: Vec<_> = (0..3)
+            .map(|_| SuiAddress::random_for_testing_only())
+            .collect();
+        let mut saved = SavedAccounts::new(
+            addresses
+                .iter()
+                .map(|address| saved_account(*address))
+                .collect(),
+            false,
+        );
+        // Configs of builders creating a keypair for every config change address every run
+        let configs = coin_configs(&[
+            SuiAddress::random_for_testing_only(),
+            SuiAddress::random_for_testing_only(),
+        ]);
+        let taken = saved.take(&configs).unwrap();
+        assert_eq!(taken[1].address(), addresses[1]);
+        assert!(saved.take(&configs).is_none());
+        assert_eq!(saved.into_remaining()[0].address(), addresses[2]);
+    }
+
+    #[derive(Debug)]
+    struct NamedWorkloadBuilder {}
+
+    #[async_trait::async_trait]
+    impl WorkloadBuilder<dyn Payload> for NamedWorkloadBuilder {
+        async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+            vec![]
+        }
+        async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+            vec![]
+        }
+        async fn build(
+            &self,
+            _init_gas: Vec<Gas>,
+            _payload_gas: Vec<Gas>,
+        ) -> Box<dyn Workload<dyn Payload>> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn test_workload_name() {
+        let mut names = HashMap::new();
+        assert_eq!(
+            workload_name(&NamedWorkloadBuilder {}, &mut names),
+            "NamedWorkloadBuilder"
+        );
+        assert_eq!(
+            workload_name(&NamedWorkloadBuilder {}, &mut names),
+            "NamedWorkloadBuilder#2"
+        );
+    }
+
+    #[test]
+    fn test_split_coin_gas_budget_overflow() {
+        assert_eq!(
+            split_coin_gas_budget(DUMMY_GAS_PRICE).unwrap(),
+            500_000_000 * DUMMY_GAS_PRICE
+        );
+        let err = split_coin_gas_budget(u64::MAX).unwrap_err();
+        assert!(err.to_string().contains("overflows"), "{}", err);
+    }
+}
@@ -8,10 +8,13 @@ use prometheus::Registry;
 use rand::seq::SliceRandom;
 
 use std::sync::Arc;
+use std::time::Duration;
 use sui_benchmark::drivers::bench_driver::BenchDriver;
 use sui_benchmark::drivers::driver::Driver;
+use sui_benchmark::drivers::replay::replay_trace;
 use sui_benchmark::drivers::BenchmarkCmp;
 use sui_benchmark::drivers::BenchmarkStats;
+use sui_benchmark::drivers::{HistogramWrapper, StressStats};
 use sui_protocol_config::{ProtocolConfig, ProtocolVersion};
 
 use sui_node::metrics;
@@ -119,6 +122,15 @@ async fn main() -> Result<()> {
     let registry_clone = registry.clone();
     let handle = std::thread::spawn(move || {
         client_runtime.block_on(async move {
+            if let Some(trace) = opts.replay_trace.clone() {
+                let benchmark_stats = replay_trace(&trace, bench_setup.proxies[0].clone()).await?;
+                let stress_stats = StressStats {
+                    cpu_usage: HistogramWrapper {
+                        histogram: hdrhistogram::Histogram::<u64>::new_with_max(100, 3).unwrap(),
+                    },
+                };
+                return Ok((benchmark_stats, stress_stats));
+            }
             let workloads = WorkloadConfiguration::configure(
                 bench_setup.bank,
                 &opts,
@@ -131,7 +143,43 @@ async fn main() -> Result<()> {
             // otherwise summarized benchmark results are
             // published in the end
             let show_progress = interval.is_unbounded();
-            let driver = BenchDriver::new(opts.stat_collection_interval, stress_stat_collection);
+            let mut driver =
+                BenchDriver::new(opts.stat_collection_interval, stress_stat_collection)
+                    .with_abandoned_transactions(
+                        opts.abandon_ratio,
+                        Duration::from_millis(opts.abandon_after_ms),
+                    )
+                    .with_warmup(opts.warmup);
+            if let Some(burst_size) = opts.burst_size {
+                driver = driver.with_bursts(burst_size, opts.burst_gap);
+            }
+            if opts.validate_locally {
+                driver = driver.with_local_validation();
+            }
+            if opts.check_payload_state {
+                driver = driver.with_payload_state_checks();
+            }
+            if let Some(max_failure_pct) = opts.abort_on_failure_rate {
+                driver = driver.with_failure_gate(max_failure_pct, opts.abort_on_failure_window);
+            }
+            if let Some(think_time) = opts.think_time {
+                driver = driver.with_think_time(think_time);
+            }
+            if opts.check_funding {
+                driver = driver.with_funding_check();
+            }
+            if let Some(run_label) = opts.run_label.clone() {
+                driver = driver.with_run_label(run_label);
+            }
+            if let Some(digest_log) = opts.digest_log.clone() {
+                driver = driver.with_digest_log(digest_log);
+            }
+            if let Some(epochs) = opts.run_epochs {
+                driver = driver.with_epochs(epochs);
+            }
+            if let Some(record_trace) = opts.record_trace.clone() {
+                driver = driver.with_trace(record_trace);
+            }
             driver
                 .run(
                     bench_setup.proxies,
@@ -161,8 +209,23 @@ async fn main() -> Result<()> {
             Ok(result) => match result {
                 Ok((benchmark_stats, stress_stats)) => {
                     let benchmark_table = benchmark_stats.to_table();
-                    eprintln!("Benchmark Report:");
+                    match &benchmark_stats.run_label {
+                        Some(run_label) => eprintln!("Benchmark Report [{}]:", run_label),
+                        None => eprintln!("Benchmark Report:"),
+                    }
                     eprintln!("{}", benchmark_table);
+                    if let Some(target_table) = benchmark_stats.to_target_table() {
+                        eprintln!("Benchmark Report by Target:");
+                        eprintln!("{}", target_table);
+                    }
+                    if let Some(epoch_table) = benchmark_stats.to_epoch_table() {
+                        eprintln!("Benchmark Report by Epoch:");
+                        eprintln!("{}", epoch_table);
+                    }
+                    if let Some(workload_table) = benchmark_stats.to_workload_table() {
+                        eprintln!("Benchmark Report by Workload:");
+                        eprintln!("{}", workload_table);
+                    }
 
                     if stress_stat_collection {
                         eprintln!("Stress Performance Report:");
@@ -189,7 +252,7 @@ async fn main() -> Result<()> {
                         std::fs::write(curr_benchmark_stats_path, serialized)?;
                     }
                 }
-                Err(e) => eprintln!("{e}"),
+                Err(e) => return Err(e),
             },
             Err(e) => eprintln!("{e:?}"),
         }
@@ -18,35 +18,52 @@ use prometheus::{register_histogram_vec_with_registry, register_int_counter_with
 use prometheus::{register_int_counter_vec_with_registry, CounterVec};
 use prometheus::{GaugeVec, IntCounter};
 use rand::seq::SliceRandom;
-use tokio::sync::mpsc::Sender;
-use tokio::sync::OnceCell;
+use rand::Rng;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::sync::{watch, OnceCell};
 use tokio_util::sync::CancellationToken;
 
 use crate::drivers::driver::Driver;
+use crate::drivers::replay::write_trace;
 use crate::drivers::HistogramWrapper;
-use crate::system_state_observer::SystemStateObserver;
+use crate::system_state_observer::{SystemState, SystemStateObserver};
 use crate::workloads::payload::Payload;
 use crate::workloads::WorkloadInfo;
-use crate::ValidatorProxy;
-use std::collections::{BTreeMap, VecDeque};
-use std::sync::Arc;
+use crate::{LatencyBreakdown, ValidatorProxy};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::future::Future;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use sui_types::messages::{TransactionDataAPI, VerifiedTransaction};
+use sui_types::base_types::{EpochId, ObjectRef, TransactionDigest};
+use sui_types::gas::SuiCostTable;
+use sui_types::gas_coin::GasCoin;
+use sui_types::messages::{Transaction, TransactionDataAPI, VerifiedTransaction};
 use sysinfo::{CpuExt, System, SystemExt};
 use tokio::sync::Barrier;
 use tokio::{time, time::Instant};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use super::Interval;
-use super::{BenchmarkStats, StressStats};
+use super::{BenchmarkStats, StressStats, TargetStats};
 pub struct BenchMetrics {
     pub benchmark_duration: IntCounter,
     pub num_success: IntCounterVec,
     pub num_error: IntCounterVec,
     pub num_submitted: IntCounterVec,
+    pub num_abandoned: IntCounterVec,
+    pub num_invalid: IntCounterVec,
     pub num_in_flight: GaugeVec,
     pub latency_s: HistogramVec,
     pub latency_squared_s: CounterVec,
+    pub finality_latency_s: HistogramVec,
+    pub latency_breakdown_s: HistogramVec,
     pub validators_in_tx_cert: IntCounterVec,
     pub validators_in_effects_cert: IntCounterVec,
     pub cpu_usage: GaugeVec,
@@ -79,6 +96,20 @@ impl BenchMetrics {
                 registry,
             )
             .unwrap(),
+            num_abandoned: register_int_counter_vec_with_registry!(
+                "num_abandoned",
+                "Total number of transactions the benchmark stopped waiting for",
+                &["workload"],
+                registry,
+            )
+            .unwrap(),
+            num_invalid: register_int_counter_vec_with_registry!(
+                "num_invalid",
+                "Total number of transactions failing local validation, which were not submitted",
+                &["workload"],
+                registry,
+            )
+            .unwrap(),
             num_submitted: register_int_counter_vec_with_registry!(
                 "num_submitted",
                 "Total number of transaction submitted to sui",
@@ -108,6 +139,22 @@ impl BenchMetrics {
                 registry,
             )
             .unwrap(),
+            finality_latency_s: register_histogram_vec_with_registry!(
+                "finality_latency_s",
+                "Total time in seconds until a transaction is included in a certified checkpoint",
+                &["workload"],
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
+            latency_breakdown_s: register_histogram_vec_with_registry!(
+                "latency_breakdown_s",
+                "Time in seconds spent in every phase of executing a transaction, if the proxy reports them",
+                &["workload", "phase"],
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
             validators_in_tx_cert: register_int_counter_vec_with_registry!(
                 "validators_in_tx_cert",
                 "Number of times a validator was included in tx cert",
@@ -131,6 +178,14 @@ impl BenchMetrics {
             .unwrap(),
         }
     }
+
+    fn observe_breakdown(&self, workload: &str, breakdown: &LatencyBreakdown) {
+        for (phase, latency) in breakdown.phases() {
+            self.latency_breakdown_s
+                .with_label_values(&[workload, phase])
+                .observe(latency.as_secs_f64());
+        }
+    }
 }
 
 struct Stats {
@@ -141,8 +196,544 @@ struct Stats {
     pub bench_stats: BenchmarkStats,
 }
 
+/// Submission of transactions in bursts rather than at a steady rate, see `BenchDriver::bursts`
+#[derive(Debug, Clone, Copy)]
+pub struct BurstPacing {
+    /// Number of transactions submitted at once by all workers together
+    pub burst_size: u64,
+    /// Time without submissions between the end of a burst and the start of the next one
+    pub gap: Duration,
+}
+
+/// Distribution of the time payloads wait between the response to a transaction and their next
+/// transaction, see `BenchDriver::think_time`. Parsed from `{distribution}:{mean}`, e.g.
+/// "exponential:500ms".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThinkTime {
+    /// Uniform between zero and twice the mean
+    Uniform(Duration),
+    /// Exponential with the given mean, i.e. every payload submits as a Poisson process while
+    /// its transactions are not in flight
+    Exponential(Duration),
+}
+
+impl ThinkTime {
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Duration {
+        self.quantile(rng.gen::<f64>())
+    }
+
+    /// Think time which the fraction `q` (0.0 to 1.0, excluded) of samples are under
+    pub fn quantile(&self, q: f64) -> Duration {
+        match self {
+            ThinkTime::Uniform(mean) => mean.mul_f64(2.0 * q),
+            ThinkTime::Exponential(mean) => mean.mul_f64(-(1.0 - q).ln()),
+        }
+    }
+}
+
+impl FromStr for ThinkTime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (distribution, mean) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Expected {{distribution}}:{{mean}}, got {:?}", s))?;
+        let mean = duration_str::parse(mean).map_err(|e| e.to_string())?;
+        match distribution {
+            "uniform" => Ok(ThinkTime::Uniform(mean)),
+            "exponential" => Ok(ThinkTime::Exponential(mean)),
+            _ => Err(format!(
+                "Unknown think time distribution {:?}, expected uniform or exponential",
+                distribution
+            )),
+        }
+    }
+}
+
+/// Ending the benchmark early, with an error, once too many transactions fail, see
+/// `BenchDriver::failure_gate`
+#[derive(Debug, Clone, Copy)]
+pub struct FailureGate {
+    /// Percentage (0.0 to 100.0) of failed transactions above which the benchmark ends
+    pub max_failure_pct: f32,
+    /// Time over which the failure rate is measured, and for which the benchmark must have run
+    /// before it can end early
+    pub window: Duration,
+}
+
+/// Outcomes of the transactions of all workers over the window of the failure gate
+struct FailureWindow {
+    gate: FailureGate,
+    /// First outcome recorded
+    start: Option<Instant>,
+    /// Outcomes by second: start of the second, number of successes and failures by category
+    buckets: VecDeque<(Instant, u64, BTreeMap<String, u64>)>,
+    /// Why the gate ended the benchmark, if it did
+    tripped: Option<String>,
+}
+
+impl FailureWindow {
+    fn new(gate: FailureGate) -> Self {
+        FailureWindow {
+            gate,
+            start: None,
+            buckets: VecDeque::new(),
+            tripped: None,
+        }
+    }
+
+    /// Records the outcome of a transaction, the category of its failure if it failed, and
+    /// returns whether the failure rate over the window just went above the maximum
+    fn record(&mut self, failure: Option<&str>) -> bool {
+        if self.tripped.is_some() {
+            return false;
+        }
+        let now = Instant::now();
+        let start = *self.start.get_or_insert(now);
+        if !matches!(self.buckets.back(), Some((second, ..)) if now - *second < Duration::from_secs(1))
+        {
+            self.buckets.push_back((now, 0, BTreeMap::new()));
+        }
+        let (_, successes, failures) = self.buckets.back_mut().unwrap();
+        match failure {
+            None => *successes += 1,
+            Some(category) => *failures.entry(category.to_string()).or_default() += 1,
+        }
+        while matches!(self.buckets.front(), Some((second, ..)) if now - *second > self.gate.window)
+        {
+            self.buckets.pop_front();
+        }
+        if now - start < self.gate.window {
+            return false;
+        }
+        let mut num_successes = 0;
+        let mut by_category: BTreeMap<&str, u64> = BTreeMap::new();
+        for (_, successes, failures) in self.buckets.iter() {
+            num_successes += successes;
+            for (category, count) in failures {
+                *by_category.entry(category.as_str()).or_default() += count;
+            }
+        }
+        let num_failures: u64 = by_category.values().sum();
+        let failure_pct = 100.0 * num_failures as f32 / (num_successes + num_failures) as f32;
+        if failure_pct <= self.gate.max_failure_pct {
+            return false;
+        }
+        let mut by_category: Vec<_> = by_category.into_iter().collect();
+        by_category.sort_by(|(_, a), (_, b)| b.cmp(a));
+        self.tripped = Some(format!(
+            "{:.1}% of transactions failed over the last {:?}, more than the maximum of {}%. \
+            Failures by category: {}",
+            failure_pct,
+            self.gate.window,
+            self.gate.max_failure_pct,
+            by_category
+                .iter()
+                .map(|(category, count)| format!("{}: {}", category, count))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        true
+    }
+}
+
+/// Successes and failures of the transactions of every workload, by the name of its payloads, for
+/// checking `Workload::min_success_rate` at the end of the run
+type WorkloadOutcomes = Arc<Mutex<BTreeMap<String, (u64, u64)>>>;
+
+/// Records the outcome of a transaction of `workload`, if any workload has a minimum success rate
+fn record_outcome(outcomes: &Option<WorkloadOutcomes>, workload: String, success: bool) {
+    if let Some(outcomes) = outcomes {
+        let mut outcomes = outcomes.lock().unwrap();
+        let (successes, failures) = outcomes.entry(workload).or_default();
+        if success {
+            *successes += 1;
+        } else {
+            *failures += 1;
+        }
+    }
+}
+
+/// Description of every workload of `min_success_rates` whose transactions did not succeed often
+/// enough according to `outcomes`
+fn success_rate_breaches(
+    min_success_rates: &BTreeMap<String, f64>,
+    outcomes: &BTreeMap<String, (u64, u64)>,
+) -> Vec<String> {
+    min_success_rates
+        .iter()
+        .filter_map(|(workload, min_success_rate)| {
+            let (successes, failures) = outcomes.get(workload).copied().unwrap_or_default();
+            let total = successes + failures;
+            if total == 0 {
+                return Some(format!("{}: no transaction completed", workload));
+            }
+            let success_rate = successes as f64 / total as f64;
+            (success_rate < *min_success_rate).then(|| {
+                format!(
+                    "{}: {:.1}% of {} transactions succeeded, less than the minimum of {:.1}%",
+                    workload,
+                    100.0 * success_rate,
+                    total,
+                    100.0 * min_success_rate
+                )
+            })
+        })
+        .collect()
+}
+
+/// Category of a transaction which failed in execution for the failure gate: the variant of its
+/// execution failure status, without its details
+fn failure_category(failure_status: &str) -> &str {
+    failure_status
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()
+        .unwrap_or(failure_status)
+}
+
+/// Category of transactions which got an error rather than effects for the failure gate
+const ERROR_CATEGORY: &str = "error";
+
+/// Records an outcome in the failure window, if there is a failure gate, and ends the benchmark
+/// if it trips the gate
+fn check_failure_gate(
+    failure_window: &Option<Arc<Mutex<FailureWindow>>>,
+    failure: Option<&str>,
+    token: &CancellationToken,
+    progress: &ProgressBar,
+) {
+    if let Some(failure_window) = failure_window {
+        let mut failure_window = failure_window.lock().unwrap();
+        if failure_window.record(failure) {
+            error!(
+                "Ending the benchmark: {}",
+                failure_window.tripped.as_ref().unwrap()
+            );
+            token.cancel();
+            progress.finish_and_clear();
+        }
+    }
+}
+
+/// First payload found in an invalid state, which ends the benchmark, see
+/// `BenchDriver::check_payload_state`
+type PayloadViolation = Arc<Mutex<Option<String>>>;
+
+/// Checks the state of a payload updated with the effects of its last transaction, if payload
+/// state is checked, and ends the benchmark if it is invalid
+fn check_payload_state(
+    payload: &dyn Payload,
+    violation: &Option<PayloadViolation>,
+    token: &CancellationToken,
+    progress: &ProgressBar,
+) {
+    if let Some(violation) = violation {
+        if let Err(e) = payload.validate_state() {
+            let mut violation = violation.lock().unwrap();
+            if violation.is_none() {
+                error!(
+                    "Ending the benchmark: payload of {} is invalid: {}",
+                    payload, e
+                );
+                *violation = Some(format!("payload of {} is invalid: {}", payload, e));
+                token.cancel();
+                progress.finish_and_clear();
+            }
+        }
+    }
+}
+
+/// When a worker submits its next transaction
+enum Pacer {
+    /// At the steady rate of the worker's target qps
+    Steady(time::Interval),
+    /// `burst_size` transactions at once, then nothing for `gap`
+    Bursts {
+        burst_size: u64,
+        gap: Duration,
+        sent: u64,
+        next_burst: Instant,
+    },
+}
+
+impl Pacer {
+    /// Waits until the next transaction is due and returns whether it starts a new burst. State
+    /// is only updated once the wait is over, so the future can be dropped in `select!`.
+    async fn tick(&mut self) -> bool {
+        match self {
+            Pacer::Steady(interval) => {
+                interval.tick().await;
+                false
+            }
+            Pacer::Bursts {
+                burst_size,
+                gap,
+                sent,
+                next_burst,
+            } => {
+                if *sent == *burst_size {
+                    time::sleep_until(*next_burst).await;
+                    *sent = 0;
+                }
+                *sent += 1;
+                if *sent == *burst_size {
+                    *next_burst = Instant::now() + *gap;
+                }
+                *sent == 1
+            }
+        }
+    }
+}
+
+/// Checks done by validators on a transaction before they load its input objects, against the
+/// latest system state seen by the observer. Whether the gas coins can pay for the budget needs
+/// their balance, which is only known to validators.
+fn check_locally(
+    tx: &VerifiedTransaction,
+    system_state_observer: &SystemStateObserver,
+) -> Result<()> {
+    let state = system_state_observer.state.borrow();
+    let protocol_config = match state.protocol_config.as_ref() {
+        Some(protocol_config) => protocol_config,
+        // Nothing to check against until the observer has read the system state
+        None => return Ok(()),
+    };
+    let data = tx.data().transaction_data();
+    data.validity_check(protocol_config)?;
+    let cost_table = SuiCostTable::new(protocol_config);
+    if data.gas_budget() < cost_table.min_gas_budget()
+        || data.gas_budget() > cost_table.max_gas_budget()
+    {
+        return Err(anyhow!(
+            "Gas budget {} outside of [{}, {}]",
+            data.gas_budget(),
+            cost_table.min_gas_budget(),
+            cost_table.max_gas_budget()
+        ));
+    }
+    if data.gas_price() < state.reference_gas_price {
+        return Err(anyhow!(
+            "Gas price {} below reference gas price {}",
+            data.gas_price(),
+            state.reference_gas_price
+        ));
+    }
+    Ok(())
+}
+
+/// Transaction which failed local validation, see `make_checked_transaction`
+#[derive(Debug)]
+struct LocallyInvalid {
+    error: anyhow::Error,
+    /// Whether the payload of the transaction is to be kept, see
+    /// `Payload::handle_invalid_transaction`
+    keep_payload: bool,
+}
+
+/// Next transaction of `payload` if it passes local validation, see
+/// `BenchDriver::validate_locally`. Otherwise the payload is told about the failure, so that it
+/// does not make the same transaction again.
+fn make_checked_transaction(
+    payload: &mut dyn Payload,
+    system_state_observer: &SystemStateObserver,
+) -> Result<VerifiedTransaction, LocallyInvalid> {
+    let tx = payload.make_transaction();
+    check_locally(&tx, system_state_observer).map_err(|error| LocallyInvalid {
+        keep_payload: payload.handle_invalid_transaction(&error),
+        error,
+    })?;
+    Ok(tx)
+}
+
+/// Transaction to check the funding of before it is submitted, see `BenchDriver::check_funding`
+struct UnderFundedCheck {
+    gas: Vec<ObjectRef>,
+    proxy: Arc<dyn ValidatorProxy + Send + Sync>,
+}
+
+/// Total balance of the gas coins `gas` if it is under `min_gas`, see
+/// `BenchDriver::check_funding`. Coins which cannot be read pass the check, so that the
+/// transaction is submitted and the validators say what is wrong with it.
+async fn gas_shortfall(
+    proxy: Arc<dyn ValidatorProxy + Send + Sync>,
+    gas: Vec<ObjectRef>,
+    min_gas: u64,
+) -> Option<u64> {
+    let mut balance = 0;
+    for (id, _, _) in gas {
+        let object = proxy.get_object(id).await.ok()?;
+        balance += GasCoin::try_from(&object).ok()?.value();
+    }
+    (balance < min_gas).then_some(balance)
+}
+
+/// Reads the objects `payload` asks for after its last `make_new_payload()` call, see
+/// `Payload::objects_to_read`
+async fn read_payload_objects(
+    payload: &mut dyn Payload,
+    proxy: &Arc<dyn ValidatorProxy + Send + Sync>,
+) {
+    let ids = payload.objects_to_read();
+    if ids.is_empty() {
+        return;
+    }
+    let mut objects = Vec::with_capacity(ids.len());
+    for id in ids {
+        objects.push(match proxy.get_object(id).await {
+            Ok(object) => Some(object.compute_object_reference()),
+            Err(e) => {
+                warn!("Failed to read object {} for {}: {}", id, payload, e);
+                None
+            }
+        });
+    }
+    payload.update_objects(objects);
+}
+
+/// Digests waiting to be appended to `BenchDriver::digest_log`
+const DIGEST_LOG_CAPACITY: usize = 10_000;
+
+/// Sender of the digests to append to `BenchDriver::digest_log`, which drops and counts them
+/// while the writer has `DIGEST_LOG_CAPACITY` of them waiting
+#[derive(Clone)]
+struct DigestSender {
+    tx: Sender<(TransactionDigest, String)>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl DigestSender {
+    fn send(&self, digest: TransactionDigest, workload: String) {
+        match self.tx.try_send((digest, workload)) {
+            Err(TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            // The writer only goes away if recording failed, which it reported
+            Ok(()) | Err(TrySendError::Closed(_)) => (),
+        }
+    }
+}
+
+/// Transactions waiting to be written to `BenchDriver::trace`
+const TRACE_CAPACITY: usize = 10_000;
+
+/// Digest log and trace records of a transaction, sent before it is submitted
+struct SubmissionRecord {
+    digest: TransactionDigest,
+    workload: String,
+    digest_tx: Option<DigestSender>,
+    trace: Option<(Sender<(String, Transaction)>, Transaction)>,
+}
+
+impl SubmissionRecord {
+    async fn send(self) {
+        if let Some(digest_tx) = self.digest_tx {
+            digest_tx.send(self.digest, self.workload.clone());
+        }
+        if let Some((trace_tx, tx)) = self.trace {
+            // The writer only goes away if recording failed, which it reported
+            let _ = trace_tx.send((self.workload, tx)).await;
+        }
+    }
+}
+
+/// Submits the transaction through `execute` unless `shortfall` finds that its gas coins hold
+/// less than `min_gas`, in which case it is neither submitted nor recorded
+async fn execute_if_funded(
+    shortfall: impl Future<Output = Option<u64>>,
+    min_gas: u64,
+    record: SubmissionRecord,
+    execute: impl Future<Output = NextOp>,
+) -> NextOp {
+    match shortfall.await {
+        // Dropping the execution future before it is polled drops the payload
+        Some(balance) => NextOp::UnderFunded {
+            digest: record.digest,
+            workload: record.workload,
+            balance,
+            required: min_gas,
+        },
+        None => {
+            record.send().await;
+            execute.await
+        }
+    }
+}
+
+/// Line of `BenchDriver::digest_log`
+#[derive(Serialize)]
+struct DigestRecord<'a> {
+    run: Option<&'a str>,
+    digest: TransactionDigest,
+    workload: &'a str,
+}
+
+/// Appends a record to `file` for every digest and workload received, until all senders are gone
+fn write_digests(
+    file: File,
+    run_label: Option<String>,
+    mut rx: Receiver<(TransactionDigest, String)>,
+) {
+    let mut writer = BufWriter::new(file);
+    while let Some((digest, workload)) = rx.blocking_recv() {
+        let record = DigestRecord {
+            run: run_label.as_deref(),
+            digest,
+            workload: &workload,
+        };
+        let res = serde_json::to_writer(&mut writer, &record)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| writeln!(writer).map_err(anyhow::Error::from));
+        if let Err(e) = res {
+            error!(
+                "Failed to record digest {}, no longer recording: {}",
+                digest, e
+            );
+            return;
+        }
+    }
+    if let Err(e) = writer.flush() {
+        error!("Failed to record digests: {}", e);
+    }
+}
+
 type RetryType = Box<(VerifiedTransaction, Box<dyn Payload>)>;
 
+/// Payloads ready for their next transaction, with the time they got the response to their last
+/// one if they were thinking since
+type FreePool = Vec<(Box<dyn Payload>, Option<Instant>)>;
+
+/// Payloads thinking after the response to their last transaction, which return to the pool
+/// with the time of the response once they are done
+type Thinking = FuturesUnordered<BoxFuture<'static, (Box<dyn Payload>, Instant)>>;
+
+/// Puts a payload whose transaction got a response back in the pool, right away unless it has
+/// to think first, see `BenchDriver::think_time`
+fn return_to_pool(
+    payload: Box<dyn Payload>,
+    think_time: Option<ThinkTime>,
+    free_pool: &mut FreePool,
+    thinking: &mut Thinking,
+) {
+    let delay = payload.think_time()
+        + think_time
+            .map(|think_time| think_time.sample(&mut rand::thread_rng()))
+            .unwrap_or_default();
+    if delay.is_zero() {
+        free_pool.push((payload, None));
+    } else {
+        let responded = Instant::now();
+        thinking.push(Box::pin(
+            time::sleep(delay).map(move |_| (payload, responded)),
+        ));
+    }
+}
+
+/// Submission time and workload of transactions which are not yet included in a checkpoint
+type PendingFinality = Arc<Mutex<HashMap<TransactionDigest, (Instant, String)>>>;
+
+/// Transactions not seen in a checkpoint after this long are no longer tracked for finality
+const MAX_FINALITY_WAIT: Duration = Duration::from_secs(120);
+
 enum NextOp {
     Response {
         /// Time taken to execute the tx and produce effects
@@ -153,8 +744,32 @@ enum NextOp {
         gas_used: u64,
         /// The payload updated with the effects of the transaction
         payload: Box<dyn Payload>,
+        /// Target of the transaction, see `Payload::stats_target`
+        target: Option<String>,
+        /// Execution failure status of the transaction, if it failed
+        failure: Option<String>,
+        /// Epoch the transaction was executed in
+        epoch: EpochId,
     },
     Retry(RetryType),
+    /// The transaction was rejected and the payload does not want it to be retried
+    Rejected {
+        digest: TransactionDigest,
+        payload: Box<dyn Payload>,
+    },
+    /// The benchmark stopped waiting for the transaction and dropped its payload
+    Abandoned {
+        digest: TransactionDigest,
+        workload: String,
+    },
+    /// The gas coins could not cover the transaction, which was not submitted. Its payload is
+    /// dropped since it would never have the funds for it.
+    UnderFunded {
+        digest: TransactionDigest,
+        workload: String,
+        balance: u64,
+        required: u64,
+    },
 }
 
 async fn print_and_start_benchmark() -> &'static Instant {
@@ -175,6 +790,59 @@ pub struct BenchWorker {
 pub struct BenchDriver {
     pub stat_collection_interval: u64,
     pub stress_stat_collection: bool,
+    /// Fraction (0.0 to 1.0) of fresh transactions for which the benchmark stops waiting after
+    /// `abandon_after`. Validators have no way to cancel a submitted transaction, so abandoning
+    /// only drops the client side request: the transaction may still be executed and finalized.
+    /// Its payload is retired because the gas coin may be locked by the abandoned transaction.
+    pub abandon_ratio: f32,
+    pub abandon_after: Duration,
+    /// Time after the start of the benchmark during which workloads run normally but their
+    /// samples are left out of the reported stats
+    pub warmup: Duration,
+    /// Submit transactions in bursts instead of at the target qps of the workloads. Each worker
+    /// gets a share of the burst proportional to its target qps.
+    pub bursts: Option<BurstPacing>,
+    /// Check fresh transactions locally before submitting them, see `check_locally`.
+    /// Transactions failing the checks are counted as invalid rather than as errors and are
+    /// never submitted; their payload goes back to the pool unchanged.
+    pub validate_locally: bool,
+    /// End the benchmark with an error once the failure rate stays too high. Transactions
+    /// which failed in execution are categorized by their failure status, transactions which
+    /// got an error by the validators are all in the same category.
+    pub failure_gate: Option<FailureGate>,
+    /// Randomized time payloads wait after the response to a transaction before their next
+    /// transaction, added to the think time the payload asks for, see `Payload::think_time`.
+    /// Thinking payloads are out of the pool, so the worker may fall short of its target qps if
+    /// it does not have enough payloads to cover the think time.
+    pub think_time: Option<ThinkTime>,
+    /// Read the gas coins of fresh transactions before submitting them, for payloads which tell
+    /// the balance they need (see `Payload::min_gas_required`). Transactions the coins cannot
+    /// cover are counted as invalid and never submitted, and their payload is retired. This
+    /// costs a read per gas coin and transaction.
+    pub check_funding: bool,
+    /// Label of the run, recorded in the final stats and with every digest in `digest_log`, to
+    /// tell runs sharing a network apart. Transactions themselves are not tagged.
+    pub run_label: Option<String>,
+    /// Append a line of JSON with the run label and workload of every transaction submitted
+    /// to this file, except dry runs. Retries of a transaction are only recorded once. Up to
+    /// `DIGEST_LOG_CAPACITY` digests wait for the file, further ones are dropped and counted
+    /// rather than slow down the workers.
+    pub digest_log: Option<PathBuf>,
+    /// End the benchmark once this many epoch changes happened since it started, on top of
+    /// its own duration. Epoch changes are seen when the system state observer reads the system
+    /// state, which can be up to a minute after they happen.
+    pub epochs: Option<u64>,
+    /// Record every transaction the payloads submit to this file, except dry runs, so that the
+    /// run can be replayed against a snapshot of the network taken once the workloads were set
+    /// up, see `replay`. Funding and `init` transactions are made before the driver runs and
+    /// are not recorded. Retries of a transaction are only recorded once. A trace missing
+    /// transactions could not be replayed, so once `TRACE_CAPACITY` of them wait for the file,
+    /// transactions are held back until the writer catches up.
+    pub trace: Option<PathBuf>,
+    /// Check the state of payloads after every response, see `Payload::validate_state`, and end
+    /// the benchmark with an error on the first violation. On by default in debug builds only,
+    /// since it is meant to catch bugs of workloads while developing them.
+    pub check_payload_state: bool,
     pub start_time: Instant,
     pub token: CancellationToken,
 }
@@ -184,10 +852,75 @@ impl BenchDriver {
         BenchDriver {
             stat_collection_interval,
             stress_stat_collection,
+            abandon_ratio: 0.0,
+            abandon_after: Duration::ZERO,
+            warmup: Duration::ZERO,
+            bursts: None,
+            validate_locally: false,
+            failure_gate: None,
+            think_time: None,
+            check_funding: false,
+            run_label: None,
+            digest_log: None,
+            epochs: None,
+            trace: None,
+            check_payload_state: cfg!(debug_assertions),
             start_time: Instant::now(),
             token: CancellationToken::new(),
         }
     }
+    pub fn with_abandoned_transactions(mut self, ratio: f32, after: Duration) -> Self {
+        self.abandon_ratio = ratio;
+        self.abandon_after = after;
+        self
+    }
+    pub fn with_warmup(mut self, warmup: Duration) -> Self {
+        self.warmup = warmup;
+        self
+    }
+    pub fn with_bursts(mut self, burst_size: u64, gap: Duration) -> Self {
+        self.bursts = Some(BurstPacing { burst_size, gap });
+        self
+    }
+    pub fn with_local_validation(mut self) -> Self {
+        self.validate_locally = true;
+        self
+    }
+    pub fn with_failure_gate(mut self, max_failure_pct: f32, window: Duration) -> Self {
+        self.failure_gate = Some(FailureGate {
+            max_failure_pct,
+            window,
+        });
+        self
+    }
+    pub fn with_think_time(mut self, think_time: ThinkTime) -> Self {
+        self.think_time = Some(think_time);
+        self
+    }
+    pub fn with_funding_check(mut self) -> Self {
+        self.check_funding = true;
+        self
+    }
+    pub fn with_run_label(mut self, run_label: String) -> Self {
+        self.run_label = Some(run_label);
+        self
+    }
+    pub fn with_digest_log(mut self, path: PathBuf) -> Self {
+        self.digest_log = Some(path);
+        self
+    }
+    pub fn with_epochs(mut self, epochs: u64) -> Self {
+        self.epochs = Some(epochs);
+        self
+    }
+    pub fn with_trace(mut self, path: PathBuf) -> Self {
+        self.trace = Some(path);
+        self
+    }
+    pub fn with_payload_state_checks(mut self) -> Self {
+        self.check_payload_state = true;
+        self
+    }
     pub fn terminate(&self) {
         self.token.cancel()
     }
@@ -278,22 +1011,74 @@ impl Driver<(BenchmarkStats, StressStats)> for BenchDriver {
         let (tx, mut rx) = tokio::sync::mpsc::channel(100);
         let (stress_stat_tx, mut stress_stat_rx) = tokio::sync::mpsc::channel(100);
         let mut bench_workers = vec![];
+        let mut workload_names = vec![];
+        // Workloads of the same type share their outcomes, so the strictest minimum applies
+        let mut min_success_rates: BTreeMap<String, f64> = BTreeMap::new();
         for workload in workloads.iter() {
             let proxy = proxies
                 .choose(&mut rand::thread_rng())
                 .context("Failed to get proxy for bench driver")?;
-            bench_workers.extend(
-                self.make_workers(workload, proxy.clone(), system_state_observer.clone())
-                    .await,
-            );
+            let workers = self
+                .make_workers(workload, proxy.clone(), system_state_observer.clone())
+                .await;
+            let name = workers
+                .iter()
+                .find_map(|worker| worker.payload.first())
+                .map(|payload| payload.to_string());
+            if let (Some(min_success_rate), Some(name)) =
+                (workload.workload.min_success_rate(), name.clone())
+            {
+                let min = min_success_rates.entry(name).or_insert(min_success_rate);
+                *min = min.max(min_success_rate);
+            }
+            workload_names.push(name);
+            bench_workers.extend(workers);
         }
+        let outcomes: Option<WorkloadOutcomes> =
+            (!min_success_rates.is_empty()).then(|| Arc::new(Mutex::new(BTreeMap::new())));
         let num_workers = bench_workers.len() as u64;
         if num_workers == 0 {
             return Err(anyhow!("No workers to run benchmark!"));
         }
+        let total_target_qps: u64 = bench_workers.iter().map(|w| w.target_qps).sum();
         let stat_delay_micros = 1_000_000 * self.stat_collection_interval;
         let metrics = Arc::new(BenchMetrics::new(registry));
+        let pending_finality: PendingFinality = Arc::new(Mutex::new(HashMap::new()));
         let barrier = Arc::new(Barrier::new(num_workers as usize));
+        let (digest_tx, digest_writer) = match &self.digest_log {
+            Some(path) => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .with_context(|| format!("Failed to open digest log {}", path.display()))?;
+                let (digest_tx, digest_rx) = tokio::sync::mpsc::channel(DIGEST_LOG_CAPACITY);
+                let run_label = self.run_label.clone();
+                let writer =
+                    tokio::task::spawn_blocking(move || write_digests(file, run_label, digest_rx));
+                let digest_tx = DigestSender {
+                    tx: digest_tx,
+                    dropped: Arc::new(AtomicU64::new(0)),
+                };
+                (Some(digest_tx), Some(writer))
+            }
+            None => (None, None),
+        };
+        let (trace_tx, trace_writer) = match &self.trace {
+            Some(path) => {
+                let file = File::create(path)
+                    .with_context(|| format!("Failed to create trace {}", path.display()))?;
+                let (trace_tx, trace_rx) = tokio::sync::mpsc::channel(TRACE_CAPACITY);
+                let writer = tokio::task::spawn_blocking(move || write_trace(file, trace_rx));
+                (Some(trace_tx), Some(writer))
+            }
+            None => (None, None),
+        };
+        let failure_window = self
+            .failure_gate
+            .map(|gate| Arc::new(Mutex::new(FailureWindow::new(gate))));
+        let payload_violation: Option<PayloadViolation> =
+            self.check_payload_state.then(|| Arc::new(Mutex::new(None)));
         info!("Setting up {:?} workers...", num_workers);
         let progress = Arc::new(match run_duration {
             Interval::Count(count) => ProgressBar::new(count)
@@ -312,17 +1097,42 @@ impl Driver<(BenchmarkStats, StressStats)> for BenchDriver {
         for (i, worker) in bench_workers.into_iter().enumerate() {
             let cloned_token = self.token.clone();
             let request_delay_micros = 1_000_000 / worker.target_qps;
-            let mut free_pool = worker.payload;
+            let mut free_pool: FreePool = worker
+                .payload
+                .into_iter()
+                .map(|payload| (payload, None))
+                .collect();
+            let think_time = self.think_time;
+            let check_funding = self.check_funding;
+            let digest_tx = digest_tx.clone();
+            let trace_tx = trace_tx.clone();
             let progress_cloned = progress.clone();
             let tx_cloned = tx.clone();
             let cloned_barrier = barrier.clone();
             let metrics_cloned = metrics.clone();
+            let pending_finality_cloned = pending_finality.clone();
+            let abandon_ratio = self.abandon_ratio;
+            let abandon_after = self.abandon_after;
+            let warmup = self.warmup;
+            let validate_locally = self.validate_locally;
+            let failure_window = failure_window.clone();
+            let payload_violation = payload_violation.clone();
+            let outcomes = outcomes.clone();
+            let system_state_observer = system_state_observer.clone();
+            let bursts = self
+                .bursts
+                .map(|BurstPacing { burst_size, gap }| BurstPacing {
+                    burst_size: std::cmp::max(1, burst_size * worker.target_qps / total_target_qps),
+                    gap,
+                });
 
             let runner = tokio::spawn(async move {
                 cloned_barrier.wait().await;
                 let start_time = print_and_start_benchmark().await;
                 let mut num_success_txes = 0;
                 let mut num_error_txes = 0;
+                let mut num_abandoned_txes = 0;
+                let mut num_invalid_txes = 0;
                 let mut num_success_cmds = 0;
                 let mut num_no_gas = 0;
                 let mut num_in_flight: u64 = 0;
@@ -330,27 +1140,70 @@ impl Driver<(BenchmarkStats, StressStats)> for BenchDriver {
                 let mut total_gas_used = 0;
                 let mut latency_histogram =
                     hdrhistogram::Histogram::<u64>::new_with_max(120_000, 3).unwrap();
-                let mut request_interval =
-                    time::interval(Duration::from_micros(request_delay_micros));
-                request_interval.set_missed_tick_behavior(time::MissedTickBehavior::Burst);
+                let mut pacer = match bursts {
+                    Some(BurstPacing { burst_size, gap }) => Pacer::Bursts {
+                        burst_size,
+                        gap,
+                        sent: 0,
+                        next_burst: Instant::now(),
+                    },
+                    None => {
+                        let mut request_interval =
+                            time::interval(Duration::from_micros(request_delay_micros));
+                        request_interval.set_missed_tick_behavior(time::MissedTickBehavior::Burst);
+                        Pacer::Steady(request_interval)
+                    }
+                };
+                // Start of the latest burst, until all transactions in flight got a response
+                let mut burst_start: Option<Instant> = None;
+                let mut burst_recovery_histogram =
+                    hdrhistogram::Histogram::<u64>::new_with_max(120_000, 3).unwrap();
+                let mut num_unrecovered_bursts = 0;
+                let mut think_time_histogram =
+                    hdrhistogram::Histogram::<u64>::new_with_max(120_000, 3).unwrap();
+                let mut thinking: Thinking = FuturesUnordered::new();
+                let mut target_stats: BTreeMap<String, TargetStats> = BTreeMap::new();
+                let mut epoch_stats: BTreeMap<EpochId, TargetStats> = BTreeMap::new();
                 let mut stat_interval = time::interval(Duration::from_micros(stat_delay_micros));
                 let mut futures: FuturesUnordered<BoxFuture<NextOp>> = FuturesUnordered::new();
 
                 let mut retry_queue: VecDeque<RetryType> = VecDeque::new();
                 let mut stat_start_time: Instant = Instant::now();
+                let mut warming_up = !warmup.is_zero();
+                let warmup_done = time::sleep_until(*start_time + warmup);
+                tokio::pin!(warmup_done);
                 loop {
                     tokio::select! {
                         _ = cloned_token.cancelled() => {
                             break;
                         }
-                        _ = stat_interval.tick() => {
+                        _ = &mut warmup_done, if warming_up => {
+                            // Drop everything sampled so far, gas coins keep rotating as usual
+                            warming_up = false;
+                            num_success_txes = 0;
+                            num_error_txes = 0;
+                            num_abandoned_txes = 0;
+                            num_invalid_txes = 0;
+                            num_success_cmds = 0;
+                            num_no_gas = 0;
+                            num_submitted = 0;
+                            total_gas_used = 0;
+                            num_unrecovered_bursts = 0;
+                            stat_start_time = Instant::now();
+                            latency_histogram.reset();
+                            burst_recovery_histogram.reset();
+                            think_time_histogram.reset();
+                            target_stats.clear();
+                            epoch_stats.clear();
+                        }
+                        _ = stat_interval.tick(), if !warming_up => {
                             if tx_cloned
                                 .try_send(Stats {
                                     id: i,
                                     num_no_gas,
                                     num_in_flight,
                                     num_submitted,
-                                    bench_stats: BenchmarkStats {duration:stat_start_time.elapsed(),num_error_txes,num_success_txes,num_success_cmds,latency_ms:HistogramWrapper{histogram:latency_histogram.clone()}, total_gas_used },
+                                    bench_stats: BenchmarkStats {duration:stat_start_time.elapsed(),num_error_txes,num_success_txes,num_abandoned_txes,num_invalid_txes,num_success_cmds,latency_ms:HistogramWrapper{histogram:latency_histogram.clone()}, finality_latency_ms: HistogramWrapper::default(), total_gas_used, burst_recovery_ms: HistogramWrapper{histogram:burst_recovery_histogram.clone()}, num_unrecovered_bursts, think_time_ms: HistogramWrapper{histogram:think_time_histogram.clone()}, run_label: None, by_target: std::mem::take(&mut target_stats), by_epoch: std::mem::take(&mut epoch_stats), workload_counters: BTreeMap::new() },
                                 })
                                 .is_err()
                             {
@@ -358,29 +1211,56 @@ impl Driver<(BenchmarkStats, StressStats)> for BenchDriver {
                             }
                             num_success_txes = 0;
                             num_error_txes = 0;
+                            num_abandoned_txes = 0;
+                            num_invalid_txes = 0;
                             num_success_cmds = 0;
                             num_no_gas = 0;
                             num_submitted = 0;
+                            num_unrecovered_bursts = 0;
                             stat_start_time = Instant::now();
                             latency_histogram.reset();
+                            burst_recovery_histogram.reset();
+                            think_time_histogram.reset();
+                        }
+                        Some((payload, responded)) = thinking.next() => {
+                            free_pool.push((payload, Some(responded)));
                         }
-                        _ = request_interval.tick() => {
+                        new_burst = pacer.tick() => {
+                            if new_burst && burst_start.replace(Instant::now()).is_some() {
+                                num_unrecovered_bursts += 1;
+                            }
 
                             // If a retry is available send that
                             // (sending retries here subjects them to our rate limit)
                             if let Some(mut b) = retry_queue.pop_front() {
+                                b.0 = b.1.retry_transaction(&b.0);
                                 num_error_txes += 1;
                                 num_submitted += 1;
                                 metrics_cloned.num_submitted.with_label_values(&[&b.1.to_string()]).inc();
+                                let target = b.1.stats_target();
+                                if let Some(target) = &target {
+                                    target_stats.entry(target.clone()).or_default().num_error_txes += 1;
+                                }
+                                // Errors come without an epoch, they count towards the latest one seen
+                                epoch_stats.entry(system_state_observer.state.borrow().epoch).or_default().num_error_txes += 1;
+                                let dry_run = b.1.is_dry_run();
+                                // Finality is measured from the first submission of the transaction
+                                if !warming_up && !dry_run {
+                                    pending_finality_cloned.lock().unwrap().entry(*b.0.digest()).or_insert_with(|| (Instant::now(), b.1.to_string()));
+                                }
                                 let metrics_cloned = metrics_cloned.clone();
                                 // TODO: clone committee for each request is not ideal.
                                 let committee_cloned = Arc::new(worker.proxy.clone_committee());
+                                let proxy = worker.proxy.clone();
                                 let start = Arc::new(Instant::now());
-                                let res = worker.proxy
-                                    .execute_transaction_block(b.0.clone().into())
+                                let res = if dry_run {
+                                    worker.proxy.dry_run_transaction_block(b.0.clone().into()).map(|res| res.map(|effects| (effects, None))).boxed()
+                                } else {
+                                    worker.proxy.execute_transaction_block_with_breakdown(b.0.clone().into())
+                                }
                                     .then(|res| async move  {
                                         match res {
-                                            Ok(effects) => {
+                                            Ok((effects, breakdown)) => {
                                                 let latency = start.elapsed();
                                                 let time_from_start = start_time.elapsed();
 
@@ -391,6 +1271,9 @@ impl Driver<(BenchmarkStats, StressStats)> for BenchDriver {
                                                 let square_latency_ms = latency.as_secs_f64().powf(2.0);
                                                 metrics_cloned.latency_s.with_label_values(&[&b.1.to_string()]).observe(latency.as_secs_f64());
                                                 metrics_cloned.latency_squared_s.with_label_values(&[&b.1.to_string()]).inc_by(square_latency_ms);
+                                                if let Some(breakdown) = breakdown {
+                                                    metrics_cloned.observe_breakdown(&b.1.to_string(), &breakdown);
+                                                }
 
                                                 metrics_cloned.num_success.with_label_values(&[&b.1.to_string()]).inc();
                                                 metrics_cloned.num_in_flight.with_label_values(&[&b.1.to_string()]).dec();
@@ -401,12 +1284,20 @@ impl Driver<(BenchmarkStats, StressStats)> for BenchDriver {
                                                 }
                                                 let num_commands = b.0.data().transaction_data().kind().num_commands() as u16;
                                                 b.1.make_new_payload(&effects);
-                                                NextOp::Response {latency,num_commands,payload:b.1, gas_used: effects.gas_used() }
+                                                read_payload_objects(b.1.as_mut(), &proxy).await;
+                                                let gas_used = if dry_run { 0 } else { effects.gas_used() };
+                                                let failure = effects.failure_status();
+                                                let epoch = effects.executed_epoch();
+                                                NextOp::Response {latency,num_commands,payload:b.1, gas_used, target, failure, epoch }
                                             }
                                             Err(err) => {
                                                 error!("{}", err);
                                                 metrics_cloned.num_error.with_label_values(&[&b.1.to_string()]).inc();
-                                                NextOp::Retry(b)
+                                                if b.1.handle_error(&err) {
+                                                    NextOp::Retry(b)
+                                                } else {
+                                                    NextOp::Rejected { digest: *b.0.digest(), payload: b.1 }
+                                                }
                                             }
                                         }
                                     });
@@ -418,21 +1309,62 @@ impl Driver<(BenchmarkStats, StressStats)> for BenchDriver {
                             if free_pool.is_empty() {
                                 num_no_gas += 1;
                             } else {
-                                let mut payload = free_pool.pop().unwrap();
+                                let (mut payload, responded) = free_pool.pop().unwrap();
+                                let tx = if validate_locally {
+                                    match make_checked_transaction(payload.as_mut(), &system_state_observer) {
+                                        Ok(tx) => tx,
+                                        Err(LocallyInvalid { error, keep_payload }) => {
+                                            error!("Transaction of {} failed local validation: {}", payload, error);
+                                            num_invalid_txes += 1;
+                                            metrics_cloned.num_invalid.with_label_values(&[&payload.to_string()]).inc();
+                                            if keep_payload {
+                                                free_pool.push((payload, responded));
+                                            } else {
+                                                warn!("Retiring payload of {} after its invalid transaction", payload);
+                                            }
+                                            continue
+                                        }
+                                    }
+                                } else {
+                                    payload.make_transaction()
+                                };
+                                if let Some(responded) = responded {
+                                    think_time_histogram.saturating_record(responded.elapsed().as_millis().try_into().unwrap());
+                                }
                                 num_in_flight += 1;
                                 num_submitted += 1;
                                 metrics_cloned.num_in_flight.with_label_values(&[&payload.to_string()]).inc();
                                 metrics_cloned.num_submitted.with_label_values(&[&payload.to_string()]).inc();
-                                let tx = payload.make_transaction();
+                                let target = payload.stats_target();
                                 let start = Arc::new(Instant::now());
+                                let dry_run = payload.is_dry_run();
+                                if !warming_up && !dry_run {
+                                    pending_finality_cloned.lock().unwrap().insert(*tx.digest(), (*start, payload.to_string()));
+                                }
+                                let record = SubmissionRecord {
+                                    digest: *tx.digest(),
+                                    workload: payload.to_string(),
+                                    digest_tx: digest_tx.clone().filter(|_| !dry_run),
+                                    trace: trace_tx.clone().filter(|_| !dry_run).map(|trace_tx| (trace_tx, tx.clone().into())),
+                                };
+                                let abandoned = (abandon_ratio > 0.0 && rand::thread_rng().gen::<f32>() < abandon_ratio).then(|| NextOp::Abandoned { digest: *tx.digest(), workload: payload.to_string() });
+                                let min_gas = if check_funding { payload.min_gas_required() } else { 0 };
+                                let funding_check = (min_gas > 0).then(|| UnderFundedCheck {
+                                    gas: tx.data().transaction_data().gas().to_vec(),
+                                    proxy: worker.proxy.clone(),
+                                });
                                 let metrics_cloned = metrics_cloned.clone();
                                 // TODO: clone committee for each request is not ideal.
                                 let committee_cloned = Arc::new(worker.proxy.clone_committee());
-                                let res = worker.proxy
-                                    .execute_transaction_block(tx.clone().into())
+                                let proxy = worker.proxy.clone();
+                                let res = if dry_run {
+                                    worker.proxy.dry_run_transaction_block(tx.clone().into()).map(|res| res.map(|effects| (effects, None))).boxed()
+                                } else {
+                                    worker.proxy.execute_transaction_block_with_breakdown(tx.clone().into())
+                                }
                                 .then(|res| async move {
                                     match res {
-                                        Ok(effects) => {
+                                        Ok((effects, breakdown)) => {
                                             let latency = start.elapsed();
                                             let time_from_start = start_time.elapsed();
 
@@ -443,6 +1375,9 @@ impl Driver<(BenchmarkStats, StressStats)> for BenchDriver {
                                             let square_latency_ms = latency.as_secs_f64().powf(2.0);
                                             metrics_cloned.latency_s.with_label_values(&[&payload.to_string()]).observe(latency.as_secs_f64());
                                             metrics_cloned.latency_squared_s.with_label_values(&[&payload.to_string()]).inc_by(square_latency_ms);
+                                            if let Some(breakdown) = breakdown {
+                                                metrics_cloned.observe_breakdown(&payload.to_string(), &breakdown);
+                                            }
 
                                             metrics_cloned.num_success.with_label_values(&[&payload.to_string()]).inc();
                                             metrics_cloned.num_in_flight.with_label_values(&[&payload.to_string()]).dec();
@@ -450,46 +1385,127 @@ impl Driver<(BenchmarkStats, StressStats)> for BenchDriver {
                                             // auth_sign_info.authorities(&committee_cloned).for_each(|name| metrics_cloned.validators_in_tx_cert.with_label_values(&[&name.unwrap().to_string()]).inc());
                                             if let Some(sig_info) = effects.quorum_sig() { sig_info.authorities(&committee_cloned).for_each(|name| metrics_cloned.validators_in_effects_cert.with_label_values(&[&name.unwrap().to_string()]).inc()) }
                                             payload.make_new_payload(&effects);
+                                            read_payload_objects(payload.as_mut(), &proxy).await;
                                             let num_commands = tx.data().transaction_data().kind().num_commands() as u16;
-                                            NextOp::Response {latency,num_commands,payload, gas_used: effects.gas_used() }
+                                            let gas_used = if dry_run { 0 } else { effects.gas_used() };
+                                            let failure = effects.failure_status();
+                                            let epoch = effects.executed_epoch();
+                                            NextOp::Response {latency,num_commands,payload, gas_used, target, failure, epoch }
                                         }
                                         Err(err) => {
-                                            error!("Retry due to error: {}", err);
                                             metrics_cloned.num_error.with_label_values(&[&payload.to_string()]).inc();
-                                            NextOp::Retry(Box::new((tx, payload)))
+                                            if payload.handle_error(&err) {
+                                                error!("Retry due to error: {}", err);
+                                                NextOp::Retry(Box::new((tx, payload)))
+                                            } else {
+                                                NextOp::Rejected { digest: *tx.digest(), payload }
+                                            }
                                         }
                                     }
                                 });
-                                futures.push(Box::pin(res));
+                                let res = match funding_check {
+                                    Some(UnderFundedCheck { gas, proxy }) => execute_if_funded(gas_shortfall(proxy, gas, min_gas), min_gas, record, res).boxed(),
+                                    None => async move {
+                                        record.send().await;
+                                        res.await
+                                    }.boxed(),
+                                };
+                                match abandoned {
+                                    // Dropping the request future on timeout is all the cancellation there is
+                                    Some(abandoned) => futures.push(Box::pin(time::timeout(abandon_after, res).map(|res| res.unwrap_or(abandoned)))),
+                                    None => futures.push(Box::pin(res)),
+                                }
                             }
                         }
                         Some(op) = futures.next() => {
                             match op {
                                 NextOp::Retry(b) => {
+                                    if !warming_up {
+                                        check_failure_gate(&failure_window, Some(ERROR_CATEGORY), &cloned_token, &progress_cloned);
+                                        record_outcome(&outcomes, b.1.to_string(), false);
+                                    }
                                     retry_queue.push_back(b);
                                     BenchDriver::update_progress(*start_time, run_duration, total_gas_used, progress_cloned.clone());
                                     if progress_cloned.is_finished() {
                                         break;
                                     }
                                 }
-                                NextOp::Response { latency, num_commands, payload, gas_used } => {
+                                NextOp::Rejected { digest, payload } => {
+                                    num_error_txes += 1;
+                                    if let Some(target) = payload.stats_target() {
+                                        target_stats.entry(target).or_default().num_error_txes += 1;
+                                    }
+                                    epoch_stats.entry(system_state_observer.state.borrow().epoch).or_default().num_error_txes += 1;
+                                    num_in_flight -= 1;
+                                    metrics_cloned.num_in_flight.with_label_values(&[&payload.to_string()]).dec();
+                                    pending_finality_cloned.lock().unwrap().remove(&digest);
+                                    if !warming_up {
+                                        check_failure_gate(&failure_window, Some(ERROR_CATEGORY), &cloned_token, &progress_cloned);
+                                        record_outcome(&outcomes, payload.to_string(), false);
+                                    }
+                                    return_to_pool(payload, think_time, &mut free_pool, &mut thinking);
+                                    BenchDriver::update_progress(*start_time, run_duration, total_gas_used, progress_cloned.clone());
+                                    if progress_cloned.is_finished() {
+                                        break;
+                                    }
+                                }
+                                NextOp::UnderFunded { digest, workload, balance, required } => {
+                                    error!("Transaction of {} not submitted, its gas coins hold {} MIST of the {} it needs", workload, balance, required);
+                                    num_invalid_txes += 1;
+                                    num_in_flight -= 1;
+                                    metrics_cloned.num_invalid.with_label_values(&[&workload]).inc();
+                                    metrics_cloned.num_in_flight.with_label_values(&[&workload]).dec();
+                                    pending_finality_cloned.lock().unwrap().remove(&digest);
+                                }
+                                NextOp::Abandoned { digest, workload } => {
+                                    num_abandoned_txes += 1;
+                                    num_in_flight -= 1;
+                                    metrics_cloned.num_abandoned.with_label_values(&[&workload]).inc();
+                                    metrics_cloned.num_in_flight.with_label_values(&[&workload]).dec();
+                                    pending_finality_cloned.lock().unwrap().remove(&digest);
+                                    BenchDriver::update_progress(*start_time, run_duration, total_gas_used, progress_cloned.clone());
+                                    if progress_cloned.is_finished() {
+                                        break;
+                                    }
+                                }
+                                NextOp::Response { latency, num_commands, payload, gas_used, target, failure, epoch } => {
+                                    if !warming_up {
+                                        check_failure_gate(&failure_window, failure.as_deref().map(failure_category), &cloned_token, &progress_cloned);
+                                        record_outcome(&outcomes, payload.to_string(), failure.is_none());
+                                    }
+                                    check_payload_state(payload.as_ref(), &payload_violation, &cloned_token, &progress_cloned);
                                     num_success_txes += 1;
                                     num_success_cmds += num_commands as u64;
                                     num_in_flight -= 1;
                                     total_gas_used += gas_used;
-                                    free_pool.push(payload);
+                                    return_to_pool(payload, think_time, &mut free_pool, &mut thinking);
                                     latency_histogram.saturating_record(latency.as_millis().try_into().unwrap());
+                                    if let Some(target) = target {
+                                        let stats = target_stats.entry(target).or_default();
+                                        stats.num_success_txes += 1;
+                                        stats.latency_ms.histogram.saturating_record(latency.as_millis().try_into().unwrap());
+                                    }
+                                    let stats = epoch_stats.entry(epoch).or_default();
+                                    stats.num_success_txes += 1;
+                                    stats.latency_ms.histogram.saturating_record(latency.as_millis().try_into().unwrap());
                                     BenchDriver::update_progress(*start_time, run_duration, total_gas_used, progress_cloned.clone());
                                     if progress_cloned.is_finished() {
                                         break;
                                     }
                                 }
                             }
+                            if num_in_flight == 0 {
+                                if let Some(burst_start) = burst_start.take() {
+                                    burst_recovery_histogram.saturating_record(burst_start.elapsed().as_millis().try_into().unwrap());
+                                }
+                            }
                         }
                     }
                 }
-                // send stats one last time
-                if tx_cloned
+                // send stats one last time, unless the benchmark ended during warmup
+                if warming_up {
+                    debug!("Benchmark ended during warmup, no stats to report");
+                } else if tx_cloned
                     .try_send(Stats {
                         id: i,
                         num_no_gas,
@@ -499,11 +1515,25 @@ impl Driver<(BenchmarkStats, StressStats)> for BenchDriver {
                             duration: stat_start_time.elapsed(),
                             num_error_txes,
                             num_success_txes,
+                            num_abandoned_txes,
+                            num_invalid_txes,
                             num_success_cmds,
                             total_gas_used,
                             latency_ms: HistogramWrapper {
                                 histogram: latency_histogram,
                             },
+                            finality_latency_ms: HistogramWrapper::default(),
+                            burst_recovery_ms: HistogramWrapper {
+                                histogram: burst_recovery_histogram,
+                            },
+                            num_unrecovered_bursts,
+                            think_time_ms: HistogramWrapper {
+                                histogram: think_time_histogram,
+                            },
+                            run_label: None,
+                            by_target: target_stats,
+                            by_epoch: epoch_stats,
+                            workload_counters: BTreeMap::new(),
                         },
                     })
                     .is_err()
@@ -514,16 +1544,27 @@ impl Driver<(BenchmarkStats, StressStats)> for BenchDriver {
             tasks.push(runner);
         }
 
+        let warmup = self.warmup;
         let benchmark_stat_task = tokio::spawn(async move {
             let mut benchmark_stat = BenchmarkStats {
                 duration: Duration::ZERO,
                 num_error_txes: 0,
                 num_success_txes: 0,
+                num_abandoned_txes: 0,
+                num_invalid_txes: 0,
                 num_success_cmds: 0,
                 total_gas_used: 0,
                 latency_ms: HistogramWrapper {
                     histogram: hdrhistogram::Histogram::<u64>::new_with_max(120_000, 3).unwrap(),
                 },
+                finality_latency_ms: HistogramWrapper::default(),
+                burst_recovery_ms: HistogramWrapper::default(),
+                num_unrecovered_bursts: 0,
+                think_time_ms: HistogramWrapper::default(),
+                run_label: None,
+                by_target: BTreeMap::new(),
+                by_epoch: BTreeMap::new(),
+                workload_counters: BTreeMap::new(),
             };
             let mut stat_collection: BTreeMap<usize, Stats> = BTreeMap::new();
             let mut counter = 0;
@@ -539,7 +1580,10 @@ impl Driver<(BenchmarkStats, StressStats)> for BenchDriver {
                 },
             ) = rx.recv().await
             {
-                benchmark_stat.update(start.elapsed(), &sample_stat.bench_stats);
+                benchmark_stat.update(
+                    start.elapsed().saturating_sub(warmup),
+                    &sample_stat.bench_stats,
+                );
                 stat_collection.insert(id, sample_stat);
                 let mut total_qps: f32 = 0.0;
                 let mut total_cps: f32 = 0.0;
@@ -584,6 +1628,14 @@ impl Driver<(BenchmarkStats, StressStats)> for BenchDriver {
         });
         drop(tx);
 
+        let finality_token = CancellationToken::new();
+        let finality_task = finality_tracker(
+            proxies[0].clone(),
+            pending_finality,
+            metrics.clone(),
+            finality_token.clone(),
+        );
+
         if self.stress_stat_collection {
             tasks.push(stress_stats_collector(
                 progress.clone(),
@@ -592,6 +1644,17 @@ impl Driver<(BenchmarkStats, StressStats)> for BenchDriver {
             ));
         }
         drop(stress_stat_tx);
+        // Not joined with the other tasks, which may end before the epochs have passed
+        let epochs_token = CancellationToken::new();
+        let epochs_task = self.epochs.map(|epochs| {
+            end_after_epochs(
+                epochs,
+                system_state_observer.state.clone(),
+                self.token.clone(),
+                epochs_token.clone(),
+                progress.clone(),
+            )
+        });
 
         let stress_stat_task = tokio::spawn(async move {
             let mut stress_stat = StressStats {
@@ -634,8 +1697,77 @@ impl Driver<(BenchmarkStats, StressStats)> for BenchDriver {
             }
             res = all_tasks => res.unwrap().into_iter().collect()
         };
-        let benchmark_stat = benchmark_stat_task.await.unwrap();
+        finality_token.cancel();
+        epochs_token.cancel();
+        if let Some(epochs_task) = epochs_task {
+            epochs_task.await.unwrap();
+        }
+        let mut benchmark_stat = benchmark_stat_task.await.unwrap();
+        benchmark_stat.finality_latency_ms = finality_task.await.unwrap();
         let stress_stat = stress_stat_task.await.unwrap();
+        benchmark_stat.run_label = self.run_label.clone();
+        for (workload, name) in workloads.iter().zip(workload_names) {
+            if let Some(name) = name {
+                for (counter, value) in workload.workload.counters() {
+                    *benchmark_stat
+                        .workload_counters
+                        .entry(name.clone())
+                        .or_default()
+                        .entry(counter)
+                        .or_default() += value;
+                }
+            }
+        }
+        // Workers are done with their senders, so the writer ends once it recorded everything
+        if let Some(digest_tx) = digest_tx {
+            let dropped = digest_tx.dropped.load(Ordering::Relaxed);
+            if dropped > 0 {
+                warn!(
+                    "{} digests were not recorded, the digest log fell behind the workers",
+                    dropped
+                );
+            }
+        }
+        if let Some(digest_writer) = digest_writer {
+            digest_writer.await.unwrap();
+        }
+        drop(trace_tx);
+        if let Some(trace_writer) = trace_writer {
+            trace_writer.await.unwrap();
+        }
+        if let Some(think_time) = self.think_time {
+            let achieved = &benchmark_stat.think_time_ms.histogram;
+            let quantiles = [0.1, 0.5, 0.9, 0.99];
+            info!(
+                "Think time (ms, p10/p50/p90/p99) of {:?}: configured {}, achieved {}",
+                think_time,
+                quantiles
+                    .iter()
+                    .map(|q| think_time.quantile(*q).as_millis().to_string())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                quantiles
+                    .iter()
+                    .map(|q| achieved.value_at_quantile(*q).to_string())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+            );
+        }
+        if let Some(tripped) = failure_window.and_then(|w| w.lock().unwrap().tripped.take()) {
+            return Err(anyhow!("Benchmark ended early: {}", tripped));
+        }
+        if let Some(violation) = payload_violation.and_then(|v| v.lock().unwrap().take()) {
+            return Err(anyhow!("Benchmark ended early: {}", violation));
+        }
+        if let Some(outcomes) = outcomes {
+            let breaches = success_rate_breaches(&min_success_rates, &outcomes.lock().unwrap());
+            if !breaches.is_empty() {
+                return Err(anyhow!(
+                    "Workloads below their minimum success rate: {}",
+                    breaches.join("; ")
+                ));
+            }
+        }
         Ok((benchmark_stat, stress_stat))
     }
 }
@@ -683,3 +1815,369 @@ fn stress_stats_collector(
         }
     })
 }
+
+/// Ends the benchmark once the epoch of `state` is `epochs` past the one it started in, see
+/// `BenchDriver::epochs`. Stops without ending it once `done` is cancelled, when the benchmark
+/// ended before the epochs passed.
+fn end_after_epochs(
+    epochs: u64,
+    mut state: watch::Receiver<SystemState>,
+    token: CancellationToken,
+    done: CancellationToken,
+    progress: Arc<ProgressBar>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut start_epoch = None;
+        loop {
+            let epoch = {
+                let state = state.borrow_and_update();
+                // The epoch is not known until the observer has read the system state
+                state.protocol_config.as_ref().map(|_| state.epoch)
+            };
+            if let Some(epoch) = epoch {
+                let start_epoch = *start_epoch.get_or_insert(epoch);
+                if epoch >= start_epoch + epochs {
+                    info!(
+                        "Ending the benchmark at epoch {}, {} epochs after it started",
+                        epoch, epochs
+                    );
+                    token.cancel();
+                    progress.finish_and_clear();
+                    break;
+                }
+            }
+            tokio::select! {
+                _ = token.cancelled() => break,
+                _ = done.cancelled() => break,
+                changed = state.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Follows certified checkpoints and records, for every transaction in `pending`, the time from
+/// its submission until it is included in a checkpoint.
+fn finality_tracker(
+    proxy: Arc<dyn ValidatorProxy + Send + Sync>,
+    pending: PendingFinality,
+    metrics: Arc<BenchMetrics>,
+    token: CancellationToken,
+) -> tokio::task::JoinHandle<HistogramWrapper> {
+    tokio::spawn(async move {
+        let mut finality_histogram = HistogramWrapper::default();
+        let mut next_checkpoint = None;
+        let mut poll_interval = time::interval(Duration::from_millis(200));
+        poll_interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = token.cancelled() => break,
+                _ = poll_interval.tick() => (),
+            }
+            // Catch up with all checkpoints certified since the last poll
+            loop {
+                match proxy.get_checkpoint_transactions(next_checkpoint).await {
+                    Ok(Some((sequence_number, digests))) => {
+                        let now = Instant::now();
+                        let mut pending = pending.lock().unwrap();
+                        for digest in digests {
+                            if let Some((submitted, workload)) = pending.remove(&digest) {
+                                let latency = now - submitted;
+                                metrics
+                                    .finality_latency_s
+                                    .with_label_values(&[&workload])
+                                    .observe(latency.as_secs_f64());
+                                finality_histogram
+                                    .histogram
+                                    .saturating_record(latency.as_millis().try_into().unwrap());
+                            }
+                        }
+                        pending.retain(|_, (submitted, _)| now - *submitted < MAX_FINALITY_WAIT);
+                        next_checkpoint = Some(sequence_number + 1);
+                    }
+                    Ok(None) => break,
+                    Err(err) => {
+                        debug!("Failed to read checkpoint {:?}: {}", next_checkpoint, err);
+                        break;
+                    }
+                }
+            }
+        }
+        finality_histogram
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ExecutionEffects;
+    use sui_core::test_utils::make_transfer_sui_transaction;
+    use sui_protocol_config::ProtocolConfig;
+    use sui_types::base_types::{random_object_ref, SuiAddress};
+    use sui_types::crypto::{get_key_pair, AccountKeyPair};
+    use sui_types::messages::TransactionData;
+    use sui_types::utils::to_sender_signed_transaction;
+    use tokio::sync::mpsc::channel;
+
+    type DigestRx = Receiver<(TransactionDigest, String)>;
+    type TraceRx = Receiver<(String, Transaction)>;
+
+    fn make_record() -> (SubmissionRecord, DigestRx, TraceRx) {
+        let (sender, keypair) = get_key_pair();
+        let tx = make_transfer_sui_transaction(
+            random_object_ref(),
+            SuiAddress::random_for_testing_only(),
+            None,
+            sender,
+            &keypair,
+            None,
+        );
+        let (digest_tx, digest_rx) = channel(DIGEST_LOG_CAPACITY);
+        let (trace_tx, trace_rx) = channel(TRACE_CAPACITY);
+        let record = SubmissionRecord {
+            digest: *tx.digest(),
+            workload: "transfer_object".to_string(),
+            digest_tx: Some(DigestSender {
+                tx: digest_tx,
+                dropped: Arc::new(AtomicU64::new(0)),
+            }),
+            trace: Some((trace_tx, tx.into())),
+        };
+        (record, digest_rx, trace_rx)
+    }
+
+    fn submitted(record: &SubmissionRecord) -> NextOp {
+        NextOp::Abandoned {
+            digest: record.digest,
+            workload: record.workload.clone(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_under_funded_transaction_is_not_recorded() {
+        let (record, mut digest_rx, mut trace_rx) = make_record();
+        let digest = record.digest;
+        let op = execute_if_funded(async { Some(10) }, 100, record, async {
+            panic!("Under funded transaction submitted")
+        })
+        .await;
+        assert!(matches!(
+            op,
+            NextOp::UnderFunded { digest: d, balance: 10, required: 100, .. } if d == digest
+        ));
+        // The senders went away with the record, without sending anything
+        assert!(digest_rx.recv().await.is_none());
+        assert!(trace_rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_funded_transaction_is_recorded() {
+        let (record, mut digest_rx, mut trace_rx) = make_record();
+        let digest = record.digest;
+        let execute = submitted(&record);
+        let op = execute_if_funded(async { None }, 100, record, async { execute }).await;
+        assert!(matches!(op, NextOp::Abandoned { digest: d, .. } if d == digest));
+        assert_eq!(
+            digest_rx.recv().await,
+            Some((digest, "transfer_object".to_string()))
+        );
+        let (workload, tx) = trace_rx.recv().await.unwrap();
+        assert_eq!(workload, "transfer_object");
+        assert_eq!(*tx.digest(), digest);
+        assert!(digest_rx.recv().await.is_none());
+    }
+
+    #[test]
+    fn test_digest_log_drops_digests_it_cannot_keep_up_with() {
+        let (tx, mut rx) = channel(2);
+        let digest_tx = DigestSender {
+            tx,
+            dropped: Arc::new(AtomicU64::new(0)),
+        };
+        let digests: Vec<_> = (0..5).map(|_| TransactionDigest::random()).collect();
+        for digest in &digests {
+            digest_tx.send(*digest, "transfer_object".to_string());
+        }
+        assert_eq!(digest_tx.dropped.load(Ordering::Relaxed), 3);
+        // The first digests are kept, in order
+        assert_eq!(rx.try_recv().unwrap().0, digests[0]);
+        assert_eq!(rx.try_recv().unwrap().0, digests[1]);
+        assert!(rx.try_recv().is_err());
+        // A writer which stopped recording does not count as falling behind
+        drop(rx);
+        digest_tx.send(digests[0], "transfer_object".to_string());
+        assert_eq!(digest_tx.dropped.load(Ordering::Relaxed), 3);
+    }
+
+    /// Payload transferring its gas coin back to its sender at the reference gas price, caching
+    /// its transaction until it sees effects like delegation payloads do
+    #[derive(Debug)]
+    struct CachingPayload {
+        gas: ObjectRef,
+        sender: SuiAddress,
+        keypair: AccountKeyPair,
+        last_tx: Option<VerifiedTransaction>,
+        keep_invalid: bool,
+        system_state_observer: Arc<SystemStateObserver>,
+    }
+
+    impl CachingPayload {
+        fn new(keep_invalid: bool, system_state_observer: Arc<SystemStateObserver>) -> Self {
+            let (sender, keypair) = get_key_pair();
+            CachingPayload {
+                gas: random_object_ref(),
+                sender,
+                keypair,
+                last_tx: None,
+                keep_invalid,
+                system_state_observer,
+            }
+        }
+
+        fn make_transaction_at(&self, gas_price: u64) -> VerifiedTransaction {
+            let state = self.system_state_observer.state.borrow();
+            let cost_table = SuiCostTable::new(state.protocol_config.as_ref().unwrap());
+            let data = TransactionData::new_transfer_sui(
+                self.sender,
+                self.sender,
+                None,
+                self.gas,
+                cost_table.max_gas_budget(),
+                gas_price,
+            );
+            to_sender_signed_transaction(data, &self.keypair)
+        }
+    }
+
+    impl std::fmt::Display for CachingPayload {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "caching")
+        }
+    }
+
+    impl Payload for CachingPayload {
+        fn make_new_payload(&mut self, _effects: &ExecutionEffects) {
+            self.last_tx = None;
+        }
+
+        fn make_transaction(&mut self) -> VerifiedTransaction {
+            let gas_price = self
+                .system_state_observer
+                .state
+                .borrow()
+                .reference_gas_price;
+            if self.last_tx.is_none() {
+                self.last_tx = Some(self.make_transaction_at(gas_price));
+            }
+            self.last_tx.clone().unwrap()
+        }
+
+        fn handle_invalid_transaction(&mut self, _error: &anyhow::Error) -> bool {
+            self.last_tx = None;
+            self.keep_invalid
+        }
+    }
+
+    #[test]
+    fn test_payload_recovers_from_invalid_cached_transaction() {
+        let observer = Arc::new(SystemStateObserver::new_for_testing(epoch_state(1)));
+        let mut payload = CachingPayload::new(true, observer.clone());
+        // Made before the reference gas price went up, e.g. in the previous epoch
+        payload.last_tx = Some(payload.make_transaction_at(500));
+        let invalid = make_checked_transaction(&mut payload, &observer).unwrap_err();
+        assert!(invalid.keep_payload);
+        assert!(invalid
+            .error
+            .to_string()
+            .contains("below reference gas price"));
+        // The cached transaction was dropped, so the next one is made at the current price
+        let tx = make_checked_transaction(&mut payload, &observer).unwrap();
+        assert_eq!(tx.data().transaction_data().gas_price(), 1000);
+        assert!(make_checked_transaction(&mut payload, &observer).is_ok());
+    }
+
+    #[test]
+    fn test_payload_can_retire_after_invalid_transaction() {
+        let observer = Arc::new(SystemStateObserver::new_for_testing(epoch_state(1)));
+        let mut payload = CachingPayload::new(false, observer.clone());
+        payload.last_tx = Some(payload.make_transaction_at(500));
+        let invalid = make_checked_transaction(&mut payload, &observer).unwrap_err();
+        assert!(!invalid.keep_payload);
+    }
+
+    fn epoch_state(epoch: u64) -> SystemState {
+        SystemState {
+            epoch,
+            reference_gas_price: 1000,
+            protocol_config: Some(ProtocolConfig::get_for_max_version()),
+            active_validators: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_end_after_epochs_stops_when_the_duration_ends_first() {
+        let (state_tx, state_rx) = watch::channel(epoch_state(0));
+        let token = CancellationToken::new();
+        let done = CancellationToken::new();
+        let task = end_after_epochs(
+            2,
+            state_rx,
+            token.clone(),
+            done.clone(),
+            Arc::new(ProgressBar::hidden()),
+        );
+        // The observer keeps publishing the epoch the benchmark started in
+        state_tx.send(epoch_state(0)).unwrap();
+        state_tx.send(epoch_state(1)).unwrap();
+        done.cancel();
+        time::timeout(Duration::from_secs(10), task)
+            .await
+            .expect("Epoch task outlived the benchmark")
+            .unwrap();
+        assert!(!token.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_end_after_epochs_ends_the_benchmark() {
+        let (state_tx, state_rx) = watch::channel(epoch_state(3));
+        let token = CancellationToken::new();
+        let task = end_after_epochs(
+            2,
+            state_rx,
+            token.clone(),
+            CancellationToken::new(),
+            Arc::new(ProgressBar::hidden()),
+        );
+        state_tx.send(epoch_state(4)).unwrap();
+        state_tx.send(epoch_state(5)).unwrap();
+        time::timeout(Duration::from_secs(10), task)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(token.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_trace_holds_transactions_back_until_the_writer_catches_up() {
+        let (first, _, _) = make_record();
+        let (second, _, _) = make_record();
+        let (trace_tx, mut trace_rx) = channel(1);
+        let with_trace = |record: SubmissionRecord| SubmissionRecord {
+            digest_tx: None,
+            trace: record.trace.map(|(_, tx)| (trace_tx.clone(), tx)),
+            ..record
+        };
+        let (first, second) = (with_trace(first), with_trace(second));
+        let second_digest = second.digest;
+        first.send().await;
+        let mut second = Box::pin(second.send());
+        // The trace is full, the second transaction waits for room rather than being dropped
+        assert!(futures::poll!(second.as_mut()).is_pending());
+        trace_rx.recv().await.unwrap();
+        second.await;
+        assert_eq!(*trace_rx.recv().await.unwrap().1.digest(), second_digest);
+    }
+}
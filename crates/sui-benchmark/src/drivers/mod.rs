@@ -2,10 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use duration_str::parse;
-use std::{str::FromStr, time::Duration};
+use std::{collections::BTreeMap, str::FromStr, time::Duration};
 
 pub mod bench_driver;
 pub mod driver;
+pub mod replay;
 use comfy_table::{Cell, Color, ContentArrangement, Row, Table};
 use hdrhistogram::{serialization::Serializer, Histogram};
 
@@ -44,6 +45,14 @@ pub struct HistogramWrapper {
     histogram: Histogram<u64>,
 }
 
+impl Default for HistogramWrapper {
+    fn default() -> Self {
+        HistogramWrapper {
+            histogram: Histogram::<u64>::new_with_max(120_000, 3).unwrap(),
+        }
+    }
+}
+
 impl serde::Serialize for HistogramWrapper {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         let mut vec = Vec::new();
@@ -94,19 +103,76 @@ impl StressStats {
     }
 }
 
+/// Statistics of the transactions with the same target, see `Payload::stats_target`
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default)]
+pub struct TargetStats {
+    pub num_error_txes: u64,
+    pub num_success_txes: u64,
+    pub latency_ms: HistogramWrapper,
+}
+
+impl TargetStats {
+    pub fn update(&mut self, sample_stat: &TargetStats) {
+        self.num_error_txes += sample_stat.num_error_txes;
+        self.num_success_txes += sample_stat.num_success_txes;
+        self.latency_ms
+            .histogram
+            .add(&sample_stat.latency_ms.histogram)
+            .unwrap();
+    }
+}
+
 /// Stores the final statistics of the test run.
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default)]
 pub struct BenchmarkStats {
     pub duration: Duration,
     /// Number of transactions that ended in an error
     pub num_error_txes: u64,
     /// Number of transactions that were executed successfully
     pub num_success_txes: u64,
+    /// Number of transactions the benchmark stopped waiting for, see `BenchDriver::abandon_ratio`
+    #[serde(default)]
+    pub num_abandoned_txes: u64,
+    /// Number of transactions which failed local validation or whose gas coins could not cover
+    /// them, and which were never submitted, see `BenchDriver::validate_locally` and
+    /// `BenchDriver::check_funding`
+    #[serde(default)]
+    pub num_invalid_txes: u64,
     /// Total number of commands in transactions that executed successfully
     pub num_success_cmds: u64,
     /// Total gas used
     pub total_gas_used: u64,
+    /// Time from submission until effects are returned
     pub latency_ms: HistogramWrapper,
+    /// Time from submission until the transaction is included in a certified checkpoint
+    #[serde(default)]
+    pub finality_latency_ms: HistogramWrapper,
+    /// Time from the start of a burst until no transaction is in flight any more, when
+    /// submitting in bursts. Recorded per worker.
+    #[serde(default)]
+    pub burst_recovery_ms: HistogramWrapper,
+    /// Bursts which still had transactions in flight when the next burst started
+    #[serde(default)]
+    pub num_unrecovered_bursts: u64,
+    /// Time from the response to a transaction of a payload until the payload submits its next
+    /// transaction, for payloads which think between transactions. Includes the wait for the
+    /// pacing of the worker on top of the think time itself.
+    #[serde(default)]
+    pub think_time_ms: HistogramWrapper,
+    /// Label of the run, see `BenchDriver::run_label`
+    #[serde(default)]
+    pub run_label: Option<String>,
+    /// Transactions of payloads which report a target, broken down by target
+    #[serde(default)]
+    pub by_target: BTreeMap<String, TargetStats>,
+    /// Transactions broken down by the epoch they were executed in. Transactions which got an
+    /// error count towards the latest epoch known to the benchmark when they got it.
+    #[serde(default)]
+    pub by_epoch: BTreeMap<u64, TargetStats>,
+    /// Counters of the workloads which keep any, by payload type, see `Workload::counters`.
+    /// Workloads of the same type add up their counters.
+    #[serde(default)]
+    pub workload_counters: BTreeMap<String, BTreeMap<String, u64>>,
 }
 
 impl BenchmarkStats {
@@ -114,29 +180,79 @@ impl BenchmarkStats {
         self.duration = duration;
         self.num_error_txes += sample_stat.num_error_txes;
         self.num_success_txes += sample_stat.num_success_txes;
+        self.num_abandoned_txes += sample_stat.num_abandoned_txes;
+        self.num_invalid_txes += sample_stat.num_invalid_txes;
         self.num_success_cmds += sample_stat.num_success_cmds;
         self.total_gas_used += sample_stat.total_gas_used;
         self.latency_ms
             .histogram
             .add(&sample_stat.latency_ms.histogram)
             .unwrap();
+        self.finality_latency_ms
+            .histogram
+            .add(&sample_stat.finality_latency_ms.histogram)
+            .unwrap();
+        self.burst_recovery_ms
+            .histogram
+            .add(&sample_stat.burst_recovery_ms.histogram)
+            .unwrap();
+        self.num_unrecovered_bursts += sample_stat.num_unrecovered_bursts;
+        self.think_time_ms
+            .histogram
+            .add(&sample_stat.think_time_ms.histogram)
+            .unwrap();
+        for (target, stats) in &sample_stat.by_target {
+            self.by_target
+                .entry(target.clone())
+                .or_default()
+                .update(stats);
+        }
+        for (epoch, stats) in &sample_stat.by_epoch {
+            self.by_epoch.entry(*epoch).or_default().update(stats);
+        }
+        for (workload, counters) in &sample_stat.workload_counters {
+            let total = self.workload_counters.entry(workload.clone()).or_default();
+            for (counter, value) in counters {
+                *total.entry(counter.clone()).or_default() += value;
+            }
+        }
     }
     pub fn to_table(&self) -> Table {
         let mut table = Table::new();
+        let mut header = vec![
+            "duration(s)",
+            "tps",
+            "cps",
+            "error%",
+            "abandoned",
+            "invalid",
+            "latency (min)",
+            "latency (p50)",
+            "latency (p99)",
+            "finality (p50)",
+            "finality (p99)",
+            "gas used (MIST total)",
+            "gas used/hr (MIST approx.)",
+        ];
+        // Only runs submitting in bursts record recoveries
+        let bursts =
+            !self.burst_recovery_ms.histogram.is_empty() || self.num_unrecovered_bursts > 0;
+        if bursts {
+            header.extend([
+                "burst recovery (p50)",
+                "burst recovery (max)",
+                "unrecovered bursts",
+            ]);
+        }
+        // Only runs with think time record it
+        let think_time = !self.think_time_ms.histogram.is_empty();
+        if think_time {
+            header.extend(["think time (p50)", "think time (p99)"]);
+        }
         table
             .set_content_arrangement(ContentArrangement::Dynamic)
             .set_width(200)
-            .set_header(vec![
-                "duration(s)",
-                "tps",
-                "cps",
-                "error%",
-                "latency (min)",
-                "latency (p50)",
-                "latency (p99)",
-                "gas used (MIST total)",
-                "gas used/hr (MIST approx.)",
-            ]);
+            .set_header(header);
         let mut row = Row::new();
         row.add_cell(Cell::new(self.duration.as_secs()));
         row.add_cell(Cell::new(self.num_success_txes / self.duration.as_secs()));
@@ -145,9 +261,17 @@ impl BenchmarkStats {
             (100 * self.num_error_txes) as f32
                 / (self.num_error_txes + self.num_success_txes) as f32,
         ));
+        row.add_cell(Cell::new(self.num_abandoned_txes));
+        row.add_cell(Cell::new(self.num_invalid_txes));
         row.add_cell(Cell::new(self.latency_ms.histogram.min()));
         row.add_cell(Cell::new(self.latency_ms.histogram.value_at_quantile(0.5)));
         row.add_cell(Cell::new(self.latency_ms.histogram.value_at_quantile(0.99)));
+        row.add_cell(Cell::new(
+            self.finality_latency_ms.histogram.value_at_quantile(0.5),
+        ));
+        row.add_cell(Cell::new(
+            self.finality_latency_ms.histogram.value_at_quantile(0.99),
+        ));
         row.add_cell(Cell::new(format_num_with_separators(
             self.total_gas_used,
             3,
@@ -158,9 +282,104 @@ impl BenchmarkStats {
             3,
             ",",
         )));
+        if bursts {
+            row.add_cell(Cell::new(
+                self.burst_recovery_ms.histogram.value_at_quantile(0.5),
+            ));
+            row.add_cell(Cell::new(self.burst_recovery_ms.histogram.max()));
+            row.add_cell(Cell::new(self.num_unrecovered_bursts));
+        }
+        if think_time {
+            row.add_cell(Cell::new(
+                self.think_time_ms.histogram.value_at_quantile(0.5),
+            ));
+            row.add_cell(Cell::new(
+                self.think_time_ms.histogram.value_at_quantile(0.99),
+            ));
+        }
         table.add_row(row);
         table
     }
+    /// Success rate and latency of every target, if any payload reported one
+    pub fn to_target_table(&self) -> Option<Table> {
+        if self.by_target.is_empty() {
+            return None;
+        }
+        Some(breakdown_table(
+            "target",
+            self.by_target
+                .iter()
+                .map(|(target, stats)| (target.clone(), stats)),
+        ))
+    }
+    /// Success rate and latency of every epoch, if the run spanned epochs
+    pub fn to_epoch_table(&self) -> Option<Table> {
+        if self.by_epoch.len() < 2 {
+            return None;
+        }
+        Some(breakdown_table(
+            "epoch",
+            self.by_epoch
+                .iter()
+                .map(|(epoch, stats)| (epoch.to_string(), stats)),
+        ))
+    }
+    /// Counters of every workload which kept any
+    pub fn to_workload_table(&self) -> Option<Table> {
+        if self.workload_counters.is_empty() {
+            return None;
+        }
+        let mut table = Table::new();
+        table
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_width(200)
+            .set_header(vec!["workload", "counter", "value"]);
+        for (workload, counters) in &self.workload_counters {
+            for (counter, value) in counters {
+                let mut row = Row::new();
+                row.add_cell(Cell::new(workload));
+                row.add_cell(Cell::new(counter));
+                row.add_cell(Cell::new(value));
+                table.add_row(row);
+            }
+        }
+        Some(table)
+    }
+}
+
+/// Success rate and latency of the transactions of every group of a breakdown of the stats
+fn breakdown_table<'a>(
+    group: &str,
+    breakdown: impl Iterator<Item = (String, &'a TargetStats)>,
+) -> Table {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_width(200)
+        .set_header(vec![
+            group,
+            "success",
+            "error",
+            "success%",
+            "latency (p50)",
+            "latency (p99)",
+        ]);
+    for (name, stats) in breakdown {
+        let mut row = Row::new();
+        row.add_cell(Cell::new(name));
+        row.add_cell(Cell::new(stats.num_success_txes));
+        row.add_cell(Cell::new(stats.num_error_txes));
+        row.add_cell(Cell::new(
+            (100 * stats.num_success_txes) as f32
+                / (stats.num_error_txes + stats.num_success_txes) as f32,
+        ));
+        row.add_cell(Cell::new(stats.latency_ms.histogram.value_at_quantile(0.5)));
+        row.add_cell(Cell::new(
+            stats.latency_ms.histogram.value_at_quantile(0.99),
+        ));
+        table.add_row(row);
+    }
+    table
 }
 
 /// A comparison between an old and a new benchmark.
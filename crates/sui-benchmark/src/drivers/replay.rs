@@ -0,0 +1,226 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Recording the transactions submitted by a run to a trace file, and replaying them.
+//!
+//! A trace holds the signed transactions themselves, so a replay submits exactly the same bytes
+//! in the same order, whatever the seed and timing of the recorded run. Only the transactions of
+//! payloads are recorded: those of the bank funding the workloads and of workload `init`, e.g.
+//! package publishing, are made before the driver runs and are not. Since transactions refer to
+//! their input objects by version, a trace can only be replayed against a snapshot of the
+//! network taken once the recorded run had set up its workloads, not against a fresh network
+//! with the same genesis. The replay checks this before submitting anything, with the gas coins
+//! every gas owner of the trace first pays with, and later reports every owned input whose
+//! version differs from the recorded one. Shared objects are referred to by their initial
+//! version only, so their transactions can still go through, in an order which may differ from
+//! the recorded run.
+
+use crate::drivers::BenchmarkStats;
+use crate::ValidatorProxy;
+use anyhow::{ensure, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use sui_types::messages::{InputObjectKind, Transaction, TransactionDataAPI};
+use tokio::sync::mpsc::Receiver;
+use tokio::time::Instant;
+use tracing::{error, info, warn};
+
+/// Transaction of a trace and the workload which submitted it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TraceEntry {
+    pub workload: String,
+    pub tx: Transaction,
+}
+
+/// Appends a length-prefixed, BCS-encoded `TraceEntry` to `file` for every transaction received,
+/// until all senders are gone
+pub fn write_trace(file: File, mut rx: Receiver<(String, Transaction)>) {
+    let mut writer = BufWriter::new(file);
+    while let Some((workload, tx)) = rx.blocking_recv() {
+        let digest = *tx.digest();
+        let res = bcs::to_bytes(&TraceEntry { workload, tx })
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| {
+                writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+                writer.write_all(&bytes)?;
+                Ok(())
+            });
+        if let Err(e) = res {
+            error!(
+                "Failed to record transaction {}, no longer recording: {}",
+                digest, e
+            );
+            return;
+        }
+    }
+    if let Err(e) = writer.flush() {
+        error!("Failed to record transactions: {}", e);
+    }
+}
+
+/// Entries of the trace at `path`, in the order they were recorded
+pub fn read_trace(path: &Path) -> Result<Vec<TraceEntry>> {
+    let file =
+        File::open(path).with_context(|| format!("Failed to open trace {}", path.display()))?;
+    let mut reader = BufReader::new(file);
+    let mut entries = vec![];
+    loop {
+        let mut len = [0u8; 4];
+        match reader.read_exact(&mut len) {
+            Ok(()) => (),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+        let mut bytes = vec![0u8; u32::from_le_bytes(len) as usize];
+        reader.read_exact(&mut bytes).with_context(|| {
+            format!(
+                "Trace {} is truncated after {} transactions",
+                path.display(),
+                entries.len()
+            )
+        })?;
+        entries.push(bcs::from_bytes(&bytes).with_context(|| {
+            format!(
+                "Invalid transaction {} of trace {}",
+                entries.len(),
+                path.display()
+            )
+        })?);
+    }
+    Ok(entries)
+}
+
+/// Logs every owned input of `tx` which is not at the version `tx` refers to any more, and
+/// returns whether there was any
+async fn report_version_mismatches(
+    proxy: &Arc<dyn ValidatorProxy + Send + Sync>,
+    tx: &Transaction,
+) -> bool {
+    let inputs = match tx.data().transaction_data().input_objects() {
+        Ok(inputs) => inputs,
+        Err(_) => return false,
+    };
+    let mut mismatch = false;
+    for input in inputs {
+        if let InputObjectKind::ImmOrOwnedMoveObject((id, version, _)) = input {
+            match proxy.get_object(id).await {
+                Ok(object) if object.version() == version => (),
+                Ok(object) => {
+                    mismatch = true;
+                    warn!(
+                        "Input {} of transaction {} was recorded at version {}, it is at {}",
+                        id,
+                        tx.digest(),
+                        version,
+                        object.version()
+                    );
+                }
+                Err(e) => {
+                    mismatch = true;
+                    warn!(
+                        "Input {} of transaction {} was recorded at version {}, it is missing: {}",
+                        id,
+                        tx.digest(),
+                        version,
+                        e
+                    );
+                }
+            }
+        }
+    }
+    mismatch
+}
+
+/// Checks that the network is the snapshot `entries` were recorded against: the gas coins that
+/// every gas owner first pays with in the trace were funded by the bank or by workload `init`,
+/// which are not recorded, so they must be at their recorded versions before the replay starts
+async fn check_snapshot(
+    proxy: &Arc<dyn ValidatorProxy + Send + Sync>,
+    entries: &[TraceEntry],
+) -> Result<()> {
+    let mut gas_owners = HashSet::new();
+    for entry in entries {
+        let data = entry.tx.data().transaction_data();
+        if !gas_owners.insert(data.gas_owner()) {
+            continue;
+        }
+        for (id, version, _) in data.gas() {
+            let object = proxy.get_object(*id).await.with_context(|| {
+                format!(
+                    "Gas coin {} of {} is missing, the trace can only be replayed against a \
+                    snapshot of the network after the recorded run set up its workloads",
+                    id,
+                    data.gas_owner()
+                )
+            })?;
+            ensure!(
+                object.version() == *version,
+                "Gas coin {} of {} is at version {} instead of {}, the trace can only be \
+                replayed against a snapshot of the network after the recorded run set up its \
+                workloads",
+                id,
+                data.gas_owner(),
+                object.version(),
+                version
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Submits the transactions of the trace at `path` one after the other, each once the previous
+/// one got a response, so that transactions depending on the outputs of earlier ones find them.
+/// The stats are broken down by the workload which recorded the transactions.
+pub async fn replay_trace(
+    path: &Path,
+    proxy: Arc<dyn ValidatorProxy + Send + Sync>,
+) -> Result<BenchmarkStats> {
+    let entries = read_trace(path)?;
+    check_snapshot(&proxy, &entries).await?;
+    info!(
+        "Replaying {} transactions of trace {}",
+        entries.len(),
+        path.display()
+    );
+    let mut stats = BenchmarkStats::default();
+    let mut num_mismatched = 0;
+    let start = Instant::now();
+    for TraceEntry { workload, tx } in entries {
+        let num_commands = tx.data().transaction_data().kind().num_commands() as u64;
+        let submitted = Instant::now();
+        let target = stats.by_target.entry(workload).or_default();
+        match proxy.execute_transaction_block(tx.clone()).await {
+            Ok(effects) => {
+                let latency_ms = submitted.elapsed().as_millis().try_into().unwrap();
+                stats.num_success_txes += 1;
+                stats.num_success_cmds += num_commands;
+                stats.total_gas_used += effects.gas_used();
+                stats.latency_ms.histogram.saturating_record(latency_ms);
+                target.num_success_txes += 1;
+                target.latency_ms.histogram.saturating_record(latency_ms);
+            }
+            Err(e) => {
+                error!("Replayed transaction {} failed: {}", tx.digest(), e);
+                stats.num_error_txes += 1;
+                target.num_error_txes += 1;
+                if report_version_mismatches(&proxy, &tx).await {
+                    num_mismatched += 1;
+                }
+            }
+        }
+    }
+    if num_mismatched > 0 {
+        warn!(
+            "{} replayed transactions had inputs at other versions than recorded",
+            num_mismatched
+        );
+    }
+    // The report divides by the duration in whole seconds
+    stats.duration = start.elapsed().max(Duration::from_secs(1));
+    Ok(stats)
+}
@@ -0,0 +1,220 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::workloads::{Gas, GasCoinConfig};
+use crate::ValidatorProxy;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::crypto::{KeypairTraits, SuiKeyPair};
+use sui_types::gas_coin::GasCoin;
+use sui_types::object::Owner;
+
+/// Accounts funded by the bank for the workloads of a run, saved to a file so that later runs
+/// reuse them instead of funding new ones. Accounts are kept by the name of their workload, see
+/// `BenchmarkBank::generate` for how they are matched to the coin configs of a later run.
+///
+/// The file holds the private keys of the accounts unencrypted, in the same encoding as the
+/// keystore of the Sui CLI, which is why the benchmark only uses it with
+/// `--insecure-plaintext-keystore`. It is created readable by its owner only on unix, but
+/// anyone with access to that user's files can spend the funded coins.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GasKeystore {
+    workloads: BTreeMap<String, WorkloadAccounts>,
+}
+
+/// Accounts funded for the coin configs of a workload
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WorkloadAccounts {
+    pub init: Vec<SavedAccount>,
+    pub payloads: Vec<SavedAccount>,
+}
+
+/// Gas coin funded for a coin config and its owner
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedAccount {
+    address: SuiAddress,
+    coin: ObjectID,
+    /// Key of the owner, unless the builder of the workload holds it. Gas coins are signed for
+    /// with Ed25519 keys, owners of other schemes are only ever held by their builders.
+    keypair: Option<SuiKeyPair>,
+}
+
+impl SavedAccount {
+    /// Account of `gas` with the key of its owner
+    pub fn new(gas: &Gas) -> Self {
+        SavedAccount {
+            address: gas.1,
+            coin: gas.0 .0,
+            keypair: Some(SuiKeyPair::Ed25519(gas.2.copy())),
+        }
+    }
+
+    /// Account of `gas` whose owner is held by the builder of its workload rather than saved
+    pub fn without_keypair(gas: &Gas) -> Self {
+        SavedAccount {
+            address: gas.1,
+            coin: gas.0 .0,
+            keypair: None,
+        }
+    }
+
+    pub fn address(&self) -> SuiAddress {
+        self.address
+    }
+
+    /// Latest version of the saved coin for `config`, checked to still belong to the account and
+    /// to hold at least half of the amount of the config, so that a run does not start with
+    /// accounts about to run dry. Accounts saved without a key are signed for with the key of
+    /// the config, which must then be sent to the same address.
+    pub async fn load(
+        &self,
+        proxy: &Arc<dyn ValidatorProxy + Send + Sync>,
+        config: &GasCoinConfig,
+    ) -> Result<Gas> {
+        let keypair = match &self.keypair {
+            Some(SuiKeyPair::Ed25519(keypair)) => Arc::new(keypair.copy()),
+            Some(other) => bail!(
+                "Saved account keys must be Ed25519, got {:?}",
+                other.public()
+            ),
+            None if config.address == self.address => config.keypair.clone(),
+            None => bail!(
+                "Saved account {} has no key and its workload now funds {}",
+                self.address,
+                config.address
+            ),
+        };
+        let address = self.address;
+        let object = proxy
+            .get_object(self.coin)
+            .await
+            .with_context(|| format!("Coin {} of saved account {} is gone", self.coin, address))?;
+        if object.owner != Owner::AddressOwner(address) {
+            bail!(
+                "Coin {} no longer belongs to saved account {}, it is owned by {}",
+                self.coin,
+                address,
+                object.owner
+            );
+        }
+        let balance = GasCoin::try_from(&object)
+            .map_err(|e| anyhow!("Object {} of saved account {}: {}", self.coin, address, e))?
+            .value();
+        if balance < config.amount / 2 {
+            bail!(
+                "Coin {} of saved account {} only holds {} MIST, its workload needs {}",
+                self.coin,
+                address,
+                balance,
+                config.amount
+            );
+        }
+        Ok((object.compute_object_reference(), address, keypair))
+    }
+}
+
+impl GasKeystore {
+    /// Accounts saved at `path`, or `None` if there is no file there yet
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read gas keystore {}", path.display()))?;
+        let keystore = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid gas keystore {}", path.display()))?;
+        Ok(Some(keystore))
+    }
+
+    /// Writes the accounts to a temporary file next to `path`, created readable by its owner
+    /// only, then renames it over `path`, so that the keys are never readable by others and an
+    /// interrupted save leaves any previous keystore intact
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+        // A temporary file left by an earlier failed save may have been created without the mode
+        let _ = std::fs::remove_file(&tmp_path);
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let write = || -> std::io::Result<()> {
+            let mut file = options.open(&tmp_path)?;
+            file.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+            file.sync_all()?;
+            std::fs::rename(&tmp_path, path)
+        };
+        let written = write();
+        if written.is_err() {
+            let _ = std::fs::remove_file(&tmp_path);
+        }
+        written.with_context(|| format!("Failed to write gas keystore {}", path.display()))
+    }
+
+    /// Removes the accounts saved for workload `name`, to be reused before they are replaced
+    pub fn take_workload(&mut self, name: &str) -> Option<WorkloadAccounts> {
+        self.workloads.remove(name)
+    }
+
+    pub fn insert(&mut self, name: String, accounts: WorkloadAccounts) {
+        self.workloads.insert(name, accounts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sui_types::base_types::random_object_ref;
+    use sui_types::crypto::get_key_pair;
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("gas-keystore-{}.json", ObjectID::random()));
+        let (address, keypair) = get_key_pair();
+        let gas = (random_object_ref(), address, Arc::new(keypair));
+        let mut keystore = GasKeystore::default();
+        keystore.insert(
+            "transfer".to_owned(),
+            WorkloadAccounts {
+                init: vec![],
+                payloads: vec![SavedAccount::new(&gas)],
+            },
+        );
+        keystore.insert(
+            "delegation".to_owned(),
+            WorkloadAccounts {
+                init: vec![],
+                payloads: vec![SavedAccount::without_keypair(&gas)],
+            },
+        );
+        keystore.save(&path).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        // Saving again replaces the keystore written by the first save
+        keystore.save(&path).unwrap();
+        let mut loaded = GasKeystore::load(&path).unwrap().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let accounts = loaded.take_workload("transfer").unwrap();
+        assert!(accounts.init.is_empty());
+        assert_eq!(accounts.payloads[0].coin, gas.0 .0);
+        assert_eq!(accounts.payloads[0].address(), address);
+        assert!(accounts.payloads[0].keypair.is_some());
+        let accounts = loaded.take_workload("delegation").unwrap();
+        assert!(accounts.payloads[0].keypair.is_none());
+        assert!(loaded.take_workload("transfer").is_none());
+    }
+}
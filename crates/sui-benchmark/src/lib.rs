@@ -1,6 +1,6 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
-use anyhow::bail;
+use anyhow::{bail, Context};
 use async_trait::async_trait;
 use embedded_reconfig_observer::EmbeddedReconfigObserver;
 use fullnode_reconfig_observer::FullNodeReconfigObserver;
@@ -10,7 +10,7 @@ use roaring::RoaringBitmap;
 use std::{
     collections::BTreeMap,
     sync::{Arc, Mutex},
-    time::Duration,
+    time::{Duration, Instant},
 };
 use sui_config::genesis::Genesis;
 use sui_core::{
@@ -23,16 +23,18 @@ use sui_core::{
     },
 };
 use sui_json_rpc_types::{
-    SuiObjectDataOptions, SuiTransactionBlockEffects, SuiTransactionBlockEffectsAPI,
-    SuiTransactionBlockResponseOptions,
+    SuiExecutionStatus, SuiObjectDataOptions, SuiTransactionBlockEffects,
+    SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponseOptions,
 };
 use sui_network::{DEFAULT_CONNECT_TIMEOUT_SEC, DEFAULT_REQUEST_TIMEOUT_SEC};
 use sui_sdk::{SuiClient, SuiClientBuilder};
-use sui_types::base_types::SequenceNumber;
+use sui_types::base_types::{SequenceNumber, TransactionDigest};
+use sui_types::event::Event;
 use sui_types::messages::Argument;
 use sui_types::messages::CallArg;
 use sui_types::messages::ObjectArg;
 use sui_types::messages::TransactionEvents;
+use sui_types::messages_checkpoint::{CheckpointRequest, CheckpointSequenceNumber};
 use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use sui_types::sui_system_state::sui_system_state_summary::SuiSystemStateSummary;
 use sui_types::{
@@ -44,8 +46,9 @@ use sui_types::{
     },
     message_envelope::Envelope,
     messages::{
-        CertifiedTransaction, CertifiedTransactionEffects, HandleCertificateResponse,
-        QuorumDriverResponse, Transaction, TransactionEffectsAPI, TransactionStatus,
+        CertifiedTransaction, CertifiedTransactionEffects, ExecutionFailureStatus, ExecutionStatus,
+        HandleCertificateResponse, QuorumDriverResponse, Transaction, TransactionEffectsAPI,
+        TransactionStatus,
     },
     object::Object,
 };
@@ -63,6 +66,7 @@ pub mod benchmark_setup;
 pub mod drivers;
 pub mod embedded_reconfig_observer;
 pub mod fullnode_reconfig_observer;
+pub mod gas_keystore;
 pub mod in_memory_wallet;
 pub mod options;
 pub mod system_state_observer;
@@ -70,6 +74,10 @@ pub mod util;
 pub mod workloads;
 use futures::FutureExt;
 
+/// Number of object ids a transaction can create, `max_num_new_move_object_ids` of the
+/// protocol config
+const MAX_CREATION_NUM: u64 = 2048;
+
 #[derive(Debug)]
 /// A wrapper on execution results to accommodate different types of
 /// responses from LocalValidatorAggregatorProxy and FullNodeProxy
@@ -106,6 +114,72 @@ impl ExecutionEffects {
         }
     }
 
+    /// The first object created by the transaction, if any. It is returned by value rather than
+    /// by reference since fullnode effects are converted by `created` on every call.
+    ///
+    /// Created objects are listed in no particular order, so payloads which create several
+    /// objects should tell them apart by owner with `created_owned_by`, by count with
+    /// `created_single` or by creation order with `created_in_creation_order`.
+    pub fn first_created(&self) -> Option<(ObjectRef, Owner)> {
+        self.created().into_iter().next()
+    }
+
+    /// The object created by the transaction, if it created exactly one
+    pub fn created_single(&self) -> Option<ObjectRef> {
+        match self.created().as_slice() {
+            [(obj_ref, _)] => Some(*obj_ref),
+            _ => None,
+        }
+    }
+
+    /// Objects created by the transaction and owned by `address`
+    pub fn created_owned_by(&self, address: SuiAddress) -> Vec<ObjectRef> {
+        self.created()
+            .into_iter()
+            .filter(|(_, owner)| *owner == Owner::AddressOwner(address))
+            .map(|(obj_ref, _)| obj_ref)
+            .collect()
+    }
+
+    /// Objects created by the transaction, in the order the transaction created them.
+    ///
+    /// Effects do not list created objects in creation order, but the id of every object
+    /// created with `object::new` is derived from the transaction digest and the number of
+    /// ids the transaction created before it, so the order is recovered by deriving ids.
+    /// Objects whose id is not derived this way, such as dynamic fields, come last.
+    pub fn created_in_creation_order(&self) -> Vec<(ObjectRef, Owner)> {
+        let digest = self.transaction_digest();
+        let mut created = self.created();
+        let mut creation_nums = BTreeMap::new();
+        for creation_num in 0..MAX_CREATION_NUM {
+            if creation_nums.len() == created.len() {
+                break;
+            }
+            let id = ObjectID::derive_id(digest, creation_num);
+            if created
+                .iter()
+                .any(|((created_id, ..), _)| *created_id == id)
+            {
+                creation_nums.insert(id, creation_num);
+            }
+        }
+        created.sort_by_key(|((id, ..), _)| creation_nums.get(id).copied().unwrap_or(u64::MAX));
+        created
+    }
+
+    /// The `StakedSui` created by a successful stake of `staker`, if any.
+    ///
+    /// Effects record no object types, so rather than reading every created object to find
+    /// its type, this relies on staking creating the `StakedSui` before any other object the
+    /// transaction creates for `staker`, which holds for transactions that stake before they
+    /// split, withdraw or otherwise create objects for the staker.
+    pub fn created_staked_sui(&self, staker: SuiAddress) -> Option<ObjectRef> {
+        self.created_in_creation_order()
+            .into_iter()
+            .find(|(_, owner)| *owner == Owner::AddressOwner(staker))
+            .map(|(obj_ref, _)| obj_ref)
+    }
+
     pub fn deleted(&self) -> Vec<ObjectRef> {
         match self {
             ExecutionEffects::CertifiedTransactionEffects(certified_effects, ..) => {
@@ -119,6 +193,17 @@ impl ExecutionEffects {
         }
     }
 
+    pub fn transaction_digest(&self) -> TransactionDigest {
+        match self {
+            ExecutionEffects::CertifiedTransactionEffects(certified_effects, ..) => {
+                *certified_effects.data().transaction_digest()
+            }
+            ExecutionEffects::SuiTransactionBlockEffects(sui_tx_effects) => {
+                *sui_tx_effects.transaction_digest()
+            }
+        }
+    }
+
     pub fn quorum_sig(&self) -> Option<&AuthorityStrongQuorumSignInfo> {
         match self {
             ExecutionEffects::CertifiedTransactionEffects(certified_effects, ..) => {
@@ -140,6 +225,52 @@ impl ExecutionEffects {
         }
     }
 
+    pub fn executed_epoch(&self) -> EpochId {
+        match self {
+            ExecutionEffects::CertifiedTransactionEffects(certified_effects, ..) => {
+                certified_effects.data().executed_epoch()
+            }
+            ExecutionEffects::SuiTransactionBlockEffects(sui_tx_effects) => {
+                sui_tx_effects.executed_epoch()
+            }
+        }
+    }
+
+    /// Events emitted by the transaction. Only validators return them along with the effects,
+    /// transactions executed through a fullnode have none
+    pub fn events(&self) -> Option<&TransactionEvents> {
+        match self {
+            ExecutionEffects::CertifiedTransactionEffects(_, events) => Some(events),
+            ExecutionEffects::SuiTransactionBlockEffects(_) => None,
+        }
+    }
+
+    /// First event of type `module::name` emitted by the transaction, if the effects carry events
+    pub fn find_event(&self, module: &str, name: &str) -> Option<&Event> {
+        self.events()?.data.iter().find(|event| {
+            event.type_.module.as_str() == module && event.type_.name.as_str() == name
+        })
+    }
+
+    /// Certified effects of `effects` and `events`, without any validator signatures
+    #[cfg(test)]
+    pub fn new_for_testing(
+        effects: sui_types::messages::TransactionEffects,
+        events: TransactionEvents,
+    ) -> Self {
+        ExecutionEffects::CertifiedTransactionEffects(
+            CertifiedTransactionEffects::new_from_data_and_sig(
+                effects,
+                AuthorityQuorumSignInfo {
+                    epoch: 0,
+                    signature: AggregateAuthoritySignature::default(),
+                    signers_map: RoaringBitmap::new(),
+                },
+            ),
+            events,
+        )
+    }
+
     pub fn sender(&self) -> SuiAddress {
         match self.gas_object().1 {
             Owner::AddressOwner(a) => a,
@@ -158,6 +289,48 @@ impl ExecutionEffects {
         }
     }
 
+    /// Why the transaction failed to execute, if it did
+    pub fn failure_status(&self) -> Option<String> {
+        match self {
+            ExecutionEffects::CertifiedTransactionEffects(certified_effects, ..) => {
+                match certified_effects.data().status() {
+                    ExecutionStatus::Success => None,
+                    ExecutionStatus::Failure {
+                        error,
+                        command: Some(command),
+                    } => Some(format!("{:?} in command {}", error, command)),
+                    ExecutionStatus::Failure {
+                        error,
+                        command: None,
+                    } => Some(format!("{:?}", error)),
+                }
+            }
+            ExecutionEffects::SuiTransactionBlockEffects(sui_tx_effects) => {
+                match sui_tx_effects.status() {
+                    SuiExecutionStatus::Success => None,
+                    SuiExecutionStatus::Failure { error } => Some(error.clone()),
+                }
+            }
+        }
+    }
+
+    /// Code the transaction aborted with, if it failed with a Move abort. Fullnode effects only
+    /// describe failures in text, so this is always `None` for them.
+    pub fn move_abort_code(&self) -> Option<u64> {
+        match self {
+            ExecutionEffects::CertifiedTransactionEffects(certified_effects, ..) => {
+                match certified_effects.data().status() {
+                    ExecutionStatus::Failure {
+                        error: ExecutionFailureStatus::MoveAbort(_, code),
+                        ..
+                    } => Some(*code),
+                    _ => None,
+                }
+            }
+            ExecutionEffects::SuiTransactionBlockEffects(_) => None,
+        }
+    }
+
     pub fn gas_cost_summary(&self) -> GasCostSummary {
         match self {
             crate::ExecutionEffects::CertifiedTransactionEffects(a, _) => {
@@ -178,18 +351,78 @@ impl ExecutionEffects {
     }
 }
 
+/// Event of type `0x3::{module}::{name}` emitted by `sender`, holding `contents`
+#[cfg(test)]
+pub fn make_event_for_testing(
+    sender: SuiAddress,
+    module: &str,
+    name: &str,
+    contents: Vec<u8>,
+) -> Event {
+    use move_core_types::identifier::Identifier;
+    use move_core_types::language_storage::StructTag;
+    Event {
+        package_id: sui_types::SUI_SYSTEM_PACKAGE_ID,
+        transaction_module: Identifier::new(module).unwrap(),
+        sender,
+        type_: StructTag {
+            address: sui_types::SUI_SYSTEM_ADDRESS,
+            module: Identifier::new(module).unwrap(),
+            name: Identifier::new(name).unwrap(),
+            type_params: vec![],
+        },
+        contents,
+    }
+}
+
+/// Time spent in the phases of executing a transaction
+#[derive(Debug, Clone, Copy)]
+pub struct LatencyBreakdown {
+    /// Until a quorum of validators signed the transaction
+    pub certificate: Duration,
+    /// From sending the certificate until the first validator returned effects, i.e. the
+    /// execution by the fastest validator
+    pub execution: Duration,
+    /// From the first effects until a quorum of validators returned them
+    pub effects_certificate: Duration,
+}
+
+impl LatencyBreakdown {
+    pub fn phases(&self) -> [(&'static str, Duration); 3] {
+        [
+            ("certificate", self.certificate),
+            ("execution", self.execution),
+            ("effects_certificate", self.effects_certificate),
+        ]
+    }
+}
+
 #[async_trait]
 pub trait ValidatorProxy {
     async fn get_object(&self, object_id: ObjectID) -> Result<Object, anyhow::Error>;
 
     async fn get_latest_system_state_object(&self) -> Result<SuiSystemStateSummary, anyhow::Error>;
 
+    /// Submits `tx` and waits for its effects. There is no way to cancel a transaction once it
+    /// is submitted: dropping the returned future only stops waiting for it, validators may
+    /// still execute it.
     async fn execute_transaction_block(&self, tx: Transaction) -> anyhow::Result<ExecutionEffects>;
 
     /// This function is similar to `execute_transaction` but does not check any validator's
     /// signature. It should only be used for benchmarks.
     async fn execute_bench_transaction(&self, tx: Transaction) -> anyhow::Result<ExecutionEffects>;
 
+    /// Like `execute_transaction_block`, also returning how long the phases of the execution
+    /// took if the proxy can tell them apart.
+    async fn execute_transaction_block_with_breakdown(
+        &self,
+        tx: Transaction,
+    ) -> anyhow::Result<(ExecutionEffects, Option<LatencyBreakdown>)>;
+
+    /// Executes `tx` against the latest state without committing anything, so its effects must
+    /// not be used to update objects of the sender.
+    async fn dry_run_transaction_block(&self, tx: Transaction) -> anyhow::Result<ExecutionEffects>;
+
     fn clone_committee(&self) -> Committee;
 
     fn get_current_epoch(&self) -> EpochId;
@@ -197,6 +430,14 @@ pub trait ValidatorProxy {
     fn clone_new(&self) -> Box<dyn ValidatorProxy + Send + Sync>;
 
     async fn get_validators(&self) -> Result<Vec<SuiAddress>, anyhow::Error>;
+
+    /// Returns the sequence number and transaction digests of the checkpoint with sequence
+    /// number `sequence_number`, or of the latest certified checkpoint if `None`. Returns
+    /// `Ok(None)` if that checkpoint has not been certified yet.
+    async fn get_checkpoint_transactions(
+        &self,
+        sequence_number: Option<CheckpointSequenceNumber>,
+    ) -> Result<Option<(CheckpointSequenceNumber, Vec<TransactionDigest>)>, anyhow::Error>;
 }
 
 // TODO: Eventually remove this proxy because we shouldn't rely on validators to read objects.
@@ -289,59 +530,13 @@ impl LocalValidatorAggregatorProxy {
             requests: Mutex::new(JoinSet::new()),
         }
     }
-}
-
-#[async_trait]
-impl ValidatorProxy for LocalValidatorAggregatorProxy {
-    async fn get_object(&self, object_id: ObjectID) -> Result<Object, anyhow::Error> {
-        let auth_agg = self.qd.authority_aggregator().load();
-        Ok(auth_agg
-            .get_latest_object_version_for_testing(object_id)
-            .await?)
-    }
-
-    async fn get_latest_system_state_object(&self) -> Result<SuiSystemStateSummary, anyhow::Error> {
-        let auth_agg = self.qd.authority_aggregator().load();
-        Ok(auth_agg
-            .get_latest_system_state_object_for_testing()
-            .await?
-            .into_sui_system_state_summary())
-    }
-
-    async fn execute_transaction_block(&self, tx: Transaction) -> anyhow::Result<ExecutionEffects> {
-        if std::env::var("BENCH_MODE").is_ok() {
-            return self.execute_bench_transaction(tx).await;
-        }
-        let tx_digest = *tx.digest();
-        let tx = tx.verify()?;
-        let mut retry_cnt = 0;
-        while retry_cnt < 3 {
-            let ticket = self.qd.submit_transaction(tx.clone()).await?;
-            // The ticket only times out when QuorumDriver exceeds the retry times
-            match ticket.await {
-                Ok(resp) => {
-                    let QuorumDriverResponse {
-                        effects_cert,
-                        events,
-                    } = resp;
-                    return Ok(ExecutionEffects::CertifiedTransactionEffects(
-                        effects_cert.into(),
-                        events,
-                    ));
-                }
-                Err(err) => {
-                    error!(
-                        ?tx_digest,
-                        retry_cnt, "Transaction failed with err: {:?}", err
-                    );
-                    retry_cnt += 1;
-                }
-            }
-        }
-        bail!("Transaction {:?} failed for {retry_cnt} times", tx_digest);
-    }
 
-    async fn execute_bench_transaction(&self, tx: Transaction) -> anyhow::Result<ExecutionEffects> {
+    /// `ValidatorProxy::execute_bench_transaction`, also timing its phases
+    async fn execute_bench_transaction_timed(
+        &self,
+        tx: Transaction,
+    ) -> anyhow::Result<(ExecutionEffects, LatencyBreakdown)> {
+        let start = Instant::now();
         // Store the epoch number; we read it from the votes and use it later to create the certificate.
         let mut epoch = 0;
 
@@ -440,6 +635,9 @@ impl ValidatorProxy for LocalValidatorAggregatorProxy {
             }
         };
 
+        let certified = Instant::now();
+        let mut first_effects = None;
+
         // Send the certificate to all validators.
         let mut futures = FuturesUnordered::new();
         total_stake = 0;
@@ -465,6 +663,7 @@ impl ValidatorProxy for LocalValidatorAggregatorProxy {
                     signed_effects,
                     events,
                 }) => {
+                    first_effects.get_or_insert_with(Instant::now);
                     let author = signed_effects.auth_sig().authority;
                     transaction_effects = Some(signed_effects.data().clone());
                     transaction_events = Some(events);
@@ -504,15 +703,104 @@ impl ValidatorProxy for LocalValidatorAggregatorProxy {
             });
         }
 
+        let breakdown = LatencyBreakdown {
+            certificate: certified - start,
+            // Set along with the effects checked above
+            execution: first_effects.unwrap() - certified,
+            effects_certificate: first_effects.unwrap().elapsed(),
+        };
+
         // Package the certificate and effects to return.
         let signed_material = certified_transaction.auth_sig().clone();
         let effects = ExecutionEffects::CertifiedTransactionEffects(
             Envelope::new_from_data_and_sig(transaction_effects.unwrap(), signed_material),
             transaction_events.unwrap(),
         );
+        Ok((effects, breakdown))
+    }
+}
+
+#[async_trait]
+impl ValidatorProxy for LocalValidatorAggregatorProxy {
+    async fn get_object(&self, object_id: ObjectID) -> Result<Object, anyhow::Error> {
+        let auth_agg = self.qd.authority_aggregator().load();
+        Ok(auth_agg
+            .get_latest_object_version_for_testing(object_id)
+            .await?)
+    }
+
+    async fn get_latest_system_state_object(&self) -> Result<SuiSystemStateSummary, anyhow::Error> {
+        let auth_agg = self.qd.authority_aggregator().load();
+        Ok(auth_agg
+            .get_latest_system_state_object_for_testing()
+            .await?
+            .into_sui_system_state_summary())
+    }
+
+    async fn execute_transaction_block(&self, tx: Transaction) -> anyhow::Result<ExecutionEffects> {
+        if std::env::var("BENCH_MODE").is_ok() {
+            return self.execute_bench_transaction(tx).await;
+        }
+        let tx_digest = *tx.digest();
+        let tx = tx.verify()?;
+        let mut retry_cnt = 0;
+        // Kept so that callers can tell why the transaction failed
+        let mut last_err = String::new();
+        while retry_cnt < 3 {
+            let ticket = self.qd.submit_transaction(tx.clone()).await?;
+            // The ticket only times out when QuorumDriver exceeds the retry times
+            match ticket.await {
+                Ok(resp) => {
+                    let QuorumDriverResponse {
+                        effects_cert,
+                        events,
+                    } = resp;
+                    return Ok(ExecutionEffects::CertifiedTransactionEffects(
+                        effects_cert.into(),
+                        events,
+                    ));
+                }
+                Err(err) => {
+                    error!(
+                        ?tx_digest,
+                        retry_cnt, "Transaction failed with err: {:?}", err
+                    );
+                    last_err = format!("{:?}", err);
+                    retry_cnt += 1;
+                }
+            }
+        }
+        bail!(
+            "Transaction {:?} failed for {retry_cnt} times, last error: {}",
+            tx_digest,
+            last_err
+        );
+    }
+
+    async fn execute_bench_transaction(&self, tx: Transaction) -> anyhow::Result<ExecutionEffects> {
+        let (effects, _) = self.execute_bench_transaction_timed(tx).await?;
         Ok(effects)
     }
 
+    async fn execute_transaction_block_with_breakdown(
+        &self,
+        tx: Transaction,
+    ) -> anyhow::Result<(ExecutionEffects, Option<LatencyBreakdown>)> {
+        // The quorum driver does not tell its phases apart, only the benchmark path does
+        if std::env::var("BENCH_MODE").is_ok() {
+            let (effects, breakdown) = self.execute_bench_transaction_timed(tx).await?;
+            return Ok((effects, Some(breakdown)));
+        }
+        Ok((self.execute_transaction_block(tx).await?, None))
+    }
+
+    async fn dry_run_transaction_block(&self, tx: Transaction) -> anyhow::Result<ExecutionEffects> {
+        bail!(
+            "Cannot dry run {:?}: validators do not dry run transactions, use a fullnode",
+            tx.digest()
+        )
+    }
+
     fn clone_committee(&self) -> Committee {
         self.qd.clone_committee()
     }
@@ -541,6 +829,31 @@ impl ValidatorProxy for LocalValidatorAggregatorProxy {
             .map(|v| v.sui_address)
             .collect())
     }
+
+    async fn get_checkpoint_transactions(
+        &self,
+        sequence_number: Option<CheckpointSequenceNumber>,
+    ) -> Result<Option<(CheckpointSequenceNumber, Vec<TransactionDigest>)>, anyhow::Error> {
+        // Checkpoints are certified, so reading them from a single validator is enough
+        let client = self
+            .clients
+            .values()
+            .next()
+            .context("No validator client to read checkpoints from")?;
+        let response = client
+            .handle_checkpoint(CheckpointRequest {
+                sequence_number,
+                request_content: true,
+            })
+            .await?;
+        match (response.checkpoint, response.contents) {
+            (Some(checkpoint), Some(contents)) => Ok(Some((
+                *checkpoint.sequence_number(),
+                contents.iter().map(|digests| digests.transaction).collect(),
+            ))),
+            _ => Ok(None),
+        }
+    }
 }
 
 pub struct FullNodeProxy {
@@ -601,6 +914,7 @@ impl ValidatorProxy for FullNodeProxy {
         let tx_digest = *tx.digest();
         let tx = tx.verify()?;
         let mut retry_cnt = 0;
+        let mut last_err = String::new();
         while retry_cnt < 10 {
             // Fullnode could time out after WAIT_FOR_FINALITY_TIMEOUT (30s) in TransactionOrchestrator
             // SuiClient times out after 60s
@@ -625,17 +939,41 @@ impl ValidatorProxy for FullNodeProxy {
                         ?tx_digest,
                         retry_cnt, "Transaction failed with err: {:?}", err
                     );
+                    last_err = format!("{:?}", err);
                     retry_cnt += 1;
                 }
             }
         }
-        bail!("Transaction {:?} failed for {retry_cnt} times", tx_digest);
+        bail!(
+            "Transaction {:?} failed for {retry_cnt} times, last error: {}",
+            tx_digest,
+            last_err
+        );
     }
 
     async fn execute_bench_transaction(&self, tx: Transaction) -> anyhow::Result<ExecutionEffects> {
         self.execute_transaction_block(tx).await
     }
 
+    async fn execute_transaction_block_with_breakdown(
+        &self,
+        tx: Transaction,
+    ) -> anyhow::Result<(ExecutionEffects, Option<LatencyBreakdown>)> {
+        // The fullnode executes the transaction on behalf of the benchmark
+        Ok((self.execute_transaction_block(tx).await?, None))
+    }
+
+    async fn dry_run_transaction_block(&self, tx: Transaction) -> anyhow::Result<ExecutionEffects> {
+        let response = self
+            .sui_client
+            .read_api()
+            .dry_run_transaction_block(tx.data().transaction_data().clone())
+            .await?;
+        Ok(ExecutionEffects::SuiTransactionBlockEffects(
+            response.effects,
+        ))
+    }
+
     fn clone_committee(&self) -> Committee {
         self.committee.clone()
     }
@@ -660,6 +998,21 @@ impl ValidatorProxy for FullNodeProxy {
             .active_validators;
         Ok(validators.into_iter().map(|v| v.sui_address).collect())
     }
+
+    async fn get_checkpoint_transactions(
+        &self,
+        sequence_number: Option<CheckpointSequenceNumber>,
+    ) -> Result<Option<(CheckpointSequenceNumber, Vec<TransactionDigest>)>, anyhow::Error> {
+        let read_api = self.sui_client.read_api();
+        let latest = read_api.get_latest_checkpoint_sequence_number().await?;
+        let sequence_number = match sequence_number {
+            Some(s) if s > latest => return Ok(None),
+            Some(s) => s,
+            None => latest,
+        };
+        let checkpoint = read_api.get_checkpoint(sequence_number.into()).await?;
+        Ok(Some((sequence_number, checkpoint.transactions)))
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
@@ -781,3 +1134,84 @@ pub fn convert_move_call_args(
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sui_types::base_types::{random_object_ref, ObjectDigest};
+    use sui_types::messages::{TransactionEffects, TransactionEffectsV1};
+
+    #[test]
+    fn test_find_event() {
+        let sender = SuiAddress::random_for_testing_only();
+        let events = TransactionEvents {
+            data: vec![
+                make_event_for_testing(sender, "validator", "UnstakingRequestEvent", vec![0]),
+                make_event_for_testing(sender, "validator_set", "StakingRequestEvent", vec![1]),
+                make_event_for_testing(sender, "validator", "StakingRequestEvent", vec![2]),
+                make_event_for_testing(sender, "validator", "StakingRequestEvent", vec![3]),
+            ],
+        };
+        let effects = ExecutionEffects::new_for_testing(TransactionEffects::default(), events);
+        // Both the module and the name have to match, the first such event is returned
+        let event = effects
+            .find_event("validator", "StakingRequestEvent")
+            .unwrap();
+        assert_eq!(event.contents, vec![2]);
+        assert!(effects.find_event("validator", "StakingEvent").is_none());
+        assert!(effects
+            .find_event("staking_pool", "StakingRequestEvent")
+            .is_none());
+        let effects = ExecutionEffects::new_for_testing(
+            TransactionEffects::default(),
+            TransactionEvents::default(),
+        );
+        assert!(effects
+            .find_event("validator", "StakingRequestEvent")
+            .is_none());
+    }
+
+    #[test]
+    fn test_created_staked_sui() {
+        let staker = SuiAddress::random_for_testing_only();
+        let other = SuiAddress::random_for_testing_only();
+        let digest = TransactionDigest::random();
+        let created_ref = |creation_num| {
+            (
+                ObjectID::derive_id(digest, creation_num),
+                SequenceNumber::from_u64(1),
+                ObjectDigest::random(),
+            )
+        };
+        // The transaction created an object for another address, then the StakedSui, then a
+        // coin for the staker, listed out of creation order along with an object whose id is
+        // not derived from the digest
+        let (foreign, staked_sui, coin) = (created_ref(0), created_ref(1), created_ref(2));
+        let dynamic_field = random_object_ref();
+        let effects = ExecutionEffects::new_for_testing(
+            TransactionEffects::V1(TransactionEffectsV1 {
+                transaction_digest: digest,
+                created: vec![
+                    (dynamic_field, Owner::AddressOwner(staker)),
+                    (coin, Owner::AddressOwner(staker)),
+                    (staked_sui, Owner::AddressOwner(staker)),
+                    (foreign, Owner::AddressOwner(other)),
+                ],
+                ..Default::default()
+            }),
+            TransactionEvents::default(),
+        );
+        let ordered: Vec<_> = effects
+            .created_in_creation_order()
+            .into_iter()
+            .map(|(obj_ref, _)| obj_ref)
+            .collect();
+        assert_eq!(ordered, vec![foreign, staked_sui, coin, dynamic_field]);
+        assert_eq!(effects.created_staked_sui(staker), Some(staked_sui));
+        assert_eq!(effects.created_staked_sui(other), Some(foreign));
+        assert_eq!(
+            effects.created_staked_sui(SuiAddress::random_for_testing_only()),
+            None
+        );
+    }
+}
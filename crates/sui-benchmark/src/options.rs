@@ -2,9 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use clap::*;
+use std::path::PathBuf;
+use std::time::Duration;
+use sui_types::base_types::ObjectID;
 
 use strum_macros::EnumString;
 
+use crate::drivers::bench_driver::ThinkTime;
 use crate::drivers::Interval;
 
 #[derive(Parser)]
@@ -44,6 +48,15 @@ pub struct Opts {
     pub primary_gas_objects: u64,
     #[clap(long, default_value = "500", global = true)]
     pub gas_request_chunk_size: u64,
+    /// File the accounts funded for the workloads are saved to, so that later runs with the
+    /// same workloads reuse them instead of funding new ones. If the file exists, its accounts
+    /// are checked to still hold their coins and reused. The file holds private keys in the
+    /// clear, so it needs `--insecure-plaintext-keystore`.
+    #[clap(long, global = true)]
+    pub gas_keystore: Option<PathBuf>,
+    /// Acknowledges that the file of `--gas-keystore` holds unencrypted private keys
+    #[clap(long, global = true)]
+    pub insecure_plaintext_keystore: bool,
     /// Whether to run local or remote benchmark
     /// NOTE: For running remote benchmark we must have the following
     /// genesis_blob_path, keypair_path and primary_gas_id
@@ -73,6 +86,10 @@ pub struct Opts {
     /// Default workload is 100% transfer object
     #[clap(subcommand)]
     pub run_spec: RunSpec,
+    /// TOML file declaring the workloads to run with their weights and parameters, in place of
+    /// the workload options of the run spec. See `WorkloadConfigFile` for the format.
+    #[clap(long, global = true)]
+    pub workload_config: Option<PathBuf>,
     #[clap(long, default_value = "9091", global = true)]
     pub server_metric_port: u16,
     #[clap(long, default_value = "127.0.0.1", global = true)]
@@ -93,6 +110,11 @@ pub struct Opts {
     /// "10000"
     #[clap(long, global = true, default_value = "unbounded")]
     pub run_duration: Interval,
+    /// Duration at the start of the benchmark during which
+    /// transactions are executed but left out of the reported
+    /// stats, e.g. "30s". Counts towards `run_duration`.
+    #[clap(long, global = true, default_value = "0s", parse(try_from_str = duration_str::parse))]
+    pub warmup: Duration,
     /// Path where benchmark stats is stored
     #[clap(long, default_value = "/tmp/bench_result", global = true)]
     pub benchmark_stats_path: String,
@@ -108,6 +130,69 @@ pub struct Opts {
     // the end of the benchmark or periodically during a continuous run.
     #[clap(long, action, global = true)]
     pub stress_stat_collection: bool,
+    // Fraction (0.0 to 1.0) of transactions for which the benchmark stops waiting
+    // after `abandon_after_ms` and retires the payload. Validators cannot cancel
+    // a submitted transaction, so abandoned transactions may still be executed.
+    #[clap(long, default_value = "0.0", global = true)]
+    pub abandon_ratio: f32,
+    #[clap(long, default_value = "100", global = true)]
+    pub abandon_after_ms: u64,
+    // Number of transactions submitted at once, then none for `burst_gap`, instead of
+    // submitting at the target qps. Shared among all workers in proportion to their qps.
+    #[clap(long, global = true)]
+    pub burst_size: Option<u64>,
+    #[clap(long, global = true, default_value = "1s", parse(try_from_str = duration_str::parse))]
+    pub burst_gap: Duration,
+    // Check every transaction locally (structure, gas budget and price against the latest
+    // system state) before submitting it. Transactions failing the checks are counted as
+    // invalid and not submitted, which keeps harness bugs out of the error rate.
+    #[clap(long, action, global = true)]
+    pub validate_locally: bool,
+    // Check the state of every payload after each response and end the benchmark with an error
+    // if it is invalid, to catch workload bugs. Always on in debug builds.
+    #[clap(long, action, global = true)]
+    pub check_payload_state: bool,
+    // End the benchmark with an error as soon as more than this percentage (0.0 to 100.0) of
+    // transactions failed over the last `abort_on_failure_window`, for use as a pass/fail gate.
+    // Transactions failing in execution count as failures, as well as those the validators
+    // returned an error for.
+    #[clap(long, global = true)]
+    pub abort_on_failure_rate: Option<f32>,
+    #[clap(long, global = true, default_value = "30s", parse(try_from_str = duration_str::parse))]
+    pub abort_on_failure_window: Duration,
+    // Randomized time every payload waits after the response to a transaction before its next
+    // one, as `{distribution}:{mean}` where the distribution is uniform or exponential, e.g.
+    // "exponential:500ms". Payloads submit as soon as the pacing allows by default.
+    #[clap(long, global = true)]
+    pub think_time: Option<ThinkTime>,
+    // Read the gas coins of every fresh transaction before submitting it, for workloads which
+    // know the balance their transactions need. Transactions the coins cannot cover are counted
+    // as invalid and not submitted, and their payload is retired.
+    #[clap(long, action, global = true)]
+    pub check_funding: bool,
+    // Label of the run, recorded in the benchmark stats and with every digest in `digest_log`,
+    // to tell apart runs sharing a network
+    #[clap(long, global = true)]
+    pub run_label: Option<String>,
+    // File to append the digest, run label and workload of every submitted transaction to, one
+    // line of JSON per transaction
+    #[clap(long, global = true)]
+    pub digest_log: Option<PathBuf>,
+    // End the benchmark after this many epoch changes, or at the end of `run_duration` if that
+    // comes first. Results are broken down by epoch whenever the run spans epochs.
+    #[clap(long, global = true)]
+    pub run_epochs: Option<u64>,
+    // File to record every submitted transaction to, so that the run can be replayed with
+    // `replay_trace`
+    #[clap(long, global = true)]
+    pub record_trace: Option<PathBuf>,
+    // Submit the transactions recorded in this trace one after the other instead of running
+    // workloads. Only the transactions of payloads are recorded, not those funding and setting
+    // up the workloads, so the network must be a snapshot taken once the recorded run had set
+    // up its workloads, which is checked before replaying. Inputs at other versions than
+    // recorded are reported.
+    #[clap(long, global = true)]
+    pub replay_trace: Option<PathBuf>,
 
     /// Start the stress test at a given protocol version. (Usually unnecessary if stress test is
     /// built at the same commit as the validators.
@@ -115,7 +200,7 @@ pub struct Opts {
     pub protocol_version: Option<u64>,
 }
 
-#[derive(Debug, Clone, Parser, Eq, PartialEq, EnumString)]
+#[derive(Debug, Clone, Parser, PartialEq, EnumString)]
 #[non_exhaustive]
 #[clap(rename_all = "kebab-case")]
 pub enum RunSpec {
@@ -148,6 +233,95 @@ pub enum RunSpec {
         // relative weight of adversarial transactions in the benchmark workload
         #[clap(long, default_value = "0")]
         adversarial: u32,
+        // relative weight of governance (proposal/vote) transactions in the benchmark workload
+        #[clap(long, default_value = "0")]
+        governance: u32,
+        // relative weight of validator lifecycle (join and leave the validator set) transactions
+        // in the benchmark workload
+        #[clap(long, default_value = "0")]
+        validator_lifecycle: u32,
+        // relative weight of transactions which all increment the shared counter
+        // given by `hotspot_object` in the benchmark workload
+        #[clap(long, default_value = "0")]
+        hotspot: u32,
+        // relative weight of transactions paying gas with several coins in the
+        // benchmark workload
+        #[clap(long, default_value = "0")]
+        gas_smash: u32,
+        // relative weight of transactions just under or just over the maximum
+        // transaction size in the benchmark workload
+        #[clap(long, default_value = "0")]
+        size_limit: u32,
+        // relative weight of dry run reads of the system state, which commit nothing, in the
+        // benchmark workload. Needs a proxy which can dry run, i.e. a fullnode
+        #[clap(long, default_value = "0")]
+        system_state_read: u32,
+        // relative weight of calls to the Move function given by `move_call_target` in the
+        // benchmark workload
+        #[clap(long, default_value = "0")]
+        move_call: u32,
+        // relative weight of transactions spending a coin which is spent at the same time by a
+        // conflicting transaction in the benchmark workload
+        #[clap(long, default_value = "0")]
+        equivocation: u32,
+        // relative weight of transactions reading the on-chain clock in the benchmark workload
+        #[clap(long, default_value = "0")]
+        clock: u32,
+        // relative weight of transactions creating objects of `object_size_bytes` bytes in the
+        // benchmark workload
+        #[clap(long, default_value = "0")]
+        object_size: u32,
+        // relative weight of transactions paid for by a few sponsors on behalf of many senders
+        // in the benchmark workload
+        #[clap(long, default_value = "0")]
+        gas_station: u32,
+        // relative weight of transfers of a coin back and forth between two accounts, the
+        // cheapest chain of transactions there is, in the benchmark workload
+        #[clap(long, default_value = "0")]
+        ping_pong: u32,
+        // relative weight of transactions upgrading packages and calling the upgraded versions
+        // in the benchmark workload
+        #[clap(long, default_value = "0")]
+        upgrade: u32,
+        // relative weight of calls of a generic function with `type_args_count` type arguments
+        // in the benchmark workload
+        #[clap(long, default_value = "0")]
+        type_args: u32,
+        // relative weight of transactions staking a coin and withdrawing the previous stake in
+        // a single programmable transaction block in the benchmark workload
+        #[clap(long, default_value = "0")]
+        stake_withdraw: u32,
+        // relative weight of transfers signed with Ed25519, Secp256k1 and Secp256r1 keys in the
+        // proportions of `signature_schemes_mix` in the benchmark workload
+        #[clap(long, default_value = "0")]
+        signature_schemes: u32,
+        // relative weight of calls aborting with `move_abort_code` in the benchmark workload
+        #[clap(long, default_value = "0")]
+        move_abort: u32,
+        // relative weight of transfers of which a fraction has a gas budget under their cost in
+        // the benchmark workload
+        #[clap(long, default_value = "0")]
+        insufficient_gas: u32,
+        // relative weight of transfers of coins of a custom coin type, and of SUI coins for
+        // comparison, in the benchmark workload
+        #[clap(long, default_value = "0")]
+        custom_coin: u32,
+        // relative weight of calls with a pure input of each of `pure_input_sizes` in the
+        // benchmark workload
+        #[clap(long, default_value = "0")]
+        pure_input: u32,
+        // relative weight of transactions creating objects, freezing them and reading the frozen
+        // objects in the benchmark workload
+        #[clap(long, default_value = "0")]
+        freeze: u32,
+        // relative weight of sums of vectors of each of `vector_sum_lengths` in the benchmark
+        // workload
+        #[clap(long, default_value = "0")]
+        vector_sum: u32,
+        // relative weight of payloads mixing dry run reads with committed writes, in the
+        // proportion of `read_write_read_ratio`, in the benchmark workload
+        #[clap(long, default_value = "0")]
+        read_write: u32,
 
         // --- workload-specific options --- (TODO: use subcommands or similar)
         // 100 for max hotness i.e all requests target
@@ -158,6 +332,11 @@ pub enum RunSpec {
         // total_shared_counters = max(1, qps * (1.0 - hotness/100.0))
         #[clap(long, default_value = "50")]
         shared_counter_hotness_factor: u32,
+        // number of addresses owning the shared counter payloads in turn, every payload has
+        // its own address if not set. Payloads sharing an address still use separate gas
+        // coins, so this adds contention per sender rather than on objects
+        #[clap(long)]
+        shared_counter_distinct_addresses: Option<u64>,
         // batch size use for batch payment workload
         #[clap(long, default_value = "15")]
         batch_payment_size: u32,
@@ -167,6 +346,128 @@ pub enum RunSpec {
         // Default is (0-0.5) implying random load at 50% load. See `AdversarialPayloadType` enum for `adversarial_type`
         #[clap(long, default_value = "0-1.0")]
         adversarial_cfg: String,
+        // percentage (0 to 100) of governance transactions which create a new
+        // proposal, the rest vote on an existing proposal
+        #[clap(long, default_value = "10")]
+        governance_proposal_ratio: u32,
+        // amounts (in MIST) staked by delegation transactions and their relative weights.
+        // Format is "{amount}:{weight},{amount}:{weight},..." and each delegation payload samples
+        // one amount. Every stake is split out of the payload's gas coin, so large amounts exhaust
+        // it faster. Amounts must be at least the minimum stake of 1 SUI.
+        #[clap(long, default_value = "1000000000:1")]
+        delegation_stake_amounts: String,
+        // seed for sampling delegation stake amounts, random if not set
+        #[clap(long)]
+        delegation_seed: Option<u64>,
+        // stake with validators of the current committee instead of pinning each delegation
+        // payload to a validator of the committee at the start of the run
+        #[clap(long)]
+        delegation_track_committee: bool,
+        // fraction (0.0 to 1.0) of delegation transactions after which the payload re-reads its
+        // objects from the proxy instead of trusting the effects, like a client which does not
+        // track effects. Every re-read is an extra RPC call
+        #[clap(long, default_value = "0.0")]
+        delegation_refetch_ratio: f32,
+        // check that every successful stake of the delegation workload emitted a staking event
+        // for its validator and amount. Only effects returned by validators carry events
+        #[clap(long)]
+        delegation_check_events: bool,
+        // fraction (0.0 to 1.0) of delegation transactions which must succeed, checked at the end
+        // of the run, which fails otherwise even if other workloads did fine
+        #[clap(long)]
+        delegation_min_success_rate: Option<f32>,
+        // shared counter (a `counter::Counter` of the basics package, e.g. created by
+        // an earlier shared counter run) targeted by every hotspot transaction
+        #[clap(long)]
+        hotspot_object: Option<ObjectID>,
+        // number of gas coins smashed together by every gas smash transaction
+        #[clap(long, default_value = "10")]
+        gas_smash_coins_per_tx: u32,
+        // distance in bytes from the maximum transaction size of every size limit transaction
+        #[clap(long, default_value = "100")]
+        size_limit_offset_bytes: u64,
+        // fraction (0.0 to 1.0) of size limit transactions which are over the maximum size and
+        // should be rejected, the rest are under it
+        #[clap(long, default_value = "0.5")]
+        size_limit_oversized_ratio: f32,
+        // function called by every move call transaction, as "{package}::{module}::{function}"
+        #[clap(long)]
+        move_call_target: Option<String>,
+        // comma separated type arguments of the move call, e.g. "0x2::sui::SUI"
+        #[clap(long, default_value = "")]
+        move_call_type_args: String,
+        // comma separated arguments of the move call, filled in for every call. Each one is
+        // `gas` (the gas coin, by reference only), `sender`, `created` (the object created by
+        // `move_call_setup_function`, at its latest version), `object:{id}` (a shared or
+        // immutable object) or a pure literal `{type}:{value}` where type is one of bool, u8,
+        // u16, u32, u64, u128 or address. E.g. "created,u64:10,sender"
+        #[clap(long, default_value = "")]
+        move_call_args: String,
+        // function of the same module called without arguments to create the object of a
+        // `created` argument, before the first call and whenever the object is gone
+        #[clap(long)]
+        move_call_setup_function: Option<String>,
+        // fraction (0.0 to 1.0) of equivocation transactions which are submitted together with
+        // a conflicting transaction, the rest are submitted alone
+        #[clap(long, default_value = "0.5")]
+        equivocation_ratio: f32,
+        // number of bytes held by every object created by the object size workload. Objects over
+        // the maximum object size fail to be created
+        #[clap(long, default_value = "1024")]
+        object_size_bytes: u64,
+        // number of senders whose gas station transactions are paid for by the same sponsor
+        #[clap(long, default_value = "10")]
+        gas_station_senders_per_sponsor: u64,
+        // number of coins every gas station sponsor pays with. Each coin is used by one
+        // transaction at a time, so this bounds the transactions of a sponsor in flight
+        #[clap(long, default_value = "5")]
+        gas_station_coins_per_sponsor: u64,
+        // number of type arguments of every call of the type args workload, at most 16
+        #[clap(long, default_value = "4")]
+        type_args_count: u64,
+        // number of structs nested in every type argument of the type args workload, e.g.
+        // `Pair<Wrapper<u64>, u64>` for 2
+        #[clap(long, default_value = "2")]
+        type_args_depth: u64,
+        // signature schemes of the owners of signature schemes payloads and their relative
+        // weights. Format is "{scheme}:{weight},..." where scheme is one of ed25519, secp256k1
+        // or secp256r1
+        #[clap(long, default_value = "ed25519:1,secp256k1:1,secp256r1:1")]
+        signature_schemes_mix: String,
+        // code every call of the move abort workload aborts with
+        #[clap(long, default_value = "1")]
+        move_abort_code: u64,
+        // fraction (0.0 to 1.0) of insufficient gas transactions whose gas budget is under their
+        // cost and which should run out of gas, the rest are fully funded
+        #[clap(long, default_value = "0.5")]
+        insufficient_gas_ratio: f32,
+        // fraction (0.0 to 1.0) of their cost which the gas budget of under-funded transactions
+        // lacks. The budget never goes under the minimum gas budget
+        #[clap(long, default_value = "0.5")]
+        insufficient_gas_margin: f32,
+        // amount of the custom coin minted for every custom coin payload
+        #[clap(long, default_value = "1000000")]
+        custom_coin_mint_amount: u64,
+        // number of accounts the coins of every custom coin payload are transferred around, 1
+        // for transfers of the coins to their own owner
+        #[clap(long, default_value = "2")]
+        custom_coin_ring_size: u64,
+        // comma separated lengths in bytes of the pure inputs of the pure input workload, e.g.
+        // "16,1024,16000". Payloads cycle through them and the latency is reported by length
+        #[clap(long, default_value = "1024")]
+        pure_input_sizes: String,
+        // number of objects the freeze workload creates and freezes in a run, split between its
+        // payloads with at least one each, after which payloads read the frozen objects
+        #[clap(long, default_value = "1000")]
+        freeze_objects: u64,
+        // comma separated lengths of the vectors summed by the vector sum workload, e.g.
+        // "10,1000,10000". The computation cost of sums is reported by length
+        #[clap(long, default_value = "1000")]
+        vector_sum_lengths: String,
+        // probability for every transaction of the read/write workload to be a dry run read
+        // rather than a write, from 0.0 for writes only to 1.0 for reads only
+        #[clap(long, default_value = "0.5")]
+        read_write_read_ratio: f32,
 
         // --- generic options ---
         // Target qps
@@ -175,7 +476,7 @@ pub enum RunSpec {
         // Number of workers
         #[clap(long, default_value = "12", global = true)]
         num_workers: u64,
-        // Max in-flight ratio
+        // Max in-flight ratio, lowered if target qps times the ratio exceeds 100000 operations
         #[clap(long, default_value = "5", global = true)]
         in_flight_ratio: u64,
     },
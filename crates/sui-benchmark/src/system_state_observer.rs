@@ -5,23 +5,29 @@ use crate::ValidatorProxy;
 use std::sync::Arc;
 use std::time::Duration;
 use sui_protocol_config::{ProtocolConfig, ProtocolVersion};
+use sui_types::base_types::SuiAddress;
 use tokio::sync::oneshot::Sender;
 use tokio::sync::watch;
 use tokio::sync::watch::Receiver;
+use tokio::sync::Notify;
 use tokio::time;
 use tokio::time::Instant;
 use tracing::{error, info};
 
 #[derive(Debug, Clone)]
 pub struct SystemState {
+    pub epoch: u64,
     pub reference_gas_price: u64,
     pub protocol_config: Option<ProtocolConfig>,
+    /// Sui addresses of the validators in the current committee
+    pub active_validators: Vec<SuiAddress>,
 }
 
 #[derive(Debug)]
 pub struct SystemStateObserver {
     pub state: Receiver<SystemState>,
     pub _sender: Sender<()>,
+    refresh: Arc<Notify>,
 }
 
 impl SystemStateObserver {
@@ -30,32 +36,71 @@ impl SystemStateObserver {
         let mut interval = tokio::time::interval_at(Instant::now(), Duration::from_secs(60));
         interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
         let (tx, rx) = watch::channel(SystemState {
+            epoch: 0,
             reference_gas_price: 1u64,
             protocol_config: None,
+            active_validators: vec![],
         });
+        let refresh = Arc::new(Notify::new());
+        let refresh_cloned = refresh.clone();
         tokio::task::spawn(async move {
             loop {
                 tokio::select! {
-                    _ = interval.tick() => {
-                        match proxy.get_latest_system_state_object().await {
-                            Ok(result) => {
-                                let p = ProtocolConfig::get_for_version(ProtocolVersion::new(result.protocol_version));
-                                if tx.send(SystemState {reference_gas_price: result.reference_gas_price,protocol_config: Some(p)}).is_ok() {
-                                    info!("Reference gas price = {:?}", result.reference_gas_price    );
-                                }
-                            }
-                            Err(err) => {
-                                error!("Failed to get system state object: {:?}", err);
-                            }
+                    _ = interval.tick() => (),
+                    // Refreshes between ticks when asked to
+                    _ = refresh_cloned.notified() => (),
+                    _ = &mut recv => break,
+                }
+                match proxy.get_latest_system_state_object().await {
+                    Ok(result) => {
+                        let p = ProtocolConfig::get_for_version(ProtocolVersion::new(
+                            result.protocol_version,
+                        ));
+                        let active_validators = result
+                            .active_validators
+                            .iter()
+                            .map(|v| v.sui_address)
+                            .collect();
+                        if tx
+                            .send(SystemState {
+                                epoch: result.epoch,
+                                reference_gas_price: result.reference_gas_price,
+                                protocol_config: Some(p),
+                                active_validators,
+                            })
+                            .is_ok()
+                        {
+                            info!("Reference gas price = {:?}", result.reference_gas_price);
                         }
                     }
-                    _ = &mut recv => break,
+                    Err(err) => {
+                        error!("Failed to get system state object: {:?}", err);
+                    }
                 }
             }
         });
         Self {
             state: rx,
             _sender: sender,
+            refresh,
+        }
+    }
+
+    /// Observer of a fixed `state`, which is never read from a proxy
+    #[cfg(test)]
+    pub fn new_for_testing(state: SystemState) -> Self {
+        let (sender, _) = tokio::sync::oneshot::channel();
+        let (_, rx) = watch::channel(state);
+        Self {
+            state: rx,
+            _sender: sender,
+            refresh: Arc::new(Notify::new()),
         }
     }
+
+    /// Asks for the system state to be read again without waiting for the next periodic read,
+    /// e.g. after noticing an epoch change. The new state is still published asynchronously.
+    pub fn request_refresh(&self) {
+        self.refresh.notify_one();
+    }
 }
@@ -1,13 +1,15 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use sui_keys::keystore::{AccountKeystore, FileBasedKeystore};
 use sui_types::{base_types::SuiAddress, crypto::SuiKeyPair};
 
-use crate::ValidatorProxy;
+use crate::{ExecutionEffects, ValidatorProxy};
+use std::future::Future;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use sui_types::base_types::ObjectRef;
 use sui_types::messages::{TransactionData, VerifiedTransaction, DUMMY_GAS_PRICE};
 use sui_types::utils::to_sender_signed_transaction;
@@ -16,6 +18,7 @@ use crate::workloads::Gas;
 use sui_types::crypto::{AccountKeyPair, KeypairTraits};
 use test_utils::messages::create_publish_move_package_transaction;
 use test_utils::transaction::parse_package_ref;
+use tracing::warn;
 
 // This is the maximum gas we will transfer from primary coin into any gas coin
 // for running the benchmark
@@ -23,6 +26,11 @@ pub const MAX_GAS_FOR_TESTING: u64 = 1_000_000_000;
 
 pub type UpdatedAndNewlyMintedGasCoins = (Gas, Gas, Vec<Gas>);
 
+/// Reads of the committee before `get_validators_with_retry` gives up
+const GET_VALIDATORS_ATTEMPTS: u32 = 5;
+/// Wait after the first failed read of the committee, doubled after every further one
+const GET_VALIDATORS_BACKOFF: Duration = Duration::from_millis(500);
+
 pub fn get_ed25519_keypair_from_keystore(
     keystore_path: PathBuf,
     requested_address: &SuiAddress,
@@ -64,11 +72,115 @@ pub async fn publish_basics_package(
 ) -> ObjectRef {
     let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     path.push("../../sui_programmability/examples/basics");
+    let effects = publish_package(path, gas, proxy, sender, keypair, gas_price).await;
+    parse_package_ref(&effects.created()).unwrap()
+}
+
+/// Publish the Move package at `path` and return the effects of the publish transaction.
+pub async fn publish_package(
+    path: PathBuf,
+    gas: ObjectRef,
+    proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+    sender: SuiAddress,
+    keypair: &AccountKeyPair,
+    gas_price: u64,
+) -> ExecutionEffects {
     let transaction =
         create_publish_move_package_transaction(gas, path, sender, keypair, Some(gas_price));
-    let effects = proxy
+    proxy
         .execute_transaction_block(transaction.into())
         .await
+        .unwrap()
+}
+
+/// Active validators read through `proxy`, retried with backoff while the read fails or returns
+/// no validators, as it can around reconfigurations. Fails once the attempts are exhausted, so
+/// that workloads picking validators for their payloads never see an empty committee.
+pub async fn get_validators_with_retry(
+    proxy: &Arc<dyn ValidatorProxy + Sync + Send>,
+) -> Result<Vec<SuiAddress>> {
+    retry_get_validators(|| proxy.get_validators(), GET_VALIDATORS_BACKOFF).await
+}
+
+/// Calls `get_validators` until it returns validators, waiting `backoff` after the first failed
+/// read and twice as long after every further one
+async fn retry_get_validators<F, Fut>(
+    mut get_validators: F,
+    mut backoff: Duration,
+) -> Result<Vec<SuiAddress>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Vec<SuiAddress>>>,
+{
+    for attempt in 1..=GET_VALIDATORS_ATTEMPTS {
+        match get_validators().await {
+            Ok(validators) if !validators.is_empty() => return Ok(validators),
+            Ok(_) => warn!(
+                "No active validators (attempt {} of {})",
+                attempt, GET_VALIDATORS_ATTEMPTS
+            ),
+            Err(e) => warn!(
+                "Failed to fetch validators (attempt {} of {}): {}",
+                attempt, GET_VALIDATORS_ATTEMPTS, e
+            ),
+        }
+        if attempt < GET_VALIDATORS_ATTEMPTS {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+    bail!(
+        "No active validators after {} attempts",
+        GET_VALIDATORS_ATTEMPTS
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    const BACKOFF: Duration = Duration::from_millis(1);
+
+    #[tokio::test]
+    async fn test_get_validators_retries_empty_committee() {
+        let validators = vec![SuiAddress::random_for_testing_only()];
+        let reads = AtomicU32::new(0);
+        // Empty during a reconfiguration, then a failed read, then the new committee
+        let fetched = retry_get_validators(
+            || {
+                let read = reads.fetch_add(1, Ordering::SeqCst);
+                let validators = validators.clone();
+                async move {
+                    match read {
+                        0 => Ok(vec![]),
+                        1 => Err(anyhow!("connection reset")),
+                        _ => Ok(validators),
+                    }
+                }
+            },
+            BACKOFF,
+        )
+        .await
         .unwrap();
-    parse_package_ref(&effects.created()).unwrap()
+        assert_eq!(fetched, validators);
+        assert_eq!(reads.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_get_validators_gives_up_on_empty_committee() {
+        let reads = AtomicU32::new(0);
+        let err = retry_get_validators(
+            || {
+                reads.fetch_add(1, Ordering::SeqCst);
+                async { Ok(vec![]) }
+            },
+            BACKOFF,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("No active validators"), "{}", err);
+        assert_eq!(reads.load(Ordering::SeqCst), GET_VALIDATORS_ATTEMPTS);
+    }
 }
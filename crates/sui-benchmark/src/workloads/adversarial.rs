@@ -12,7 +12,7 @@ use crate::workloads::payload::Payload;
 use crate::workloads::{Gas, GasCoinConfig};
 use crate::ProgrammableTransactionBuilder;
 use crate::{convert_move_call_args, BenchMoveCallArg, ExecutionEffects, ValidatorProxy};
-use anyhow::anyhow;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use itertools::Itertools;
 use move_core_types::identifier::Identifier;
@@ -453,13 +453,14 @@ impl Workload<dyn Payload> for AdversarialWorkload {
         &mut self,
         proxy: Arc<dyn ValidatorProxy + Sync + Send>,
         system_state_observer: Arc<SystemStateObserver>,
-    ) {
+    ) -> Result<()> {
         let gas = &self.init_gas;
         let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         path.push("src/workloads/data/adversarial");
         let SystemState {
             reference_gas_price,
             protocol_config,
+            ..
         } = system_state_observer.state.borrow().clone();
         let protocol_config = protocol_config.unwrap();
         let gas_budget = protocol_config.max_tx_gas();
@@ -534,6 +535,7 @@ impl Workload<dyn Payload> for AdversarialWorkload {
             .iter()
             .map(|o| BenchMoveCallArg::Shared((o.0 .0, o.0 .1, false)))
             .collect();
+        Ok(())
     }
 
     async fn make_test_payloads(
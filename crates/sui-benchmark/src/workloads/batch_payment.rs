@@ -1,6 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use anyhow::Result;
 use async_trait::async_trait;
 use sui_types::base_types::{ObjectID, SequenceNumber};
 use sui_types::digests::ObjectDigest;
@@ -195,7 +196,8 @@ impl Workload<dyn Payload> for BatchPaymentWorkload {
         &mut self,
         _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
         _system_state_observer: Arc<SystemStateObserver>,
-    ) {
+    ) -> Result<()> {
+        Ok(())
     }
 
     async fn make_test_payloads(
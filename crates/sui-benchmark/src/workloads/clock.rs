@@ -0,0 +1,251 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::in_memory_wallet::move_call_pt_impl;
+use crate::system_state_observer::SystemStateObserver;
+use crate::util::publish_basics_package;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{BenchMoveCallArg, ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use sui_types::base_types::ObjectID;
+use sui_types::crypto::get_key_pair;
+use sui_types::messages::VerifiedTransaction;
+use sui_types::{SUI_CLOCK_OBJECT_ID, SUI_CLOCK_OBJECT_SHARED_VERSION};
+use tracing::{error, info};
+
+/// Timestamps read by the transactions of all payloads of the workload
+#[derive(Debug, Default)]
+struct ClockStats {
+    reads: AtomicU64,
+    /// Reads which returned an earlier timestamp than the previous read of the same payload
+    regressions: AtomicU64,
+}
+
+impl ClockStats {
+    fn counters(&self) -> Vec<(String, u64)> {
+        vec![
+            ("reads".into(), self.reads.load(Ordering::Relaxed)),
+            (
+                "regressions".into(),
+                self.regressions.load(Ordering::Relaxed),
+            ),
+        ]
+    }
+}
+
+/// Timestamp emitted by `basics::clock::get_time`, if the effects carry its event
+fn read_timestamp_ms(effects: &ExecutionEffects) -> Option<u64> {
+    effects
+        .find_event("clock", "TimeEvent")
+        .and_then(|event| bcs::from_bytes(&event.contents).ok())
+}
+
+#[derive(Debug)]
+pub struct ClockTestPayload {
+    package_id: ObjectID,
+    gas: Gas,
+    /// Timestamp read by the previous transaction of this payload. Transactions of a payload
+    /// are ordered by their gas coin, so the timestamps they read must never go back
+    last_timestamp_ms: Option<u64>,
+    stats: Arc<ClockStats>,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for ClockTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "clock")
+    }
+}
+
+impl Payload for ClockTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        self.gas.0 = effects.gas_object().0;
+        let timestamp_ms = match read_timestamp_ms(effects) {
+            Some(timestamp_ms) => timestamp_ms,
+            None => return,
+        };
+        if let Some(last_timestamp_ms) = self.last_timestamp_ms {
+            if timestamp_ms < last_timestamp_ms {
+                self.stats.regressions.fetch_add(1, Ordering::Relaxed);
+                error!(
+                    "Clock went back from {} ms to {} ms between transactions of one sender",
+                    last_timestamp_ms, timestamp_ms
+                );
+            }
+        }
+        self.last_timestamp_ms = Some(timestamp_ms);
+        self.stats.reads.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let (gas_budget, gas_price) = {
+            let state = self.system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        // Entry functions can only take the clock by immutable reference, only the consensus
+        // commit prologue writes to it
+        move_call_pt_impl(
+            self.gas.1,
+            &self.gas.2,
+            self.package_id,
+            "clock",
+            "get_time",
+            vec![],
+            vec![BenchMoveCallArg::Shared((
+                SUI_CLOCK_OBJECT_ID,
+                SUI_CLOCK_OBJECT_SHARED_VERSION,
+                false,
+            ))],
+            &self.gas.0,
+            gas_budget,
+            gas_price,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct ClockWorkloadBuilder {
+    num_payloads: u64,
+}
+
+impl ClockWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder =
+                Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(ClockWorkloadBuilder {
+                    num_payloads: max_ops,
+                }));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for ClockWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        // Gas coin for publishing package
+        let (address, keypair) = get_key_pair();
+        vec![GasCoinConfig {
+            amount: MAX_GAS_FOR_TESTING,
+            address,
+            keypair: Arc::new(keypair),
+        }]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        (0..self.num_payloads)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(ClockWorkload {
+            basics_package_id: None,
+            init_gas,
+            payload_gas,
+            stats: Arc::default(),
+        }))
+    }
+}
+
+/// Reads the on-chain `Clock` through `basics::clock::get_time`. The clock is a system object,
+/// shared since genesis at a well-known initial version and advanced by every consensus commit
+/// rather than by transactions, so this measures the read path of shared objects without
+/// contention between transactions. Timestamps are checked for monotonicity when the proxy
+/// returns events, i.e. when running against validators.
+#[derive(Debug)]
+pub struct ClockWorkload {
+    basics_package_id: Option<ObjectID>,
+    init_gas: Vec<Gas>,
+    payload_gas: Vec<Gas>,
+    stats: Arc<ClockStats>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for ClockWorkload {
+    async fn init(
+        &mut self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        if self.basics_package_id.is_some() {
+            return Ok(());
+        }
+        let gas_price = system_state_observer.state.borrow().reference_gas_price;
+        let gas = self
+            .init_gas
+            .first()
+            .expect("Not enough gas to initialize clock workload");
+        info!("Publishing basics package");
+        self.basics_package_id = Some(
+            publish_basics_package(gas.0, proxy, gas.1, &gas.2, gas_price)
+                .await
+                .0,
+        );
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        self.payload_gas
+            .iter()
+            .map(|gas| {
+                Box::new(ClockTestPayload {
+                    package_id: self.basics_package_id.unwrap(),
+                    gas: gas.clone(),
+                    last_timestamp_ms: None,
+                    stats: self.stats.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        self.stats.counters()
+    }
+}
@@ -0,0 +1,403 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::in_memory_wallet::move_call_pt_impl;
+use crate::system_state_observer::SystemStateObserver;
+use crate::util::publish_package;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{BenchMoveCallArg, ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use sui_types::base_types::{ObjectID, ObjectRef, SuiAddress};
+use sui_types::coin::COIN_TREASURE_CAP_NAME;
+use sui_types::crypto::get_key_pair;
+use sui_types::messages::{TransactionData, VerifiedTransaction};
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::utils::to_sender_signed_transaction;
+use test_utils::transaction::parse_package_ref;
+use tracing::info;
+
+const MODULE_NAME: &str = "bench_coin";
+/// Recipients of the coins minted by every transaction of the initialization
+const MINT_CHUNK_SIZE: usize = 100;
+
+/// Coin transferred by a transaction of the workload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TransferredCoin {
+    Custom,
+    Sui,
+}
+
+impl std::fmt::Display for TransferredCoin {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TransferredCoin::Custom => write!(f, "custom_coin"),
+            TransferredCoin::Sui => write!(f, "sui"),
+        }
+    }
+}
+
+/// Gas used by the transfers of all payloads of the workload, by coin type
+#[derive(Debug, Default)]
+struct CustomCoinStats {
+    custom_transfers: AtomicU64,
+    custom_gas_used: AtomicU64,
+    sui_transfers: AtomicU64,
+    sui_gas_used: AtomicU64,
+}
+
+impl CustomCoinStats {
+    fn record(&self, coin: TransferredCoin, gas_used: u64) {
+        let (transfers, total_gas_used) = match coin {
+            TransferredCoin::Custom => (&self.custom_transfers, &self.custom_gas_used),
+            TransferredCoin::Sui => (&self.sui_transfers, &self.sui_gas_used),
+        };
+        total_gas_used.fetch_add(gas_used, Ordering::Relaxed);
+        transfers.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        vec![
+            (
+                "custom coin transfers".into(),
+                self.custom_transfers.load(Ordering::Relaxed),
+            ),
+            (
+                "custom coin gas used".into(),
+                self.custom_gas_used.load(Ordering::Relaxed),
+            ),
+            (
+                "sui transfers".into(),
+                self.sui_transfers.load(Ordering::Relaxed),
+            ),
+            (
+                "sui gas used".into(),
+                self.sui_gas_used.load(Ordering::Relaxed),
+            ),
+        ]
+    }
+}
+
+#[derive(Debug)]
+pub struct CustomCoinTestPayload {
+    /// Accounts the coins go around, every one of them paying for its own transfers
+    accounts: Vec<Gas>,
+    /// Account holding both coins
+    holder: usize,
+    custom_coin: ObjectRef,
+    sui_coin: ObjectRef,
+    /// Coin transferred by the next transaction. The holder transfers the custom coin, then the
+    /// SUI coin, so that both are transferred as often and between the same accounts.
+    next: TransferredCoin,
+    stats: Arc<CustomCoinStats>,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for CustomCoinTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "custom_coin")
+    }
+}
+
+impl Payload for CustomCoinTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        self.stats.record(self.next, effects.gas_used());
+        self.accounts[self.holder].0 = effects.gas_object().0;
+        let coin = match self.next {
+            TransferredCoin::Custom => &mut self.custom_coin,
+            TransferredCoin::Sui => &mut self.sui_coin,
+        };
+        let id = coin.0;
+        *coin = effects
+            .mutated()
+            .iter()
+            .find(|(obj_ref, _)| obj_ref.0 == id)
+            .expect("Transferred coin should be mutated")
+            .0;
+        self.next = match self.next {
+            TransferredCoin::Custom => TransferredCoin::Sui,
+            TransferredCoin::Sui => {
+                self.holder = (self.holder + 1) % self.accounts.len();
+                TransferredCoin::Custom
+            }
+        };
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let (gas_budget, gas_price) = {
+            let state = self.system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        let (gas, sender, keypair) = &self.accounts[self.holder];
+        let recipient = self.accounts[(self.holder + 1) % self.accounts.len()].1;
+        let coin = match self.next {
+            TransferredCoin::Custom => self.custom_coin,
+            TransferredCoin::Sui => self.sui_coin,
+        };
+        let mut builder = ProgrammableTransactionBuilder::new();
+        builder.transfer_object(recipient, coin).unwrap();
+        let data = TransactionData::new_programmable(
+            *sender,
+            vec![*gas],
+            builder.finish(),
+            gas_budget,
+            gas_price,
+        );
+        to_sender_signed_transaction(data, keypair.as_ref())
+    }
+
+    fn stats_target(&self) -> Option<String> {
+        Some(self.next.to_string())
+    }
+}
+
+#[derive(Debug)]
+pub struct CustomCoinWorkloadBuilder {
+    num_payloads: u64,
+    mint_amount: u64,
+    ring_size: u64,
+}
+
+impl CustomCoinWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        mint_amount: u64,
+        ring_size: u64,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder = Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(
+                CustomCoinWorkloadBuilder {
+                    num_payloads: max_ops,
+                    mint_amount,
+                    ring_size,
+                },
+            ));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for CustomCoinWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        // Gas coin for publishing the package and minting the coin of every payload
+        let (address, keypair) = get_key_pair();
+        vec![GasCoinConfig {
+            amount: MAX_GAS_FOR_TESTING,
+            address,
+            keypair: Arc::new(keypair),
+        }]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        // For every payload, the gas coin and SUI coin of its first account, then the gas coin
+        // of each of its other accounts
+        let mut configs = vec![];
+        for _ in 0..self.num_payloads {
+            for account in 0..self.ring_size {
+                let (address, keypair) = get_key_pair();
+                let keypair = Arc::new(keypair);
+                let num_coins = if account == 0 { 2 } else { 1 };
+                for _ in 0..num_coins {
+                    configs.push(GasCoinConfig {
+                        amount: MAX_GAS_FOR_TESTING,
+                        address,
+                        keypair: keypair.clone(),
+                    });
+                }
+            }
+        }
+        configs
+    }
+    async fn build(
+        &self,
+        init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(CustomCoinWorkload {
+            package_id: None,
+            minted: HashMap::new(),
+            mint_amount: self.mint_amount,
+            ring_size: self.ring_size as usize,
+            init_gas,
+            payload_gas,
+            stats: Arc::default(),
+        }))
+    }
+}
+
+/// Transfers coins of a custom coin type, minted at initialization with the treasury cap of a
+/// package published by the workload, around a ring of accounts. The same accounts transfer a
+/// SUI coin the same way in between, so that the gas used by the generic coin path can be
+/// compared with the one of SUI. The stats are broken down by coin type.
+#[derive(Debug)]
+pub struct CustomCoinWorkload {
+    package_id: Option<ObjectID>,
+    /// Custom coin minted for the first account of every payload
+    minted: HashMap<SuiAddress, ObjectRef>,
+    mint_amount: u64,
+    ring_size: usize,
+    init_gas: Vec<Gas>,
+    payload_gas: Vec<Gas>,
+    stats: Arc<CustomCoinStats>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for CustomCoinWorkload {
+    async fn init(
+        &mut self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        if self.package_id.is_some() {
+            return Ok(());
+        }
+        let (gas_budget, gas_price) = {
+            let state = system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        let (mut gas, sender, keypair) = self
+            .init_gas
+            .first()
+            .expect("Not enough gas to initialize custom coin workload")
+            .clone();
+        info!("Publishing custom coin package");
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/workloads/data/custom_coin");
+        let effects = publish_package(path, gas, proxy.clone(), sender, &keypair, gas_price).await;
+        let package_id = parse_package_ref(&effects.created())
+            .expect("Custom coin package should be published")
+            .0;
+        gas = effects.gas_object().0;
+        // Publishing creates the upgrade cap and the treasury cap for the sender
+        let mut treasury_cap = None;
+        for obj_ref in effects.created_owned_by(sender) {
+            let object = proxy
+                .get_object(obj_ref.0)
+                .await
+                .expect("Objects created by publishing should exist");
+            if object
+                .type_()
+                .map_or(false, |t| t.name() == COIN_TREASURE_CAP_NAME)
+            {
+                treasury_cap = Some(obj_ref);
+            }
+        }
+        let mut treasury_cap = treasury_cap.expect("Publishing should create a treasury cap");
+
+        let recipients: Vec<SuiAddress> = self
+            .payload_gas
+            .chunks(self.ring_size + 1)
+            .map(|accounts| accounts[0].1)
+            .collect();
+        info!("Minting custom coins for {} payloads", recipients.len());
+        for chunk in recipients.chunks(MINT_CHUNK_SIZE) {
+            let transaction = move_call_pt_impl(
+                sender,
+                &keypair,
+                package_id,
+                MODULE_NAME,
+                "mint",
+                vec![],
+                vec![
+                    treasury_cap.into(),
+                    self.mint_amount.into(),
+                    // unwrap safe because every vector of addresses is BCS-serializable
+                    BenchMoveCallArg::Pure(bcs::to_bytes(chunk).unwrap()),
+                ],
+                &gas,
+                gas_budget,
+                gas_price,
+            );
+            let effects = proxy
+                .execute_transaction_block(transaction.into())
+                .await
+                .expect("Minting custom coins should succeed");
+            if let Some(error) = effects.failure_status() {
+                panic!("Minting custom coins failed: {}", error);
+            }
+            gas = effects.gas_object().0;
+            treasury_cap = effects
+                .mutated()
+                .iter()
+                .find(|(obj_ref, _)| obj_ref.0 == treasury_cap.0)
+                .expect("Minting should mutate the treasury cap")
+                .0;
+            for recipient in chunk {
+                let coin = *effects
+                    .created_owned_by(*recipient)
+                    .first()
+                    .expect("Minting should create a coin for every recipient");
+                self.minted.insert(*recipient, coin);
+            }
+        }
+        self.package_id = Some(package_id);
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        self.payload_gas
+            .chunks(self.ring_size + 1)
+            .map(|coins| {
+                // Coins of a payload are its first account's gas and SUI coins, then the gas
+                // coin of each of its other accounts
+                let mut accounts = vec![coins[0].clone()];
+                accounts.extend(coins[2..].iter().cloned());
+                Box::new(CustomCoinTestPayload {
+                    holder: 0,
+                    custom_coin: self.minted[&coins[0].1],
+                    sui_coin: coins[1].0,
+                    next: TransferredCoin::Custom,
+                    accounts,
+                    stats: self.stats.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        self.stats.counters()
+    }
+}
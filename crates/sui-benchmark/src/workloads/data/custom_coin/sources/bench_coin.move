@@ -0,0 +1,43 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+/// Coin type other than SUI, whose treasury cap goes to the publisher, so that transfers of
+/// `Coin<BENCH_COIN>` go through the generic coin path rather than the one of SUI.
+module custom_coin::bench_coin {
+    use std::option;
+    use std::vector;
+    use sui::coin::{Self, TreasuryCap};
+    use sui::transfer;
+    use sui::tx_context::{Self, TxContext};
+
+    struct BENCH_COIN has drop {}
+
+    fun init(witness: BENCH_COIN, ctx: &mut TxContext) {
+        let (treasury_cap, metadata) = coin::create_currency(
+            witness,
+            0,
+            b"BENCH",
+            b"Bench coin",
+            b"Coin of the custom coin benchmark workload",
+            option::none(),
+            ctx,
+        );
+        transfer::public_freeze_object(metadata);
+        transfer::public_transfer(treasury_cap, tx_context::sender(ctx));
+    }
+
+    /// Mints a coin of `amount` for every one of `recipients`
+    public entry fun mint(
+        treasury_cap: &mut TreasuryCap<BENCH_COIN>,
+        amount: u64,
+        recipients: vector<address>,
+        ctx: &mut TxContext,
+    ) {
+        let i = 0;
+        let len = vector::length(&recipients);
+        while (i < len) {
+            coin::mint_and_transfer(treasury_cap, amount, *vector::borrow(&recipients, i), ctx);
+            i = i + 1;
+        }
+    }
+}
@@ -0,0 +1,33 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+/// Objects which the freeze benchmark workload creates, freezes and then reads.
+module freeze::freeze {
+    use sui::object::{Self, UID};
+    use sui::transfer;
+    use sui::tx_context::{Self, TxContext};
+
+    /// An object with `store`, so that it can be frozen with `public_freeze_object`.
+    struct Frozen has key, store {
+        id: UID,
+        value: u64,
+    }
+
+    /// Create an owned object holding `value` and send it to the sender.
+    public entry fun create(value: u64, ctx: &mut TxContext) {
+        transfer::public_transfer(
+            Frozen { id: object::new(ctx), value },
+            tx_context::sender(ctx),
+        )
+    }
+
+    /// Make `obj` immutable.
+    public entry fun freeze_object(obj: Frozen) {
+        transfer::public_freeze_object(obj)
+    }
+
+    /// Read the value of an object, frozen or not.
+    public entry fun read(obj: &Frozen) {
+        let _ = obj.value;
+    }
+}
@@ -0,0 +1,39 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+/// Minimal on-chain governance used by the governance benchmark workload.
+/// Rules:
+/// - anyone can create and share a proposal
+/// - anyone can vote for or against any proposal
+module governance::governance {
+    use sui::object::{Self, UID};
+    use sui::transfer;
+    use sui::tx_context::{Self, TxContext};
+
+    /// A shared proposal that accumulates votes.
+    struct Proposal has key {
+        id: UID,
+        proposer: address,
+        yes_votes: u64,
+        no_votes: u64,
+    }
+
+    /// Create and share a new proposal.
+    public entry fun propose(ctx: &mut TxContext) {
+        transfer::share_object(Proposal {
+            id: object::new(ctx),
+            proposer: tx_context::sender(ctx),
+            yes_votes: 0,
+            no_votes: 0,
+        })
+    }
+
+    /// Cast a vote on `proposal`.
+    public entry fun vote(proposal: &mut Proposal, approve: bool) {
+        if (approve) {
+            proposal.yes_votes = proposal.yes_votes + 1;
+        } else {
+            proposal.no_votes = proposal.no_votes + 1;
+        }
+    }
+}
@@ -0,0 +1,10 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+/// Entry function which always aborts, so that every call fails execution and goes through
+/// charging gas for and rolling back a failed transaction.
+module move_abort::move_abort {
+    public entry fun abort_with(code: u64) {
+        abort code
+    }
+}
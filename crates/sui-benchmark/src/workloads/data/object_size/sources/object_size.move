@@ -0,0 +1,30 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+/// Objects of a chosen size used by the object size benchmark workload.
+module object_size::object_size {
+    use std::vector;
+    use sui::object::{Self, UID};
+    use sui::transfer;
+    use sui::tx_context::{Self, TxContext};
+
+    /// An owned object whose size is dominated by `contents`.
+    struct Blob has key {
+        id: UID,
+        contents: vector<u8>,
+    }
+
+    /// Create a blob holding `size` bytes and send it to the sender.
+    public entry fun create(size: u64, ctx: &mut TxContext) {
+        let contents = vector::empty();
+        let i = 0;
+        while (i < size) {
+            vector::push_back(&mut contents, 0);
+            i = i + 1;
+        };
+        transfer::transfer(
+            Blob { id: object::new(ctx), contents },
+            tx_context::sender(ctx),
+        )
+    }
+}
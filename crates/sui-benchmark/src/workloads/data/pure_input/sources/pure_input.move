@@ -0,0 +1,12 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+/// Entry function which only reads the length of its pure input, so that the cost of a call is
+/// mostly that of deserializing and checking the input.
+module pure_input::pure_input {
+    use std::vector;
+
+    public entry fun read_length(bytes: vector<u8>) {
+        vector::length(&bytes);
+    }
+}
@@ -0,0 +1,33 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+/// Generic entry functions of every arity up to the limit of type arguments of a call. They do
+/// nothing, so that calling them costs little more than loading and checking the types they are
+/// instantiated with.
+module type_args::type_args {
+    struct Wrapper<T> {
+        value: T,
+    }
+
+    struct Pair<A, B> {
+        first: A,
+        second: B,
+    }
+
+    public entry fun call_1<T0>() {}
+    public entry fun call_2<T0, T1>() {}
+    public entry fun call_3<T0, T1, T2>() {}
+    public entry fun call_4<T0, T1, T2, T3>() {}
+    public entry fun call_5<T0, T1, T2, T3, T4>() {}
+    public entry fun call_6<T0, T1, T2, T3, T4, T5>() {}
+    public entry fun call_7<T0, T1, T2, T3, T4, T5, T6>() {}
+    public entry fun call_8<T0, T1, T2, T3, T4, T5, T6, T7>() {}
+    public entry fun call_9<T0, T1, T2, T3, T4, T5, T6, T7, T8>() {}
+    public entry fun call_10<T0, T1, T2, T3, T4, T5, T6, T7, T8, T9>() {}
+    public entry fun call_11<T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10>() {}
+    public entry fun call_12<T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11>() {}
+    public entry fun call_13<T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12>() {}
+    public entry fun call_14<T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13>() {}
+    public entry fun call_15<T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14>() {}
+    public entry fun call_16<T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15>() {}
+}
@@ -0,0 +1,18 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module upgrade::upgrade {
+    use sui::event;
+
+    struct Called has copy, drop {
+        version: u64,
+    }
+
+    public fun version(): u64 {
+        1
+    }
+
+    public entry fun call() {
+        event::emit(Called { version: version() })
+    }
+}
@@ -0,0 +1,24 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module upgrade::upgrade {
+    use sui::event;
+
+    struct Called has copy, drop {
+        version: u64,
+    }
+
+    /// Changed body, which a compatible upgrade allows
+    public fun version(): u64 {
+        2
+    }
+
+    public entry fun call() {
+        event::emit(Called { version: version() })
+    }
+
+    /// Added function, so that the upgraded package differs in its interface too
+    public fun previous_version(): u64 {
+        version() - 1
+    }
+}
@@ -0,0 +1,45 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+/// Objects holding a vector of numbers which the vector sum benchmark workload iterates over.
+module vector_sum::vector_sum {
+    use std::vector;
+    use sui::object::{Self, UID};
+    use sui::transfer;
+    use sui::tx_context::{Self, TxContext};
+
+    /// An owned object whose size is dominated by `values`.
+    struct Numbers has key {
+        id: UID,
+        values: vector<u64>,
+        /// Sum of `values` as of the last call to `sum`
+        total: u64,
+    }
+
+    /// Create an object holding the numbers from 0 to `length` excluded and send it to the
+    /// sender.
+    public entry fun create(length: u64, ctx: &mut TxContext) {
+        let values = vector::empty();
+        let i = 0;
+        while (i < length) {
+            vector::push_back(&mut values, i);
+            i = i + 1;
+        };
+        transfer::transfer(
+            Numbers { id: object::new(ctx), values, total: 0 },
+            tx_context::sender(ctx),
+        )
+    }
+
+    /// Iterate over the values of `numbers` and record their sum.
+    public entry fun sum(numbers: &mut Numbers) {
+        let total = 0;
+        let i = 0;
+        let length = vector::length(&numbers.values);
+        while (i < length) {
+            total = total + *vector::borrow(&numbers.values, i);
+            i = i + 1;
+        };
+        numbers.total = total;
+    }
+}
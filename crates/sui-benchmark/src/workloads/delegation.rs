@@ -2,29 +2,271 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::system_state_observer::SystemStateObserver;
+use crate::util::get_validators_with_retry;
 use crate::workloads::payload::Payload;
 use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
-use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::workloads::{
+    Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadConfigError, WorkloadParams,
+};
 use crate::{ExecutionEffects, ValidatorProxy};
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use rand::seq::IteratorRandom;
+use futures::stream::{self, BoxStream, StreamExt};
+use itertools::Itertools;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use sui_core::test_utils::make_transfer_sui_transaction;
-use sui_types::base_types::{ObjectRef, SuiAddress};
-use sui_types::crypto::{get_key_pair, AccountKeyPair};
-use sui_types::messages::VerifiedTransaction;
+use sui_types::base_types::{ObjectDigest, ObjectID, ObjectRef, SequenceNumber, SuiAddress};
+use sui_types::crypto::{get_key_pair_from_rng, AccountKeyPair};
+use sui_types::messages::{TransactionDataAPI, VerifiedTransaction};
 use test_utils::messages::make_staking_transaction;
+use thiserror::Error;
+use tracing::{info, warn};
+
+/// Smallest amount which can be staked, `MIN_STAKING_THRESHOLD` of `sui_system::validator_set`
+pub const MIN_STAKING_THRESHOLD: u64 = 1_000_000_000;
+
+/// Weighted list of amounts (in MIST) for delegation payloads to stake, parsed from a comma
+/// separated list of `{amount}:{weight}` pairs, e.g. "1000000000:70,10000000000:30"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StakeAmountDistribution {
+    amounts: Vec<(u64, u32)>,
+}
+
+impl StakeAmountDistribution {
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        // unwrap safe because weights are checked to be non-zero when parsing
+        let index = WeightedIndex::new(self.amounts.iter().map(|(_, weight)| weight)).unwrap();
+        self.amounts[index.sample(rng)].0
+    }
+
+    /// Checks that every amount can be staked out of the gas coin of a payload
+    pub fn check(&self) -> Result<(), WorkloadConfigError> {
+        for (amount, _) in &self.amounts {
+            if *amount < MIN_STAKING_THRESHOLD {
+                return Err(WorkloadConfigError::StakeBelowMinimum {
+                    amount: *amount,
+                    minimum: MIN_STAKING_THRESHOLD,
+                });
+            }
+            if *amount >= MAX_GAS_FOR_TESTING {
+                return Err(WorkloadConfigError::StakeAboveFunding {
+                    amount: *amount,
+                    funding: MAX_GAS_FOR_TESTING,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for StakeAmountDistribution {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let amounts = s
+            .split(',')
+            .map(|entry| {
+                let (amount, weight) = entry
+                    .split_once(':')
+                    .ok_or_else(|| anyhow!("invalid stake amount entry {:?}", entry))?;
+                Ok((amount.trim().parse::<u64>()?, weight.trim().parse::<u32>()?))
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+        if amounts
+            .iter()
+            .any(|(amount, weight)| *amount == 0 || *weight == 0)
+        {
+            return Err(anyhow!("stake amounts and weights must be positive"));
+        }
+        Ok(StakeAmountDistribution { amounts })
+    }
+}
+
+/// How delegation payloads choose the validators they stake with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidatorSelection {
+    /// The committee is read once when the workload is initialized and every payload stakes with
+    /// the same validator for the whole run, regardless of reconfiguration. Together with a seed
+    /// this makes the assignment of payloads to validators reproducible.
+    Pinned,
+    /// Every stake goes to a validator of the committee current at the time, so that stake
+    /// follows validators joining and leaving across reconfigurations.
+    Live,
+}
+
+/// Failure of a delegation transaction, by the phase of the payload which submitted it
+#[derive(Debug, Error)]
+pub enum DelegationError {
+    /// Splitting the coin to stake off the gas coin failed, usually for lack of gas
+    #[error("preparing the coin to stake failed: {0}")]
+    CoinPrep(String),
+    /// Staking the prepared coin failed, usually because of the validator
+    #[error("staking with validator {validator} failed: {error}")]
+    Staking {
+        validator: SuiAddress,
+        error: String,
+    },
+    /// Staking succeeded without emitting the event of the stake
+    #[error("staking {amount} with validator {validator} emitted no matching staking event")]
+    MissingStakingEvent { validator: SuiAddress, amount: u64 },
+    /// Staking succeeded without creating a `StakedSui` for the staker
+    #[error("staking with validator {validator} created no StakedSui")]
+    MissingStakedSui { validator: SuiAddress },
+}
+
+/// Contents of the `sui_system::validator::StakingRequestEvent` emitted by every stake
+#[derive(Debug, Deserialize)]
+struct StakingRequestEvent {
+    _pool_id: ObjectID,
+    validator_address: SuiAddress,
+    staker_address: SuiAddress,
+    _epoch: u64,
+    amount: u64,
+}
+
+/// Failures of the transactions of all payloads of the workload. Only the first failure of every
+/// kind is logged, the counts are in the report of the run.
+#[derive(Debug, Default)]
+struct DelegationStats {
+    coin_prep_failures: AtomicU64,
+    staking_failures: AtomicU64,
+    missing_staking_events: AtomicU64,
+    missing_staked_suis: AtomicU64,
+}
+
+impl DelegationStats {
+    fn record(&self, error: &DelegationError) {
+        let counter = match error {
+            DelegationError::CoinPrep(_) => &self.coin_prep_failures,
+            DelegationError::Staking { .. } => &self.staking_failures,
+            DelegationError::MissingStakingEvent { .. } => &self.missing_staking_events,
+            DelegationError::MissingStakedSui { .. } => &self.missing_staked_suis,
+        };
+        if counter.fetch_add(1, Ordering::Relaxed) == 0 {
+            warn!(
+                "Delegation transaction failed, further failures like it are only counted: {}",
+                error
+            );
+        }
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        vec![
+            (
+                "coin prep failures".into(),
+                self.coin_prep_failures.load(Ordering::Relaxed),
+            ),
+            (
+                "staking failures".into(),
+                self.staking_failures.load(Ordering::Relaxed),
+            ),
+            (
+                "missing staking events".into(),
+                self.missing_staking_events.load(Ordering::Relaxed),
+            ),
+            (
+                "missing staked suis".into(),
+                self.missing_staked_suis.load(Ordering::Relaxed),
+            ),
+        ]
+    }
+}
+
+/// Checks that `object_ref` refers to an object that can exist rather than a default value
+fn validate_object_ref(name: &str, object_ref: &ObjectRef) -> Result<(), String> {
+    let (id, version, digest) = object_ref;
+    if *id == ObjectID::ZERO || *version == SequenceNumber::MIN || *digest == ObjectDigest::MIN {
+        return Err(format!(
+            "{} has an invalid reference {:?}",
+            name, object_ref
+        ));
+    }
+    Ok(())
+}
+
+/// Has delegation payloads re-read their objects from the proxy instead of taking their new
+/// versions from effects, to model clients which do not track effects. The driver makes the
+/// reads, see `Payload::objects_to_read`.
+#[derive(Debug)]
+struct ObjectRefetcher {
+    /// Fraction (0.0 to 1.0) of effects which are ignored in favour of re-reading the objects
+    ratio: f32,
+    /// Number of reads issued to the proxy which trusting effects would have saved
+    rpc_calls: AtomicU64,
+}
+
+impl ObjectRefetcher {
+    fn should_refetch(&self) -> bool {
+        rand::thread_rng().gen::<f32>() < self.ratio
+    }
+}
 
 #[derive(Debug)]
 pub struct DelegationTestPayload {
     coin: Option<ObjectRef>,
+    stake_amount: u64,
     gas: ObjectRef,
     validator: SuiAddress,
+    validator_selection: ValidatorSelection,
     sender: SuiAddress,
     keypair: Arc<AccountKeyPair>,
+    /// Set if some effects are ignored in favour of reading the objects from the proxy
+    refetcher: Option<Arc<ObjectRefetcher>>,
+    /// Whether the objects of the payload are to be re-read before its next transaction
+    refetch_pending: bool,
+    /// Whether successful stakes are checked to emit a matching staking event
+    check_events: bool,
+    /// Transaction returned by the last `make_transaction` call, until its effects are seen.
+    /// Submitting it again rather than a new transaction keeps a payload whose transaction
+    /// failed from signing a second, conflicting one for the same objects.
+    last_tx: Option<VerifiedTransaction>,
+    stats: Arc<DelegationStats>,
     system_state_observer: Arc<SystemStateObserver>,
 }
 
+impl DelegationTestPayload {
+    /// Failure of the transaction of the current phase of the payload
+    fn failure(&self, error: String) -> DelegationError {
+        match self.coin {
+            None => DelegationError::CoinPrep(error),
+            Some(_) => DelegationError::Staking {
+                validator: self.validator,
+                error,
+            },
+        }
+    }
+
+    /// Checks that the successful stake of `effects` emitted a staking event for the validator
+    /// and amount of the payload. Effects read from a fullnode carry no events and are skipped.
+    fn check_staking_event(&self, effects: &ExecutionEffects) {
+        if effects.events().is_none() {
+            return;
+        }
+        let matches = effects
+            .find_event("validator", "StakingRequestEvent")
+            .and_then(|event| bcs::from_bytes::<StakingRequestEvent>(&event.contents).ok())
+            .map_or(false, |event| {
+                event.validator_address == self.validator
+                    && event.staker_address == self.sender
+                    && event.amount == self.stake_amount
+            });
+        if !matches {
+            self.stats.record(&DelegationError::MissingStakingEvent {
+                validator: self.validator,
+                amount: self.stake_amount,
+            });
+        }
+    }
+}
+
 impl std::fmt::Display for DelegationTestPayload {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "delegation")
@@ -33,36 +275,83 @@ impl std::fmt::Display for DelegationTestPayload {
 
 impl Payload for DelegationTestPayload {
     fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        self.last_tx = None;
+        let failure_status = effects.failure_status();
         let coin = match self.coin {
-            None => Some(effects.created().get(0).unwrap().0),
+            // Stays `None` if the coin could not be split off, so that the split is retried
+            None => effects.created_single(),
+            // A failed stake leaves the coin in place at a new version, stake it again
+            Some(coin) if failure_status.is_some() => effects
+                .mutated()
+                .into_iter()
+                .map(|(obj_ref, _)| obj_ref)
+                .find(|obj_ref| obj_ref.0 == coin.0),
             Some(_) => None,
         };
+        if let Some(error) = failure_status {
+            self.stats.record(&self.failure(error));
+        } else if self.coin.is_some() {
+            if effects.created_staked_sui(self.sender).is_none() {
+                self.stats.record(&DelegationError::MissingStakedSui {
+                    validator: self.validator,
+                });
+            }
+            if self.check_events {
+                self.check_staking_event(effects);
+            }
+        }
         self.coin = coin;
         self.gas = effects.gas_object().0;
+        // The reference gas price may change with the epoch, so have the observer catch up
+        // rather than wait for its next periodic read
+        if effects.executed_epoch() > self.system_state_observer.state.borrow().epoch {
+            self.system_state_observer.request_refresh();
+        }
+        self.refetch_pending = self
+            .refetcher
+            .as_ref()
+            .map_or(false, |refetcher| refetcher.should_refetch());
     }
 
     /// delegation flow is split into two phases
     /// first `make_transaction` call creates separate coin object for future delegation
     /// followup call creates delegation transaction itself
     fn make_transaction(&mut self) -> VerifiedTransaction {
-        match self.coin {
-            Some(coin) => make_staking_transaction(
-                self.gas,
-                coin,
-                self.validator,
-                self.sender,
-                &self.keypair,
-                Some(
-                    self.system_state_observer
+        if let Some(tx) = &self.last_tx {
+            return tx.clone();
+        }
+        let tx = match self.coin {
+            Some(coin) => {
+                if self.validator_selection == ValidatorSelection::Live {
+                    // Keep the previous validator until the observer has seen a committee
+                    if let Some(validator) = self
+                        .system_state_observer
                         .state
                         .borrow()
-                        .reference_gas_price,
-                ),
-            ),
+                        .active_validators
+                        .choose(&mut rand::thread_rng())
+                    {
+                        self.validator = *validator;
+                    }
+                }
+                make_staking_transaction(
+                    self.gas,
+                    coin,
+                    self.validator,
+                    self.sender,
+                    &self.keypair,
+                    Some(
+                        self.system_state_observer
+                            .state
+                            .borrow()
+                            .reference_gas_price,
+                    ),
+                )
+            }
             None => make_transfer_sui_transaction(
                 self.gas,
                 self.sender,
-                Some(1),
+                Some(self.stake_amount),
                 self.sender,
                 &self.keypair,
                 Some(
@@ -72,13 +361,104 @@ impl Payload for DelegationTestPayload {
                         .reference_gas_price,
                 ),
             ),
+        };
+        self.last_tx = Some(tx.clone());
+        tx
+    }
+
+    fn handle_error(&mut self, error: &anyhow::Error) -> bool {
+        self.stats.record(&self.failure(error.to_string()));
+        true
+    }
+
+    fn handle_invalid_transaction(&mut self, _error: &anyhow::Error) -> bool {
+        // The cached transaction would fail again, e.g. at the gas price of a past epoch. It was
+        // never submitted, so a new one cannot conflict with it.
+        self.last_tx = None;
+        self.system_state_observer.request_refresh();
+        true
+    }
+
+    fn retry_transaction(&mut self, _tx: &VerifiedTransaction) -> VerifiedTransaction {
+        // No effects were seen since, so this is the cached transaction
+        self.make_transaction()
+    }
+
+    fn stats_target(&self) -> Option<String> {
+        // Splitting off the coin to stake involves no validator
+        self.coin.map(|_| self.validator.to_string())
+    }
+
+    fn validate_state(&self) -> Result<(), String> {
+        if self.last_tx.is_some() {
+            return Err("transaction still pending after its effects".to_string());
+        }
+        if self.stake_amount == 0 {
+            return Err("nothing to stake".to_string());
+        }
+        validate_object_ref("gas coin", &self.gas)?;
+        // In the staking phase the coin to stake was split off the gas coin
+        if let Some(coin) = &self.coin {
+            validate_object_ref("coin to stake", coin)?;
+            if coin.0 == self.gas.0 {
+                return Err(format!("coin to stake {} is the gas coin", coin.0));
+            }
+        }
+        Ok(())
+    }
+
+    fn objects_to_read(&mut self) -> Vec<ObjectID> {
+        if !std::mem::take(&mut self.refetch_pending) {
+            return vec![];
+        }
+        // Object ids are still taken from effects, only their latest versions are read
+        std::iter::once(self.gas.0)
+            .chain(self.coin.map(|coin| coin.0))
+            .collect()
+    }
+
+    fn update_objects(&mut self, objects: Vec<Option<ObjectRef>>) {
+        if let Some(refetcher) = &self.refetcher {
+            refetcher
+                .rpc_calls
+                .fetch_add(objects.len() as u64, Ordering::Relaxed);
+        }
+        // Objects which could not be read keep the versions from effects
+        let mut objects = objects.into_iter();
+        if let Some(gas) = objects.next().flatten() {
+            self.gas = gas;
+        }
+        if let (Some(_), Some(coin)) = (self.coin, objects.next().flatten()) {
+            self.coin = Some(coin);
+        }
+    }
+
+    fn min_gas_required(&self) -> u64 {
+        let gas_budget = self
+            .last_tx
+            .as_ref()
+            .map_or(0, |tx| tx.data().transaction_data().gas_budget());
+        match self.coin {
+            // The coin to stake is split off the gas coin
+            None => gas_budget + self.stake_amount,
+            // The coin to stake is already split off, the gas coin only pays for gas
+            Some(_) => gas_budget,
         }
     }
 }
 
 #[derive(Debug)]
 pub struct DelegationWorkloadBuilder {
-    count: u64,
+    num_payloads: u64,
+    /// Seed the owners of the payload gas coins are derived from, so that their addresses are
+    /// known before the coins are created without holding a keypair for every payload
+    owner_seed: [u8; 32],
+    stake_amounts: StakeAmountDistribution,
+    seed: Option<u64>,
+    validator_selection: ValidatorSelection,
+    refetch_ratio: f32,
+    check_events: bool,
+    min_success_rate: Option<f32>,
 }
 
 impl DelegationWorkloadBuilder {
@@ -87,27 +467,52 @@ impl DelegationWorkloadBuilder {
         target_qps: u64,
         num_workers: u64,
         in_flight_ratio: u64,
-    ) -> Option<WorkloadBuilderInfo> {
+        stake_amounts: StakeAmountDistribution,
+        seed: Option<u64>,
+        validator_selection: ValidatorSelection,
+        refetch_ratio: f32,
+        check_events: bool,
+        min_success_rate: Option<f32>,
+    ) -> Result<WorkloadBuilderInfo, WorkloadConfigError> {
         let target_qps = (workload_weight * target_qps as f32) as u64;
         let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
         let max_ops = target_qps * in_flight_ratio;
-        if max_ops == 0 || num_workers == 0 {
-            None
-        } else {
-            let workload_params = WorkloadParams {
-                target_qps,
-                num_workers,
-                max_ops,
-            };
-            let workload_builder = Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(
-                DelegationWorkloadBuilder { count: max_ops },
-            ));
-            let builder_info = WorkloadBuilderInfo {
-                workload_params,
-                workload_builder,
-            };
-            Some(builder_info)
+        if target_qps == 0 {
+            return Err(WorkloadConfigError::ZeroTargetQps);
+        }
+        if num_workers == 0 {
+            return Err(WorkloadConfigError::ZeroWorkers);
+        }
+        if max_ops == 0 {
+            return Err(WorkloadConfigError::ZeroMaxOps);
         }
+        stake_amounts.check()?;
+        let workload_params = WorkloadParams {
+            target_qps,
+            num_workers,
+            max_ops,
+        };
+        let workload_builder =
+            Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(DelegationWorkloadBuilder {
+                num_payloads: max_ops,
+                owner_seed: rand::thread_rng().gen(),
+                stake_amounts,
+                seed,
+                validator_selection,
+                refetch_ratio: refetch_ratio.clamp(0.0, 1.0),
+                check_events,
+                min_success_rate: min_success_rate.map(|rate| rate.clamp(0.0, 1.0)),
+            }));
+        Ok(WorkloadBuilderInfo {
+            workload_params,
+            workload_builder,
+        })
+    }
+
+    /// Owner of the gas coin of every payload, the same ones in the same order on every call
+    fn payload_owners(&self) -> impl Iterator<Item = (SuiAddress, AccountKeyPair)> {
+        let mut rng = StdRng::from_seed(self.owner_seed);
+        (0..self.num_payloads).map(move |_| get_key_pair_from_rng(&mut rng))
     }
 }
 
@@ -117,64 +522,459 @@ impl WorkloadBuilder<dyn Payload> for DelegationWorkloadBuilder {
         vec![]
     }
     async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
-        (0..self.count)
-            .map(|_| {
-                let (address, keypair) = get_key_pair();
-                GasCoinConfig {
-                    amount: MAX_GAS_FOR_TESTING,
-                    address,
-                    keypair: Arc::new(keypair),
-                }
+        self.stream_coin_config_for_payloads().await.collect().await
+    }
+    // The payloads are as many as the operations in flight, so their owners are only derived
+    // as the bank polls the stream
+    async fn stream_coin_config_for_payloads(&self) -> BoxStream<'_, GasCoinConfig> {
+        stream::iter(self.payload_owners())
+            .map(|(address, keypair)| GasCoinConfig {
+                amount: MAX_GAS_FOR_TESTING,
+                address,
+                keypair: Arc::new(keypair),
             })
-            .collect()
+            .boxed()
+    }
+    fn required_addresses(&self) -> Vec<SuiAddress> {
+        self.payload_owners().map(|(address, _)| address).collect()
     }
     async fn build(
         &self,
         _init_gas: Vec<Gas>,
         payload_gas: Vec<Gas>,
     ) -> Box<dyn Workload<dyn Payload>> {
-        Box::<dyn Workload<dyn Payload>>::from(Box::new(DelegationWorkload { payload_gas }))
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(DelegationWorkload {
+            payload_gas,
+            stake_amounts: self.stake_amounts.clone(),
+            seed: self.seed,
+            validator_selection: self.validator_selection,
+            pinned_validators: vec![],
+            refetcher: (self.refetch_ratio > 0.0).then(|| {
+                Arc::new(ObjectRefetcher {
+                    ratio: self.refetch_ratio,
+                    rpc_calls: AtomicU64::new(0),
+                })
+            }),
+            check_events: self.check_events,
+            min_success_rate: self.min_success_rate,
+            stats: Arc::default(),
+        }))
     }
 }
 
 #[derive(Debug)]
 pub struct DelegationWorkload {
     payload_gas: Vec<Gas>,
+    stake_amounts: StakeAmountDistribution,
+    /// Seed for sampling stake amounts, so that runs with the same seed stake the same amounts
+    seed: Option<u64>,
+    validator_selection: ValidatorSelection,
+    /// Snapshot of the committee taken in `init` which payloads pick their validators from
+    pinned_validators: Vec<SuiAddress>,
+    /// Set if payloads re-read their objects after some effects instead of trusting them
+    refetcher: Option<Arc<ObjectRefetcher>>,
+    check_events: bool,
+    /// See `Workload::min_success_rate`
+    min_success_rate: Option<f32>,
+    stats: Arc<DelegationStats>,
 }
 
 #[async_trait]
 impl Workload<dyn Payload> for DelegationWorkload {
     async fn init(
         &mut self,
-        _: Arc<dyn ValidatorProxy + Sync + Send>,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
         _system_state_observer: Arc<SystemStateObserver>,
-    ) {
+    ) -> Result<()> {
+        if !self.pinned_validators.is_empty() {
+            return Ok(());
+        }
+        // Also the initial validators of payloads in live mode, before they see a committee
+        self.pinned_validators = get_validators_with_retry(&proxy).await?;
+        info!(
+            "Delegation workload ({:?} validators) starting with {} validators",
+            self.validator_selection,
+            self.pinned_validators.len()
+        );
+        Ok(())
     }
 
     async fn make_test_payloads(
         &self,
-        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
         system_state_observer: Arc<SystemStateObserver>,
     ) -> Vec<Box<dyn Payload>> {
-        let validators = proxy
-            .get_validators()
-            .await
-            .expect("failed to fetch validators");
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let stake_amounts: Vec<u64> = self
+            .payload_gas
+            .iter()
+            .map(|_| self.stake_amounts.sample(&mut rng))
+            .collect();
+        let distribution: BTreeMap<u64, usize> =
+            stake_amounts.iter().copied().counts().into_iter().collect();
+        info!(
+            "Delegation stake amounts (MIST: payloads): {}",
+            distribution
+                .iter()
+                .map(|(amount, count)| format!("{}: {}", amount, count))
+                .join(", ")
+        );
 
         self.payload_gas
             .iter()
-            .map(|(gas, owner, keypair)| {
-                let validator = *validators.iter().choose(&mut rand::thread_rng()).unwrap();
+            .zip(stake_amounts)
+            .map(|((gas, owner, keypair), stake_amount)| {
+                // Drawn from the seeded rng so that pinned assignments are reproducible
+                let validator = *self.pinned_validators.choose(&mut rng).unwrap();
                 Box::new(DelegationTestPayload {
                     coin: None,
+                    stake_amount,
                     gas: *gas,
                     validator,
+                    validator_selection: self.validator_selection,
                     sender: *owner,
                     keypair: keypair.clone(),
+                    refetcher: self.refetcher.clone(),
+                    refetch_pending: false,
+                    check_events: self.check_events,
+                    last_tx: None,
+                    stats: self.stats.clone(),
                     system_state_observer: system_state_observer.clone(),
                 })
             })
             .map(|b| Box::<dyn Payload>::from(b))
             .collect()
     }
+
+    fn min_success_rate(&self) -> Option<f64> {
+        self.min_success_rate.map(f64::from)
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        let mut counters = self.stats.counters();
+        if let Some(refetcher) = &self.refetcher {
+            counters.push((
+                "refetch rpc calls".into(),
+                refetcher.rpc_calls.load(Ordering::Relaxed),
+            ));
+        }
+        counters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::make_event_for_testing;
+    use crate::system_state_observer::SystemState;
+    use move_core_types::identifier::Identifier;
+    use sui_types::base_types::random_object_ref;
+    use sui_types::crypto::get_key_pair;
+    use sui_types::messages::{TransactionEffects, TransactionEffectsV1, TransactionEvents};
+    use sui_types::object::Owner;
+
+    /// Payload splitting off its coin to stake, with validators picked from a live committee
+    fn make_payload() -> DelegationTestPayload {
+        let (sender, keypair) = get_key_pair();
+        let state = SystemState {
+            epoch: 0,
+            reference_gas_price: 1000,
+            protocol_config: None,
+            active_validators: (0..10)
+                .map(|_| SuiAddress::random_for_testing_only())
+                .collect(),
+        };
+        DelegationTestPayload {
+            coin: None,
+            stake_amount: MIN_STAKING_THRESHOLD,
+            gas: random_object_ref(),
+            validator: SuiAddress::random_for_testing_only(),
+            validator_selection: ValidatorSelection::Live,
+            sender,
+            keypair: Arc::new(keypair),
+            refetcher: None,
+            refetch_pending: false,
+            check_events: false,
+            last_tx: None,
+            stats: Arc::new(DelegationStats::default()),
+            system_state_observer: Arc::new(SystemStateObserver::new_for_testing(state)),
+        }
+    }
+
+    /// Successful effects of a transaction of `payload` creating `created`
+    fn make_effects(
+        payload: &DelegationTestPayload,
+        created: Vec<ObjectRef>,
+        events: TransactionEvents,
+    ) -> ExecutionEffects {
+        let owner = Owner::AddressOwner(payload.sender);
+        let gas = random_object_ref();
+        ExecutionEffects::new_for_testing(
+            TransactionEffects::V1(TransactionEffectsV1 {
+                created: created
+                    .into_iter()
+                    .map(|obj_ref| (obj_ref, owner))
+                    .collect(),
+                mutated: vec![(gas, owner)],
+                gas_object: (gas, owner),
+                ..Default::default()
+            }),
+            events,
+        )
+    }
+
+    #[test]
+    fn test_retry_resubmits_cached_transaction() {
+        let mut payload = make_payload();
+        let split_tx = payload.make_transaction();
+        // Retried until effects are seen, however many times
+        for _ in 0..3 {
+            assert_eq!(
+                payload.retry_transaction(&split_tx).digest(),
+                split_tx.digest()
+            );
+        }
+        payload.make_new_payload(&make_effects(
+            &payload,
+            vec![random_object_ref()],
+            TransactionEvents::default(),
+        ));
+        // Every new staking transaction picks a validator at random, a retry must not
+        let stake_tx = payload.make_transaction();
+        assert_ne!(stake_tx.digest(), split_tx.digest());
+        for _ in 0..10 {
+            assert_eq!(
+                payload.retry_transaction(&stake_tx).digest(),
+                stake_tx.digest()
+            );
+        }
+        payload.make_new_payload(&make_effects(
+            &payload,
+            vec![],
+            TransactionEvents::default(),
+        ));
+        assert!(payload.last_tx.is_none());
+    }
+
+    #[test]
+    fn test_invalid_transaction_is_not_made_again() {
+        let mut payload = make_payload();
+        payload.make_transaction();
+        assert!(payload.handle_invalid_transaction(&anyhow!("gas price too low")));
+        assert!(payload.last_tx.is_none());
+    }
+
+    #[test]
+    fn test_refetch_reads_objects_once() {
+        let mut payload = make_payload();
+        payload.refetcher = Some(Arc::new(ObjectRefetcher {
+            ratio: 1.0,
+            rpc_calls: AtomicU64::new(0),
+        }));
+        payload.make_transaction();
+        let coin = random_object_ref();
+        payload.make_new_payload(&make_effects(
+            &payload,
+            vec![coin],
+            TransactionEvents::default(),
+        ));
+        assert_eq!(payload.objects_to_read(), vec![payload.gas.0, coin.0]);
+        // Asked for once per effects
+        assert!(payload.objects_to_read().is_empty());
+
+        // The coin could not be read and keeps the version from effects
+        let gas = (
+            payload.gas.0,
+            SequenceNumber::from_u64(payload.gas.1.value() + 1),
+            random_object_ref().2,
+        );
+        payload.update_objects(vec![Some(gas), None]);
+        assert_eq!(payload.gas, gas);
+        assert_eq!(payload.coin, Some(coin));
+        let rpc_calls = &payload.refetcher.as_ref().unwrap().rpc_calls;
+        assert_eq!(rpc_calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_stake_without_staked_sui_is_recorded() {
+        let mut payload = make_payload();
+        payload.coin = Some(random_object_ref());
+        payload.make_new_payload(&make_effects(
+            &payload,
+            vec![random_object_ref()],
+            TransactionEvents::default(),
+        ));
+        assert_eq!(payload.stats.missing_staked_suis.load(Ordering::Relaxed), 0);
+        payload.coin = Some(random_object_ref());
+        payload.make_new_payload(&make_effects(
+            &payload,
+            vec![],
+            TransactionEvents::default(),
+        ));
+        assert_eq!(payload.stats.missing_staked_suis.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_failures_are_counted_by_kind() {
+        let stats = DelegationStats::default();
+        let validator = SuiAddress::random_for_testing_only();
+        for _ in 0..3 {
+            stats.record(&DelegationError::CoinPrep("out of gas".to_owned()));
+        }
+        stats.record(&DelegationError::MissingStakedSui { validator });
+        let counters: BTreeMap<_, _> = stats.counters().into_iter().collect();
+        assert_eq!(counters["coin prep failures"], 3);
+        assert_eq!(counters["staking failures"], 0);
+        assert_eq!(counters["missing staked suis"], 1);
+    }
+
+    fn build(stake_amounts: &str) -> Result<WorkloadBuilderInfo, WorkloadConfigError> {
+        DelegationWorkloadBuilder::from(
+            1.0,
+            10,
+            2,
+            5,
+            StakeAmountDistribution::from_str(stake_amounts).unwrap(),
+            None,
+            ValidatorSelection::Pinned,
+            0.0,
+            false,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_builder_rejects_stake_below_minimum() {
+        assert_eq!(
+            build("1:1").unwrap_err(),
+            WorkloadConfigError::StakeBelowMinimum {
+                amount: 1,
+                minimum: MIN_STAKING_THRESHOLD
+            }
+        );
+        // Every amount of the distribution is checked, not only the sampled ones
+        assert!(matches!(
+            build(&format!("{}:99,999:1", MIN_STAKING_THRESHOLD)),
+            Err(WorkloadConfigError::StakeBelowMinimum { amount: 999, .. })
+        ));
+        assert!(build(&format!("{}:1", MIN_STAKING_THRESHOLD)).is_ok());
+    }
+
+    #[test]
+    fn test_builder_rejects_stake_above_funding() {
+        assert_eq!(
+            build(&format!("{}:1", MAX_GAS_FOR_TESTING)).unwrap_err(),
+            WorkloadConfigError::StakeAboveFunding {
+                amount: MAX_GAS_FOR_TESTING,
+                funding: MAX_GAS_FOR_TESTING
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_required_addresses_are_funded_owners() {
+        let builder = build(&format!("{}:1", MIN_STAKING_THRESHOLD))
+            .unwrap()
+            .workload_builder;
+        let required = builder.required_addresses();
+        assert_eq!(required.len(), 50);
+        // Every call derives the same owners, which are those the coin configs are sent to
+        assert_eq!(builder.required_addresses(), required);
+        let funded: Vec<_> = builder
+            .stream_coin_config_for_payloads()
+            .await
+            .map(|config| config.address)
+            .collect()
+            .await;
+        assert_eq!(funded, required);
+    }
+
+    #[test]
+    fn test_builder_rejects_zero_params() {
+        let stake_amounts = StakeAmountDistribution::from_str("1000000000:1").unwrap();
+        let from = |weight, target_qps, num_workers, in_flight_ratio| {
+            DelegationWorkloadBuilder::from(
+                weight,
+                target_qps,
+                num_workers,
+                in_flight_ratio,
+                stake_amounts.clone(),
+                None,
+                ValidatorSelection::Pinned,
+                0.0,
+                false,
+                None,
+            )
+            .map(|_| ())
+        };
+        assert_eq!(from(1.0, 0, 2, 5), Err(WorkloadConfigError::ZeroTargetQps));
+        assert_eq!(from(1.0, 10, 0, 5), Err(WorkloadConfigError::ZeroWorkers));
+        assert_eq!(from(1.0, 10, 2, 0), Err(WorkloadConfigError::ZeroMaxOps));
+    }
+
+    /// Events of a stake by `payload`, with the staking event of `amount` staked with
+    /// `validator` by `staker`
+    fn staking_events(
+        payload: &DelegationTestPayload,
+        validator: SuiAddress,
+        staker: SuiAddress,
+        amount: u64,
+    ) -> TransactionEvents {
+        let contents =
+            bcs::to_bytes(&(ObjectID::random(), validator, staker, 0u64, amount)).unwrap();
+        TransactionEvents {
+            data: vec![make_event_for_testing(
+                payload.sender,
+                "validator",
+                "StakingRequestEvent",
+                contents,
+            )],
+        }
+    }
+
+    fn missing_staking_events(payload: &DelegationTestPayload) -> u64 {
+        payload.stats.missing_staking_events.load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn test_check_staking_event() {
+        let payload = make_payload();
+        let (validator, sender, amount) = (payload.validator, payload.sender, payload.stake_amount);
+        let effects = make_effects(
+            &payload,
+            vec![],
+            staking_events(&payload, validator, sender, amount),
+        );
+        payload.check_staking_event(&effects);
+        assert_eq!(missing_staking_events(&payload), 0);
+
+        // No event at all, or only events of other types
+        let effects = make_effects(&payload, vec![], TransactionEvents::default());
+        payload.check_staking_event(&effects);
+        assert_eq!(missing_staking_events(&payload), 1);
+        let mut events = staking_events(&payload, validator, sender, amount);
+        events.data[0].type_.name = Identifier::new("UnstakingRequestEvent").unwrap();
+        payload.check_staking_event(&make_effects(&payload, vec![], events));
+        assert_eq!(missing_staking_events(&payload), 2);
+
+        // A staking event, but not of this stake
+        let other = SuiAddress::random_for_testing_only();
+        for events in [
+            staking_events(&payload, other, sender, amount),
+            staking_events(&payload, validator, other, amount),
+            staking_events(&payload, validator, sender, amount + 1),
+        ] {
+            payload.check_staking_event(&make_effects(&payload, vec![], events));
+        }
+        assert_eq!(missing_staking_events(&payload), 5);
+
+        // Contents which are not a staking event
+        let mut events = staking_events(&payload, validator, sender, amount);
+        events.data[0].contents.truncate(8);
+        payload.check_staking_event(&make_effects(&payload, vec![], events));
+        assert_eq!(missing_staking_events(&payload), 6);
+    }
 }
@@ -0,0 +1,356 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::system_state_observer::SystemStateObserver;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use rand::Rng;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use sui_core::test_utils::{make_transfer_sui_transaction, MAX_GAS};
+use sui_types::base_types::{ObjectRef, SuiAddress};
+use sui_types::crypto::{get_key_pair, AccountKeyPair};
+use sui_types::messages::VerifiedTransaction;
+use tracing::error;
+
+/// Result of one of the two conflicting transactions, with the error if it was rejected
+type Outcome = Result<(), String>;
+
+/// Outcomes of the equivocation attempts of all payloads of the workload
+#[derive(Debug, Default)]
+struct EquivocationStats {
+    attempts: AtomicU64,
+    /// Attempts in which exactly one transaction committed, as expected
+    one_committed: AtomicU64,
+    /// Attempts in which both transactions committed, i.e. the coin was spent twice
+    both_committed: AtomicU64,
+    /// Attempts in which neither transaction got a quorum, which locks the coin for the epoch
+    none_committed: AtomicU64,
+    /// Rejections because validators saw the coin used by the conflicting transaction
+    rejected_double_use: AtomicU64,
+    rejected_lock_conflict: AtomicU64,
+    rejected_other: AtomicU64,
+}
+
+impl EquivocationStats {
+    fn record(&self, first: Outcome, second: Outcome) {
+        for rejection in [&first, &second]
+            .into_iter()
+            .filter_map(|o| o.as_ref().err())
+        {
+            let counter = if rejection.contains("ObjectsDoubleUsed") {
+                &self.rejected_double_use
+            } else if rejection.contains("ObjectLockConflict") {
+                &self.rejected_lock_conflict
+            } else {
+                &self.rejected_other
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+        match (first.is_ok(), second.is_ok()) {
+            (true, true) => {
+                self.both_committed.fetch_add(1, Ordering::Relaxed);
+                error!("Both transactions spending the same coin were committed");
+            }
+            (false, false) => {
+                self.none_committed.fetch_add(1, Ordering::Relaxed);
+            }
+            _ => {
+                self.one_committed.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.attempts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        vec![
+            ("attempts".into(), self.attempts.load(Ordering::Relaxed)),
+            (
+                "one committed".into(),
+                self.one_committed.load(Ordering::Relaxed),
+            ),
+            (
+                "both committed".into(),
+                self.both_committed.load(Ordering::Relaxed),
+            ),
+            (
+                "none committed".into(),
+                self.none_committed.load(Ordering::Relaxed),
+            ),
+            (
+                "rejected: double use".into(),
+                self.rejected_double_use.load(Ordering::Relaxed),
+            ),
+            (
+                "rejected: lock conflict".into(),
+                self.rejected_lock_conflict.load(Ordering::Relaxed),
+            ),
+            (
+                "rejected: other".into(),
+                self.rejected_other.load(Ordering::Relaxed),
+            ),
+        ]
+    }
+}
+
+/// The two conflicting transactions of one attempt, recorded by whichever finishes last
+#[derive(Debug)]
+struct Attempt {
+    stats: Arc<EquivocationStats>,
+    first_finished: Mutex<Option<Outcome>>,
+}
+
+impl Attempt {
+    fn finish(&self, outcome: Outcome) {
+        let mut first_finished = self.first_finished.lock().unwrap();
+        match first_finished.take() {
+            Some(first) => self.stats.record(first, outcome),
+            None => *first_finished = Some(outcome),
+        }
+    }
+}
+
+pub struct EquivocationTestPayload {
+    gas: Gas,
+    /// Coin spent by the next transaction, split off the gas coin so that only this coin is at
+    /// stake when the transactions conflict
+    target: Option<ObjectRef>,
+    /// Fraction (0.0 to 1.0) of spends which are doubled by a conflicting transaction
+    equivocation_ratio: f32,
+    /// Attempt of the last transaction, if it had a conflicting twin
+    attempt: Option<Arc<Attempt>>,
+    stats: Arc<EquivocationStats>,
+    /// Submits the conflicting transactions, the benchmark driver submits the other one
+    proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Debug for EquivocationTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("EquivocationTestPayload")
+            .field("gas", &self.gas)
+            .field("target", &self.target)
+            .field("equivocation_ratio", &self.equivocation_ratio)
+            .finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Display for EquivocationTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "equivocation")
+    }
+}
+
+impl EquivocationTestPayload {
+    /// Transaction spending all of `target` as gas and transferring what is left to `recipient`
+    fn spend_target(&self, target: ObjectRef, recipient: SuiAddress) -> VerifiedTransaction {
+        make_transfer_sui_transaction(
+            target,
+            recipient,
+            None,
+            self.gas.1,
+            &self.gas.2,
+            Some(
+                self.system_state_observer
+                    .state
+                    .borrow()
+                    .reference_gas_price,
+            ),
+        )
+    }
+}
+
+impl Payload for EquivocationTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        match self.target.take() {
+            // A new target was split off the gas coin
+            None => {
+                self.target = effects.created_single();
+                self.gas.0 = effects.gas_object().0;
+            }
+            // The target was spent, split a new one next time
+            Some(_) => {
+                if let Some(attempt) = self.attempt.take() {
+                    attempt.finish(Ok(()));
+                }
+            }
+        }
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let target = match self.target {
+            Some(target) => target,
+            None => {
+                return make_transfer_sui_transaction(
+                    self.gas.0,
+                    self.gas.1,
+                    Some(MAX_GAS),
+                    self.gas.1,
+                    &self.gas.2,
+                    Some(
+                        self.system_state_observer
+                            .state
+                            .borrow()
+                            .reference_gas_price,
+                    ),
+                )
+            }
+        };
+        if rand::thread_rng().gen::<f32>() < self.equivocation_ratio {
+            // Same coin, different recipient: the transactions conflict on the target only
+            let attempt = Arc::new(Attempt {
+                stats: self.stats.clone(),
+                first_finished: Mutex::new(None),
+            });
+            let (other_recipient, _) = get_key_pair::<AccountKeyPair>();
+            let conflicting_tx = self.spend_target(target, other_recipient);
+            let proxy = self.proxy.clone();
+            let conflicting_attempt = attempt.clone();
+            tokio::spawn(async move {
+                let outcome = proxy
+                    .execute_transaction_block(conflicting_tx.into())
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| format!("{:?}", e));
+                conflicting_attempt.finish(outcome);
+            });
+            self.attempt = Some(attempt);
+        }
+        self.spend_target(target, self.gas.1)
+    }
+
+    fn handle_error(&mut self, error: &anyhow::Error) -> bool {
+        match self.attempt.take() {
+            Some(attempt) => {
+                attempt.finish(Err(format!("{:?}", error)));
+                // The target is spent by the other transaction or locked, split a new one
+                self.target = None;
+                false
+            }
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EquivocationWorkloadBuilder {
+    num_payloads: u64,
+    equivocation_ratio: f32,
+}
+
+impl EquivocationWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        equivocation_ratio: f32,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder = Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(
+                EquivocationWorkloadBuilder {
+                    num_payloads: max_ops,
+                    equivocation_ratio: equivocation_ratio.clamp(0.0, 1.0),
+                },
+            ));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for EquivocationWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        vec![]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        (0..self.num_payloads)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        _init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(EquivocationWorkload {
+            equivocation_ratio: self.equivocation_ratio,
+            payload_gas,
+            stats: Arc::default(),
+        }))
+    }
+}
+
+/// Spends coins with two conflicting transactions at the same time to exercise the locking of
+/// owned objects by validators. At most one of them may commit; if the validators' votes are
+/// split, neither does and the coin stays locked until the end of the epoch. Every attempt
+/// spends a coin split off the payload's gas coin beforehand, so the gas coin itself is never
+/// at stake.
+#[derive(Debug)]
+pub struct EquivocationWorkload {
+    equivocation_ratio: f32,
+    payload_gas: Vec<Gas>,
+    stats: Arc<EquivocationStats>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for EquivocationWorkload {
+    async fn init(
+        &mut self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        _system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        self.payload_gas
+            .iter()
+            .map(|gas| {
+                Box::new(EquivocationTestPayload {
+                    gas: gas.clone(),
+                    target: None,
+                    equivocation_ratio: self.equivocation_ratio,
+                    attempt: None,
+                    stats: self.stats.clone(),
+                    proxy: proxy.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        self.stats.counters()
+    }
+}
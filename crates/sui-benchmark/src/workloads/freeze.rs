@@ -0,0 +1,300 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::in_memory_wallet::move_call_pt_impl;
+use crate::system_state_observer::SystemStateObserver;
+use crate::util::publish_package;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{BenchMoveCallArg, ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::sync::Arc;
+use sui_types::base_types::{ObjectID, ObjectRef};
+use sui_types::crypto::get_key_pair;
+use sui_types::messages::VerifiedTransaction;
+use sui_types::object::Owner;
+use test_utils::transaction::parse_package_ref;
+use tracing::{info, warn};
+
+const MODULE_NAME: &str = "freeze";
+
+/// Step of the payload which the last transaction was for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FreezeStep {
+    Create,
+    Freeze,
+    Read,
+}
+
+impl std::fmt::Display for FreezeStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FreezeStep::Create => write!(f, "create"),
+            FreezeStep::Freeze => write!(f, "freeze"),
+            FreezeStep::Read => write!(f, "read"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FreezeTestPayload {
+    package_id: ObjectID,
+    gas: Gas,
+    /// Objects this payload still has to create and freeze
+    remaining: u64,
+    /// Object created by the last transaction, frozen by the next one
+    to_freeze: Option<ObjectRef>,
+    /// Objects frozen by this payload, read in turn once all of them are
+    frozen: Vec<ObjectRef>,
+    /// Frozen object read by the next read
+    next_read: usize,
+    step: FreezeStep,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for FreezeTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "freeze")
+    }
+}
+
+impl FreezeTestPayload {
+    fn next_step(&self) -> FreezeStep {
+        if self.to_freeze.is_some() {
+            FreezeStep::Freeze
+        } else if self.remaining > 0 || self.frozen.is_empty() {
+            FreezeStep::Create
+        } else {
+            FreezeStep::Read
+        }
+    }
+}
+
+impl Payload for FreezeTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        if let Some(error) = effects.failure_status() {
+            // A failed freeze still changes the version of the object, which is then created
+            // again rather than tracked
+            warn!("Transaction to {} an object failed: {}", self.step, error);
+            self.to_freeze = None;
+        } else {
+            match self.step {
+                FreezeStep::Create => {
+                    self.to_freeze = effects.created_single();
+                }
+                FreezeStep::Freeze => {
+                    let id = self.to_freeze.take().map(|obj_ref| obj_ref.0);
+                    match effects
+                        .mutated()
+                        .into_iter()
+                        .find(|(obj_ref, _)| Some(obj_ref.0) == id)
+                    {
+                        Some((obj_ref, Owner::Immutable)) => {
+                            self.frozen.push(obj_ref);
+                            self.remaining = self.remaining.saturating_sub(1);
+                        }
+                        other => warn!("Frozen object is not immutable: {:?}", other),
+                    }
+                }
+                FreezeStep::Read => {
+                    self.next_read = (self.next_read + 1) % self.frozen.len();
+                }
+            }
+        }
+        self.gas.0 = effects.gas_object().0;
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let (gas_budget, gas_price) = {
+            let state = self.system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        self.step = self.next_step();
+        let (function, args) = match self.step {
+            FreezeStep::Create => ("create", vec![self.remaining.into()]),
+            FreezeStep::Freeze => (
+                "freeze_object",
+                vec![BenchMoveCallArg::ImmOrOwnedObject(self.to_freeze.unwrap())],
+            ),
+            FreezeStep::Read => (
+                "read",
+                vec![BenchMoveCallArg::ImmOrOwnedObject(
+                    self.frozen[self.next_read],
+                )],
+            ),
+        };
+        move_call_pt_impl(
+            self.gas.1,
+            &self.gas.2,
+            self.package_id,
+            MODULE_NAME,
+            function,
+            vec![],
+            args,
+            &self.gas.0,
+            gas_budget,
+            gas_price,
+        )
+    }
+
+    fn stats_target(&self) -> Option<String> {
+        // Breaks down the latency of the workload by step
+        Some(self.next_step().to_string())
+    }
+}
+
+#[derive(Debug)]
+pub struct FreezeWorkloadBuilder {
+    num_payloads: u64,
+    num_objects: u64,
+}
+
+impl FreezeWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        num_objects: u64,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder =
+                Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(FreezeWorkloadBuilder {
+                    num_payloads: max_ops,
+                    num_objects,
+                }));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for FreezeWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        // Gas coin for publishing package
+        let (address, keypair) = get_key_pair();
+        vec![GasCoinConfig {
+            amount: MAX_GAS_FOR_TESTING,
+            address,
+            keypair: Arc::new(keypair),
+        }]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        (0..self.num_payloads)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(FreezeWorkload {
+            package_id: None,
+            num_objects: self.num_objects,
+            init_gas,
+            payload_gas,
+        }))
+    }
+}
+
+/// Creates owned objects and freezes each of them with the next transaction of the same payload,
+/// to measure the transition of objects from owned to immutable. Once the configured number of
+/// objects of the run is frozen, which is split between payloads with at least one each,
+/// payloads read their frozen objects in turn as immutable inputs. The latency is broken down
+/// by step: create, freeze and read.
+#[derive(Debug)]
+pub struct FreezeWorkload {
+    package_id: Option<ObjectID>,
+    num_objects: u64,
+    init_gas: Vec<Gas>,
+    payload_gas: Vec<Gas>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for FreezeWorkload {
+    async fn init(
+        &mut self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        if self.package_id.is_some() {
+            return Ok(());
+        }
+        let gas_price = system_state_observer.state.borrow().reference_gas_price;
+        let gas = self
+            .init_gas
+            .first()
+            .expect("Not enough gas to initialize freeze workload");
+        info!("Publishing freeze package");
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/workloads/data/freeze");
+        let effects = publish_package(path, gas.0, proxy, gas.1, &gas.2, gas_price).await;
+        self.package_id = Some(
+            parse_package_ref(&effects.created())
+                .expect("Freeze package should be published")
+                .0,
+        );
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        let num_payloads = self.payload_gas.len() as u64;
+        self.payload_gas
+            .iter()
+            .enumerate()
+            .map(|(i, gas)| {
+                // The first payloads freeze one more object each when they can't all freeze
+                // the same number
+                let remaining = self.num_objects / num_payloads
+                    + u64::from((i as u64) < self.num_objects % num_payloads);
+                Box::new(FreezeTestPayload {
+                    package_id: self.package_id.unwrap(),
+                    gas: gas.clone(),
+                    remaining: remaining.max(1),
+                    to_freeze: None,
+                    frozen: vec![],
+                    next_read: 0,
+                    step: FreezeStep::Create,
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::system_state_observer::SystemStateObserver;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+use sui_types::base_types::{ObjectRef, SuiAddress};
+use sui_types::crypto::{get_key_pair, AccountKeyPair};
+use sui_types::messages::{TransactionData, VerifiedTransaction};
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::utils::to_sender_signed_transaction;
+
+/// Amount of the coins split back out of the smashed gas coin after every transaction
+const SMASHED_COIN_AMOUNT: u64 = 1_000_000;
+
+#[derive(Debug)]
+pub struct GasSmashTestPayload {
+    /// Coins used together as gas payment, the first one is the coin the others are smashed into
+    gas_coins: Vec<ObjectRef>,
+    coins_per_tx: u64,
+    sender: SuiAddress,
+    keypair: Arc<AccountKeyPair>,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for GasSmashTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "gas_smash")
+    }
+}
+
+impl Payload for GasSmashTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        // All gas coins were smashed into the first one, which also paid for the coins split
+        // out of it for the next transaction
+        let smashed_coin = effects.gas_object().0;
+        self.gas_coins = std::iter::once(smashed_coin)
+            .chain(effects.created_owned_by(self.sender))
+            .collect();
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let (gas_budget, gas_price) = {
+            let state = self.system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        // The transfer itself is trivial, it only splits off the coins to smash next time
+        let num_split_coins = (self.coins_per_tx - 1) as usize;
+        let mut builder = ProgrammableTransactionBuilder::new();
+        builder
+            .pay_sui(
+                vec![self.sender; num_split_coins],
+                vec![SMASHED_COIN_AMOUNT; num_split_coins],
+            )
+            .unwrap();
+        let data = TransactionData::new_programmable(
+            self.sender,
+            self.gas_coins.clone(),
+            builder.finish(),
+            gas_budget,
+            gas_price,
+        );
+        to_sender_signed_transaction(data, &self.keypair)
+    }
+}
+
+#[derive(Debug)]
+pub struct GasSmashWorkloadBuilder {
+    num_payloads: u64,
+    coins_per_tx: u64,
+}
+
+impl GasSmashWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        coins_per_tx: u32,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder =
+                Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(GasSmashWorkloadBuilder {
+                    num_payloads: max_ops,
+                    // at least two coins are needed for anything to be smashed
+                    coins_per_tx: std::cmp::max(coins_per_tx, 2) as u64,
+                }));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for GasSmashWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        vec![]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        // `coins_per_tx` consecutive coins for every payload owner
+        let mut configs = vec![];
+        for _ in 0..self.num_payloads {
+            let (address, keypair) = get_key_pair();
+            let keypair = Arc::new(keypair);
+            for _ in 0..self.coins_per_tx {
+                configs.push(GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING / self.coins_per_tx,
+                    address,
+                    keypair: keypair.clone(),
+                });
+            }
+        }
+        configs
+    }
+    async fn build(
+        &self,
+        _init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(GasSmashWorkload {
+            coins_per_tx: self.coins_per_tx,
+            payload_gas,
+        }))
+    }
+}
+
+/// Pays for every transaction with `coins_per_tx` small gas coins, which validators have to
+/// smash into one before charging gas.
+#[derive(Debug)]
+pub struct GasSmashWorkload {
+    coins_per_tx: u64,
+    payload_gas: Vec<Gas>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for GasSmashWorkload {
+    async fn init(
+        &mut self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        let max_gas_payment_objects = system_state_observer
+            .state
+            .borrow()
+            .protocol_config
+            .as_ref()
+            .expect("Protocol config not in system state")
+            .max_gas_payment_objects();
+        assert!(
+            self.coins_per_tx <= max_gas_payment_objects as u64,
+            "Gas smash workload uses {} coins per transaction, at most {} are allowed",
+            self.coins_per_tx,
+            max_gas_payment_objects
+        );
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        self.payload_gas
+            .chunks(self.coins_per_tx as usize)
+            .map(|coins| {
+                let (_, sender, keypair) = &coins[0];
+                Box::new(GasSmashTestPayload {
+                    gas_coins: coins.iter().map(|(obj_ref, _, _)| *obj_ref).collect(),
+                    coins_per_tx: self.coins_per_tx,
+                    sender: *sender,
+                    keypair: keypair.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+}
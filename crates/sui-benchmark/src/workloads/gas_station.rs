@@ -0,0 +1,541 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::system_state_observer::SystemStateObserver;
+use crate::util::publish_basics_package;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use move_core_types::identifier::Identifier;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use sui_types::base_types::{ObjectID, ObjectRef, SuiAddress};
+use sui_types::crypto::{get_key_pair, AccountKeyPair};
+use sui_types::messages::{
+    Argument, CallArg, Command, GasData, ObjectArg, TransactionData, TransactionKind,
+    VerifiedTransaction,
+};
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::utils::{
+    to_sender_signed_transaction, to_sender_signed_transaction_with_multi_signers,
+};
+use tracing::{info, warn};
+
+/// Gas budget of every sponsored transaction, paid out of the sponsor coin
+const GAS_BUDGET: u64 = 50_000_000;
+/// Amount put into a sponsor coin at the start and by every top up from the sponsor's reserve
+const SPONSOR_COIN_AMOUNT: u64 = 100 * GAS_BUDGET;
+/// A sponsor coin is topped up once its balance falls below this
+const TOP_UP_THRESHOLD: u64 = 10 * GAS_BUDGET;
+
+/// Whether `error` is the validators rejecting a transaction whose gas coins cannot cover its
+/// budget
+fn is_gas_balance_error(error: &anyhow::Error) -> bool {
+    format!("{:?}", error).contains("GasBalanceTooLow")
+}
+
+/// Events of the sponsor coins of all payloads of the workload
+#[derive(Debug, Default)]
+struct GasStationStats {
+    sponsored_txs: AtomicU64,
+    top_ups: AtomicU64,
+    /// Top ups put off because the reserve was in use by the top up of another sponsor coin
+    reserve_busy: AtomicU64,
+    /// Sponsored transactions rejected because their sponsor coin ran dry
+    exhausted: AtomicU64,
+    /// Top ups which failed or were rejected for the gas of the reserve, after which the reserve
+    /// tops up no more sponsor coins
+    failed_top_ups: AtomicU64,
+}
+
+impl GasStationStats {
+    fn counters(&self) -> Vec<(String, u64)> {
+        vec![
+            (
+                "sponsored txs".into(),
+                self.sponsored_txs.load(Ordering::Relaxed),
+            ),
+            ("top ups".into(), self.top_ups.load(Ordering::Relaxed)),
+            (
+                "top ups put off: reserve busy".into(),
+                self.reserve_busy.load(Ordering::Relaxed),
+            ),
+            (
+                "exhausted sponsor coins".into(),
+                self.exhausted.load(Ordering::Relaxed),
+            ),
+            (
+                "failed top ups".into(),
+                self.failed_top_ups.load(Ordering::Relaxed),
+            ),
+        ]
+    }
+}
+
+/// An account paying for the transactions of many senders out of a few coins
+#[derive(Debug)]
+struct Sponsor {
+    address: SuiAddress,
+    keypair: Arc<AccountKeyPair>,
+    /// Coin topping up the sponsor coins, taken by the payload whose top up is in flight
+    reserve: Mutex<Option<ObjectRef>>,
+    /// Set once a top up failed, after which the reserve is not given back and sponsor coins
+    /// running low are left to run dry
+    reserve_exhausted: AtomicBool,
+    /// Senders without any coins, served in turn by all sponsor coins
+    senders: Vec<(SuiAddress, Arc<AccountKeyPair>)>,
+    next_sender: AtomicUsize,
+}
+
+#[derive(Debug)]
+pub struct GasStationTestPayload {
+    package_id: ObjectID,
+    sponsor: Arc<Sponsor>,
+    /// Sponsor coin paying for the transactions of this payload
+    coin: ObjectRef,
+    /// Balance of `coin`, tracked from the gas used by its transactions
+    balance: u64,
+    /// Reserve used by the last transaction, if it topped up `coin`
+    top_up_reserve: Option<ObjectRef>,
+    stats: Arc<GasStationStats>,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for GasStationTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "gas_station")
+    }
+}
+
+impl GasStationTestPayload {
+    /// Merges `SPONSOR_COIN_AMOUNT` from the reserve into the sponsor coin, with the reserve
+    /// paying for gas
+    fn make_top_up_transaction(&self, reserve: ObjectRef, gas_price: u64) -> VerifiedTransaction {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let amount = builder.pure(SPONSOR_COIN_AMOUNT).unwrap();
+        let top_up = builder.command(Command::SplitCoins(Argument::GasCoin, vec![amount]));
+        let coin = builder.obj(ObjectArg::ImmOrOwnedObject(self.coin)).unwrap();
+        builder.command(Command::MergeCoins(coin, vec![top_up]));
+        let data = TransactionData::new_programmable(
+            self.sponsor.address,
+            vec![reserve],
+            builder.finish(),
+            GAS_BUDGET,
+            gas_price,
+        );
+        to_sender_signed_transaction(data, self.sponsor.keypair.as_ref())
+    }
+
+    /// Creates an object for the next sender of the sponsor, with the sponsor coin paying for
+    /// gas
+    fn make_sponsored_transaction(&self, gas_price: u64) -> VerifiedTransaction {
+        let senders = &self.sponsor.senders;
+        let (sender, sender_keypair) =
+            &senders[self.sponsor.next_sender.fetch_add(1, Ordering::Relaxed) % senders.len()];
+        let mut builder = ProgrammableTransactionBuilder::new();
+        builder
+            .move_call(
+                self.package_id,
+                Identifier::new("object_basics").unwrap(),
+                Identifier::new("create").unwrap(),
+                vec![],
+                vec![
+                    CallArg::Pure(bcs::to_bytes(&0u64).unwrap()),
+                    CallArg::Pure(bcs::to_bytes(sender).unwrap()),
+                ],
+            )
+            .unwrap();
+        let data = TransactionData::new_with_gas_data(
+            TransactionKind::ProgrammableTransaction(builder.finish()),
+            *sender,
+            GasData {
+                payment: vec![self.coin],
+                owner: self.sponsor.address,
+                price: gas_price,
+                budget: GAS_BUDGET,
+            },
+        );
+        to_sender_signed_transaction_with_multi_signers(
+            data,
+            vec![sender_keypair.as_ref(), self.sponsor.keypair.as_ref()],
+        )
+    }
+
+    /// Keeps the reserve of the sponsor, whose top up failed, from topping up any more coins
+    fn retire_reserve(&self, error: &str) {
+        self.stats.failed_top_ups.fetch_add(1, Ordering::Relaxed);
+        if !self.sponsor.reserve_exhausted.swap(true, Ordering::Relaxed) {
+            warn!(
+                "Top up from the reserve of sponsor {} failed, no more top ups: {}",
+                self.sponsor.address, error
+            );
+        }
+    }
+}
+
+impl Payload for GasStationTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        match self.top_up_reserve.take() {
+            Some(_) => {
+                // Inputs get new versions even if the top up failed
+                self.coin = effects
+                    .mutated()
+                    .into_iter()
+                    .map(|(obj_ref, _)| obj_ref)
+                    .find(|obj_ref| obj_ref.0 == self.coin.0)
+                    .expect("Top up should mutate the sponsor coin");
+                match effects.failure_status() {
+                    None => {
+                        *self.sponsor.reserve.lock().unwrap() = Some(effects.gas_object().0);
+                        self.balance += SPONSOR_COIN_AMOUNT;
+                        self.stats.top_ups.fetch_add(1, Ordering::Relaxed);
+                    }
+                    // Usually the reserve holding less than a top up
+                    Some(error) => self.retire_reserve(&error),
+                }
+            }
+            None => {
+                self.coin = effects.gas_object().0;
+                self.balance = self
+                    .balance
+                    .saturating_sub(std::cmp::max(effects.net_gas_used(), 0) as u64);
+                self.stats.sponsored_txs.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let gas_price = self
+            .system_state_observer
+            .state
+            .borrow()
+            .reference_gas_price;
+        if self.balance < TOP_UP_THRESHOLD
+            && !self.sponsor.reserve_exhausted.load(Ordering::Relaxed)
+        {
+            let reserve = self.sponsor.reserve.lock().unwrap().take();
+            match reserve {
+                Some(reserve) => {
+                    self.top_up_reserve = Some(reserve);
+                    return self.make_top_up_transaction(reserve, gas_price);
+                }
+                // Keep spending the coin and try again next time
+                None => {
+                    self.stats.reserve_busy.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        self.make_sponsored_transaction(gas_price)
+    }
+
+    fn handle_error(&mut self, error: &anyhow::Error) -> bool {
+        if self.top_up_reserve.is_some() {
+            if is_gas_balance_error(error) {
+                // The reserve cannot even pay for the gas of the top up
+                self.top_up_reserve = None;
+                self.retire_reserve(&error.to_string());
+                return false;
+            }
+            // Any other rejection is retried as is: the signed top up may still lock the
+            // reserve, so no other transaction may use it
+            return true;
+        }
+        if is_gas_balance_error(error) {
+            self.stats.exhausted.fetch_add(1, Ordering::Relaxed);
+            warn!(
+                "Sponsor coin {} ran dry with {} MIST left by our count",
+                self.coin.0, self.balance
+            );
+            // Top up as soon as the reserve is free rather than retrying the same transaction
+            self.balance = 0;
+            return false;
+        }
+        true
+    }
+}
+
+#[derive(Debug)]
+pub struct GasStationWorkloadBuilder {
+    num_payloads: u64,
+    senders_per_sponsor: u64,
+    coins_per_sponsor: u64,
+    /// Sponsor of each group of `coins_per_sponsor` payloads
+    sponsors: Vec<(SuiAddress, Arc<AccountKeyPair>)>,
+}
+
+impl GasStationWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        senders_per_sponsor: u64,
+        coins_per_sponsor: u64,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let coins_per_sponsor = std::cmp::max(coins_per_sponsor, 1);
+            // Sponsors are generated upfront so that the sponsor coins of the payloads and the
+            // reserves of the same sponsor go to the same address
+            let num_sponsors = (max_ops + coins_per_sponsor - 1) / coins_per_sponsor;
+            let sponsors = (0..num_sponsors)
+                .map(|_| {
+                    let (address, keypair) = get_key_pair();
+                    (address, Arc::new(keypair))
+                })
+                .collect();
+            let workload_builder = Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(
+                GasStationWorkloadBuilder {
+                    num_payloads: max_ops,
+                    senders_per_sponsor: std::cmp::max(senders_per_sponsor, 1),
+                    coins_per_sponsor,
+                    sponsors,
+                },
+            ));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for GasStationWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        // Gas coin for publishing package, then the reserve of every sponsor
+        let (address, keypair) = get_key_pair();
+        std::iter::once(GasCoinConfig {
+            amount: MAX_GAS_FOR_TESTING,
+            address,
+            keypair: Arc::new(keypair),
+        })
+        .chain(
+            self.sponsors
+                .iter()
+                .map(|(address, keypair)| GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address: *address,
+                    keypair: keypair.clone(),
+                }),
+        )
+        .collect()
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        (0..self.num_payloads)
+            .map(|i| {
+                let (address, keypair) = &self.sponsors[(i / self.coins_per_sponsor) as usize];
+                GasCoinConfig {
+                    amount: SPONSOR_COIN_AMOUNT,
+                    address: *address,
+                    keypair: keypair.clone(),
+                }
+            })
+            .collect()
+    }
+    fn required_addresses(&self) -> Vec<SuiAddress> {
+        self.sponsors.iter().map(|(address, _)| *address).collect()
+    }
+    async fn build(
+        &self,
+        init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(GasStationWorkload {
+            basics_package_id: None,
+            senders_per_sponsor: self.senders_per_sponsor,
+            init_gas,
+            payload_gas,
+            stats: Arc::default(),
+        }))
+    }
+}
+
+/// A gas station: every sponsor pays for the transactions of `senders_per_sponsor` senders,
+/// which own no coins, out of `coins_per_sponsor` sponsor coins. Each sponsor coin can only be
+/// used by one transaction at a time, so the sponsor coins are the bottleneck, and they run
+/// dry over time. A coin running low is topped up from the sponsor's reserve, which serves one
+/// top up at a time; coins which run out before they get their turn are reported as exhausted.
+/// Once a top up fails, e.g. because the reserve ran low itself, the reserve tops up no more.
+#[derive(Debug)]
+pub struct GasStationWorkload {
+    basics_package_id: Option<ObjectID>,
+    senders_per_sponsor: u64,
+    init_gas: Vec<Gas>,
+    payload_gas: Vec<Gas>,
+    stats: Arc<GasStationStats>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for GasStationWorkload {
+    async fn init(
+        &mut self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        if self.basics_package_id.is_some() {
+            return Ok(());
+        }
+        let gas_price = system_state_observer.state.borrow().reference_gas_price;
+        let gas = self
+            .init_gas
+            .first()
+            .expect("Not enough gas to initialize gas station workload");
+        info!("Publishing basics package");
+        self.basics_package_id = Some(
+            publish_basics_package(gas.0, proxy, gas.1, &gas.2, gas_price)
+                .await
+                .0,
+        );
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        let sponsors: Vec<Arc<Sponsor>> = self.init_gas[1..]
+            .iter()
+            .map(|(reserve, address, keypair)| {
+                Arc::new(Sponsor {
+                    address: *address,
+                    keypair: keypair.clone(),
+                    reserve: Mutex::new(Some(*reserve)),
+                    reserve_exhausted: AtomicBool::new(false),
+                    senders: (0..self.senders_per_sponsor)
+                        .map(|_| {
+                            let (address, keypair) = get_key_pair();
+                            (address, Arc::new(keypair))
+                        })
+                        .collect(),
+                    next_sender: AtomicUsize::new(0),
+                })
+            })
+            .collect();
+        self.payload_gas
+            .iter()
+            .map(|(coin, owner, _)| {
+                let sponsor = sponsors
+                    .iter()
+                    .find(|sponsor| sponsor.address == *owner)
+                    .expect("Every sponsor coin should belong to a sponsor with a reserve");
+                Box::new(GasStationTestPayload {
+                    package_id: self.basics_package_id.unwrap(),
+                    sponsor: sponsor.clone(),
+                    coin: *coin,
+                    balance: SPONSOR_COIN_AMOUNT,
+                    top_up_reserve: None,
+                    stats: self.stats.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        self.stats.counters()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system_state_observer::SystemState;
+    use sui_types::base_types::random_object_ref;
+    use sui_types::messages::{
+        ExecutionFailureStatus, ExecutionStatus, TransactionEffects, TransactionEffectsV1,
+        TransactionEvents,
+    };
+    use sui_types::object::Owner;
+
+    /// Payload of a sponsor with a single sender, whose sponsor coin is due for a top up
+    fn make_payload() -> GasStationTestPayload {
+        let (address, keypair) = get_key_pair();
+        let (sender, sender_keypair) = get_key_pair();
+        let state = SystemState {
+            epoch: 0,
+            reference_gas_price: 1000,
+            protocol_config: None,
+            active_validators: vec![],
+        };
+        GasStationTestPayload {
+            package_id: ObjectID::random(),
+            sponsor: Arc::new(Sponsor {
+                address,
+                keypair: Arc::new(keypair),
+                reserve: Mutex::new(Some(random_object_ref())),
+                reserve_exhausted: AtomicBool::new(false),
+                senders: vec![(sender, Arc::new(sender_keypair))],
+                next_sender: AtomicUsize::new(0),
+            }),
+            coin: random_object_ref(),
+            balance: 0,
+            top_up_reserve: None,
+            stats: Arc::default(),
+            system_state_observer: Arc::new(SystemStateObserver::new_for_testing(state)),
+        }
+    }
+
+    /// Effects of the top up of `payload`, which failed in execution
+    fn failed_top_up(payload: &GasStationTestPayload) -> ExecutionEffects {
+        let owner = Owner::AddressOwner(payload.sponsor.address);
+        let reserve = random_object_ref();
+        ExecutionEffects::new_for_testing(
+            TransactionEffects::V1(TransactionEffectsV1 {
+                status: ExecutionStatus::Failure {
+                    error: ExecutionFailureStatus::InsufficientCoinBalance,
+                    command: Some(0),
+                },
+                mutated: vec![(reserve, owner), (payload.coin, owner)],
+                gas_object: (reserve, owner),
+                ..Default::default()
+            }),
+            TransactionEvents::default(),
+        )
+    }
+
+    #[test]
+    fn test_failed_top_up_is_not_credited() {
+        let mut payload = make_payload();
+        payload.make_transaction();
+        assert!(payload.top_up_reserve.is_some());
+        payload.make_new_payload(&failed_top_up(&payload));
+        assert_eq!(payload.balance, 0);
+        assert_eq!(payload.stats.top_ups.load(Ordering::Relaxed), 0);
+        assert_eq!(payload.stats.failed_top_ups.load(Ordering::Relaxed), 1);
+        // The reserve tops up no more, the coin is spent until it runs dry
+        assert!(payload.sponsor.reserve.lock().unwrap().is_none());
+        payload.make_transaction();
+        assert!(payload.top_up_reserve.is_none());
+        assert_eq!(payload.stats.reserve_busy.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_top_up_rejected_for_gas_retires_reserve() {
+        let mut payload = make_payload();
+        payload.make_transaction();
+        assert!(!payload.handle_error(&anyhow::anyhow!("GasBalanceTooLow")));
+        assert!(payload.top_up_reserve.is_none());
+        assert!(payload.sponsor.reserve_exhausted.load(Ordering::Relaxed));
+        // Other rejections of a top up are retried as is
+        let mut payload = make_payload();
+        payload.make_transaction();
+        assert!(payload.handle_error(&anyhow::anyhow!("connection reset")));
+        assert!(payload.top_up_reserve.is_some());
+    }
+}
@@ -0,0 +1,359 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::in_memory_wallet::move_call_pt_impl;
+use crate::system_state_observer::SystemStateObserver;
+use crate::util::publish_package;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{BenchMoveCallArg, ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use rand::Rng;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use sui_types::base_types::{ObjectID, SequenceNumber};
+use sui_types::crypto::get_key_pair;
+use sui_types::messages::VerifiedTransaction;
+use sui_types::object::Owner;
+use test_utils::transaction::parse_package_ref;
+use tracing::info;
+
+const MODULE_NAME: &str = "governance";
+/// Proposals a payload keeps voting on, the oldest one is forgotten past this many
+const MAX_KNOWN_PROPOSALS: usize = 64;
+
+/// Returns the (id, initial shared version) of the proposal created in `effects`, if any
+fn created_proposal(effects: &ExecutionEffects) -> Option<(ObjectID, SequenceNumber)> {
+    effects
+        .created()
+        .into_iter()
+        .find_map(|(obj_ref, owner)| match owner {
+            Owner::Shared {
+                initial_shared_version,
+            } => Some((obj_ref.0, initial_shared_version)),
+            _ => None,
+        })
+}
+
+#[derive(Debug)]
+pub struct GovernanceTestPayload {
+    package_id: ObjectID,
+    /// Shared proposals known to this payload, as (id, initial shared version), at most
+    /// `MAX_KNOWN_PROPOSALS` of the latest ones
+    proposals: VecDeque<(ObjectID, SequenceNumber)>,
+    /// Percentage (0-100) of transactions which create a proposal rather than vote
+    proposal_ratio: u32,
+    /// Whether the last transaction created a new proposal
+    last_was_proposal: bool,
+    gas: Gas,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for GovernanceTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "governance")
+    }
+}
+
+impl Payload for GovernanceTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        if self.last_was_proposal {
+            // Remember the proposal we just created so that it can also receive votes
+            if let Some(proposal) = created_proposal(effects) {
+                if self.proposals.len() == MAX_KNOWN_PROPOSALS {
+                    self.proposals.pop_front();
+                }
+                self.proposals.push_back(proposal);
+            }
+        }
+        self.gas.0 = effects.gas_object().0;
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let mut rng = rand::thread_rng();
+        let (function, args) = if rng.gen_range(0..100) < self.proposal_ratio {
+            self.last_was_proposal = true;
+            ("propose", vec![])
+        } else {
+            self.last_was_proposal = false;
+            assert!(
+                !self.proposals.is_empty(),
+                "Governance payload should know at least one proposal"
+            );
+            let (id, initial_shared_version) =
+                self.proposals[rng.gen_range(0..self.proposals.len())];
+            let approve: bool = rng.gen();
+            (
+                "vote",
+                vec![
+                    BenchMoveCallArg::Shared((id, initial_shared_version, true)),
+                    approve.into(),
+                ],
+            )
+        };
+        let (gas_budget, gas_price) = {
+            let state = self.system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        move_call_pt_impl(
+            self.gas.1,
+            &self.gas.2,
+            self.package_id,
+            MODULE_NAME,
+            function,
+            vec![],
+            args,
+            &self.gas.0,
+            gas_budget,
+            gas_price,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct GovernanceWorkloadBuilder {
+    num_payloads: u64,
+    proposal_ratio: u32,
+}
+
+impl GovernanceWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        proposal_ratio: u32,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder = Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(
+                GovernanceWorkloadBuilder {
+                    num_payloads: max_ops,
+                    proposal_ratio: std::cmp::min(proposal_ratio, 100),
+                },
+            ));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for GovernanceWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        // Gas coins for publishing the governance package and creating the first proposal
+        (0..2)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        (0..self.num_payloads)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(GovernanceWorkload {
+            package_id: None,
+            initial_proposal: None,
+            proposal_ratio: self.proposal_ratio,
+            init_gas,
+            payload_gas,
+        }))
+    }
+}
+
+/// Models on-chain governance as a read-modify-write on shared proposal objects. The system state
+/// has no proposal or vote functions to target (`sui_system` only covers staking and validator
+/// management), so proposals are created and shared by the benchmark-local Move package in
+/// `data/governance` instead. Votes therefore contend on objects other than the system state
+/// object that the delegation workload targets, and only the reference gas price is taken from
+/// the system state.
+#[derive(Debug)]
+pub struct GovernanceWorkload {
+    package_id: Option<ObjectID>,
+    initial_proposal: Option<(ObjectID, SequenceNumber)>,
+    proposal_ratio: u32,
+    init_gas: Vec<Gas>,
+    payload_gas: Vec<Gas>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for GovernanceWorkload {
+    async fn init(
+        &mut self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        if self.package_id.is_some() {
+            return Ok(());
+        }
+        let (gas_budget, gas_price) = {
+            let state = system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        let (publish_gas, proposal_gas) = match self.init_gas.as_slice() {
+            [publish_gas, proposal_gas] => (publish_gas, proposal_gas),
+            _ => panic!("Not enough gas to initialize governance workload"),
+        };
+
+        info!("Publishing governance package");
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/workloads/data/governance");
+        let effects = publish_package(
+            path,
+            publish_gas.0,
+            proxy.clone(),
+            publish_gas.1,
+            &publish_gas.2,
+            gas_price,
+        )
+        .await;
+        let package_id = parse_package_ref(&effects.created())
+            .expect("Governance package should be published")
+            .0;
+        self.package_id = Some(package_id);
+
+        // Create the initial proposal so that votes always have a target
+        let transaction = move_call_pt_impl(
+            proposal_gas.1,
+            &proposal_gas.2,
+            package_id,
+            MODULE_NAME,
+            "propose",
+            vec![],
+            vec![],
+            &proposal_gas.0,
+            gas_budget,
+            gas_price,
+        );
+        let effects = proxy
+            .execute_transaction_block(transaction.into())
+            .await
+            .expect("Failed to create initial governance proposal");
+        self.initial_proposal = created_proposal(&effects);
+        assert!(
+            self.initial_proposal.is_some(),
+            "Initial governance proposal must be created"
+        );
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        let package_id = self.package_id.unwrap();
+        let initial_proposal = self.initial_proposal.unwrap();
+        self.payload_gas
+            .iter()
+            .map(|gas| {
+                Box::new(GovernanceTestPayload {
+                    package_id,
+                    proposals: VecDeque::from([initial_proposal]),
+                    proposal_ratio: self.proposal_ratio,
+                    last_was_proposal: false,
+                    gas: gas.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system_state_observer::SystemState;
+    use sui_types::base_types::random_object_ref;
+    use sui_types::messages::{TransactionEffects, TransactionEffectsV1, TransactionEvents};
+
+    #[test]
+    fn test_known_proposals_are_capped() {
+        let (address, keypair) = get_key_pair();
+        let initial_proposal = (ObjectID::random(), SequenceNumber::from(1));
+        let mut payload = GovernanceTestPayload {
+            package_id: ObjectID::random(),
+            proposals: VecDeque::from([initial_proposal]),
+            proposal_ratio: 100,
+            last_was_proposal: true,
+            gas: (random_object_ref(), address, Arc::new(keypair)),
+            system_state_observer: Arc::new(SystemStateObserver::new_for_testing(SystemState {
+                epoch: 0,
+                reference_gas_price: 1000,
+                protocol_config: None,
+                active_validators: vec![],
+            })),
+        };
+        let owner = Owner::AddressOwner(address);
+        for _ in 0..MAX_KNOWN_PROPOSALS * 2 {
+            let gas = random_object_ref();
+            let effects = TransactionEffects::V1(TransactionEffectsV1 {
+                created: vec![(
+                    random_object_ref(),
+                    Owner::Shared {
+                        initial_shared_version: SequenceNumber::from(1),
+                    },
+                )],
+                mutated: vec![(gas, owner)],
+                gas_object: (gas, owner),
+                ..Default::default()
+            });
+            payload.make_new_payload(&ExecutionEffects::new_for_testing(
+                effects,
+                TransactionEvents::default(),
+            ));
+        }
+        assert_eq!(payload.proposals.len(), MAX_KNOWN_PROPOSALS);
+        // The oldest proposals are the ones forgotten
+        assert!(!payload.proposals.contains(&initial_proposal));
+    }
+}
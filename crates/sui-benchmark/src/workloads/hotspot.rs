@@ -0,0 +1,199 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::system_state_observer::SystemStateObserver;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{ExecutionEffects, ValidatorProxy};
+use anyhow::{bail, ensure, Context, Result};
+use async_trait::async_trait;
+use std::sync::Arc;
+use sui_types::base_types::{ObjectID, SequenceNumber};
+use sui_types::crypto::get_key_pair;
+use sui_types::messages::VerifiedTransaction;
+use sui_types::object::Owner;
+use test_utils::messages::make_counter_increment_transaction;
+use tracing::info;
+
+#[derive(Debug)]
+pub struct HotspotTestPayload {
+    package_id: ObjectID,
+    object_id: ObjectID,
+    initial_shared_version: SequenceNumber,
+    gas: Gas,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for HotspotTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "hotspot")
+    }
+}
+
+impl Payload for HotspotTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        self.gas.0 = effects.gas_object().0;
+    }
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        make_counter_increment_transaction(
+            self.gas.0,
+            self.package_id,
+            self.object_id,
+            self.initial_shared_version,
+            self.gas.1,
+            &self.gas.2,
+            Some(
+                self.system_state_observer
+                    .state
+                    .borrow()
+                    .reference_gas_price,
+            ),
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct HotspotWorkloadBuilder {
+    object_id: ObjectID,
+    num_payloads: u64,
+}
+
+impl HotspotWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        object_id: ObjectID,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder =
+                Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(HotspotWorkloadBuilder {
+                    object_id,
+                    num_payloads: max_ops,
+                }));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for HotspotWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        vec![]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        (0..self.num_payloads)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        _init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(HotspotWorkload {
+            object_id: self.object_id,
+            target: None,
+            payload_gas,
+        }))
+    }
+}
+
+/// Sends every transaction to the same, user supplied, shared object. Unlike the shared counter
+/// workload, which spreads load over counters it creates itself, this reproduces contention on
+/// one specific object. The object must be a shared `counter::Counter` of a published basics
+/// package, since the workload has to know a function which mutates it.
+#[derive(Debug)]
+pub struct HotspotWorkload {
+    object_id: ObjectID,
+    /// Package defining the object and its initial shared version, resolved in `init`
+    target: Option<(ObjectID, SequenceNumber)>,
+    payload_gas: Vec<Gas>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for HotspotWorkload {
+    async fn init(
+        &mut self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        _system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        if self.target.is_some() {
+            return Ok(());
+        }
+        let object = proxy
+            .get_object(self.object_id)
+            .await
+            .with_context(|| format!("Hotspot object {} not found", self.object_id))?;
+        let initial_shared_version = match object.owner {
+            Owner::Shared {
+                initial_shared_version,
+            } => initial_shared_version,
+            owner => bail!(
+                "Hotspot object {} must be shared, it is owned by {}",
+                self.object_id,
+                owner
+            ),
+        };
+        let struct_tag = object
+            .struct_tag()
+            .with_context(|| format!("Hotspot object {} is a package", self.object_id))?;
+        ensure!(
+            struct_tag.module.as_str() == "counter" && struct_tag.name.as_str() == "Counter",
+            "Hotspot object {} has type {}, only counter::Counter objects can be targeted",
+            self.object_id,
+            struct_tag
+        );
+        let package_id = ObjectID::from(struct_tag.address);
+        info!(
+            "Targeting hotspot object {} of package {}",
+            self.object_id, package_id
+        );
+        self.target = Some((package_id, initial_shared_version));
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        let (package_id, initial_shared_version) = self.target.unwrap();
+        self.payload_gas
+            .iter()
+            .map(|gas| {
+                Box::new(HotspotTestPayload {
+                    package_id,
+                    object_id: self.object_id,
+                    initial_shared_version,
+                    gas: gas.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+}
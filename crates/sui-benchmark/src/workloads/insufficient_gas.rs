@@ -0,0 +1,322 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::system_state_observer::SystemStateObserver;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use rand::Rng;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use sui_types::crypto::get_key_pair;
+use sui_types::messages::{TransactionData, VerifiedTransaction};
+use sui_types::utils::to_sender_signed_transaction;
+use tracing::{error, warn};
+
+/// Whether an execution failure or error is about the gas budget of the transaction
+fn is_insufficient_gas(error: &str) -> bool {
+    error.contains("InsufficientGas") || error.contains("GasBudgetTooLow")
+}
+
+/// Outcomes of the transactions of all payloads of the workload
+#[derive(Debug, Default)]
+struct InsufficientGasStats {
+    under_funded_submitted: AtomicU64,
+    /// Under-funded transactions which failed in execution for running out of gas, as expected
+    out_of_gas: AtomicU64,
+    /// Under-funded transactions which ran out of gas and were charged no gas
+    uncharged: AtomicU64,
+    /// Gas charged to the transactions which ran out of gas
+    out_of_gas_charged: AtomicU64,
+    /// Under-funded transactions rejected before execution, i.e. under the minimum gas budget
+    rejected: AtomicU64,
+    /// Errors of under-funded transactions unrelated to gas, after which they are retried
+    retried: AtomicU64,
+    /// Under-funded transactions which were executed successfully, i.e. the budget was enough
+    succeeded: AtomicU64,
+    /// Transactions which failed, but not for their gas budget
+    other_failures: AtomicU64,
+    /// Under-funded transactions which ran out of gas, were rejected or succeeded
+    resolved: AtomicU64,
+}
+
+impl InsufficientGasStats {
+    fn counters(&self) -> Vec<(String, u64)> {
+        vec![
+            (
+                "under-funded submitted".into(),
+                self.under_funded_submitted.load(Ordering::Relaxed),
+            ),
+            ("out of gas".into(), self.out_of_gas.load(Ordering::Relaxed)),
+            (
+                "out of gas: charged".into(),
+                self.out_of_gas_charged.load(Ordering::Relaxed),
+            ),
+            (
+                "out of gas: uncharged".into(),
+                self.uncharged.load(Ordering::Relaxed),
+            ),
+            ("rejected".into(), self.rejected.load(Ordering::Relaxed)),
+            ("retried".into(), self.retried.load(Ordering::Relaxed)),
+            ("succeeded".into(), self.succeeded.load(Ordering::Relaxed)),
+            (
+                "other failures".into(),
+                self.other_failures.load(Ordering::Relaxed),
+            ),
+            ("resolved".into(), self.resolved.load(Ordering::Relaxed)),
+        ]
+    }
+}
+
+#[derive(Debug)]
+pub struct InsufficientGasTestPayload {
+    gas: Gas,
+    /// Fraction (0.0 to 1.0) of transactions whose gas budget is under their cost
+    under_funded_ratio: f32,
+    /// Fraction (0.0 to 1.0) of the cost which the budget of under-funded transactions lacks
+    margin: f32,
+    /// Gas charged to the last fully funded transaction which succeeded, i.e. what the next
+    /// transaction costs since all of them are the same. Unknown until one has been executed.
+    cost: Option<u64>,
+    /// Whether the last transaction was under-funded
+    last_was_under_funded: bool,
+    stats: Arc<InsufficientGasStats>,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for InsufficientGasTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "insufficient_gas")
+    }
+}
+
+impl InsufficientGasTestPayload {
+    fn record_under_funded(&self, stat: &AtomicU64) {
+        stat.fetch_add(1, Ordering::Relaxed);
+        self.stats.resolved.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Payload for InsufficientGasTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        let summary = effects.gas_cost_summary();
+        match (self.last_was_under_funded, effects.failure_status()) {
+            (true, Some(error)) if is_insufficient_gas(&error) => {
+                let charged = effects.gas_used();
+                self.stats
+                    .out_of_gas_charged
+                    .fetch_add(charged, Ordering::Relaxed);
+                if charged == 0 {
+                    self.stats.uncharged.fetch_add(1, Ordering::Relaxed);
+                    error!("Transaction out of gas was charged no gas: {}", error);
+                }
+                self.record_under_funded(&self.stats.out_of_gas);
+            }
+            (true, None) => {
+                warn!(
+                    "Under-funded transaction succeeded, charged {} for an expected cost of {:?}",
+                    effects.gas_used(),
+                    self.cost
+                );
+                self.record_under_funded(&self.stats.succeeded);
+            }
+            (_, Some(error)) => {
+                self.stats.other_failures.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    "Insufficient gas transaction failed for another reason: {}",
+                    error
+                );
+            }
+            (false, None) => {
+                self.cost = Some(summary.computation_cost + summary.storage_cost);
+            }
+        }
+        // The gas coin is charged whether the transaction ran out of gas or not
+        self.gas.0 = effects.gas_object().0;
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let (max_gas_budget, min_gas_budget, gas_price) = {
+            let state = self.system_state_observer.state.borrow();
+            let protocol_config = state
+                .protocol_config
+                .as_ref()
+                .expect("Protocol config not in system state");
+            // Budgets are in MIST, the fixed cost is only in MIST as well from gas model 2 on,
+            // before that it is in gas units
+            let min_gas_budget = match protocol_config.gas_model_version() {
+                1 => protocol_config
+                    .base_tx_cost_fixed()
+                    .saturating_mul(state.reference_gas_price),
+                _ => protocol_config.base_tx_cost_fixed(),
+            };
+            (
+                protocol_config.max_tx_gas(),
+                min_gas_budget,
+                state.reference_gas_price,
+            )
+        };
+        // Until the cost is known, transactions are fully funded to measure it
+        self.last_was_under_funded =
+            self.cost.is_some() && rand::thread_rng().gen::<f32>() < self.under_funded_ratio;
+        let gas_budget = match self.cost {
+            Some(cost) if self.last_was_under_funded => {
+                self.stats
+                    .under_funded_submitted
+                    .fetch_add(1, Ordering::Relaxed);
+                std::cmp::max(
+                    (cost as f64 * (1.0 - self.margin as f64)) as u64,
+                    min_gas_budget,
+                )
+            }
+            _ => max_gas_budget,
+        };
+        let data = TransactionData::new_transfer_sui(
+            self.gas.1, self.gas.1, None, self.gas.0, gas_budget, gas_price,
+        );
+        to_sender_signed_transaction(data, &self.gas.2)
+    }
+
+    fn handle_error(&mut self, error: &anyhow::Error) -> bool {
+        if !self.last_was_under_funded {
+            return true;
+        }
+        if is_insufficient_gas(&format!("{:?}", error)) {
+            // Rejected before execution, so the gas coin is untouched and the next transaction
+            // gets a new budget rather than retrying this one
+            self.last_was_under_funded = false;
+            self.record_under_funded(&self.stats.rejected);
+            false
+        } else {
+            self.stats.retried.fetch_add(1, Ordering::Relaxed);
+            true
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct InsufficientGasWorkloadBuilder {
+    num_payloads: u64,
+    under_funded_ratio: f32,
+    margin: f32,
+}
+
+impl InsufficientGasWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        under_funded_ratio: f32,
+        margin: f32,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder = Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(
+                InsufficientGasWorkloadBuilder {
+                    num_payloads: max_ops,
+                    under_funded_ratio: under_funded_ratio.clamp(0.0, 1.0),
+                    margin: margin.clamp(0.0, 1.0),
+                },
+            ));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for InsufficientGasWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        vec![]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        (0..self.num_payloads)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        _init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(InsufficientGasWorkload {
+            under_funded_ratio: self.under_funded_ratio,
+            margin: self.margin,
+            payload_gas,
+            stats: Arc::default(),
+        }))
+    }
+}
+
+/// Transfers coins to their own owner, with a gas budget under the cost of the transfer for a
+/// fraction of them. Under-funded transactions are expected to run out of gas in execution and
+/// to be charged nonetheless, and are not retried. The cost is measured by the fully funded
+/// transactions of every payload, and the under-funded budget is a configurable margin below it,
+/// but never below the minimum budget, under which validators reject transactions outright.
+#[derive(Debug)]
+pub struct InsufficientGasWorkload {
+    under_funded_ratio: f32,
+    margin: f32,
+    payload_gas: Vec<Gas>,
+    stats: Arc<InsufficientGasStats>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for InsufficientGasWorkload {
+    async fn init(
+        &mut self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        _system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        self.payload_gas
+            .iter()
+            .map(|gas| {
+                Box::new(InsufficientGasTestPayload {
+                    gas: gas.clone(),
+                    under_funded_ratio: self.under_funded_ratio,
+                    margin: self.margin,
+                    cost: None,
+                    last_was_under_funded: false,
+                    stats: self.stats.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        self.stats.counters()
+    }
+}
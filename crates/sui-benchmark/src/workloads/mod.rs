@@ -3,11 +3,35 @@
 
 pub mod adversarial;
 pub mod batch_payment;
+pub mod clock;
+pub mod custom_coin;
 pub mod delegation;
+pub mod equivocation;
+pub mod freeze;
+pub mod gas_smash;
+pub mod gas_station;
+pub mod governance;
+pub mod hotspot;
+pub mod insufficient_gas;
+pub mod move_abort;
+pub mod move_call;
+pub mod object_size;
 pub mod payload;
+pub mod ping_pong;
+pub mod pure_input;
+pub mod read_write;
 pub mod shared_counter;
+pub mod signature_schemes;
+pub mod size_limit;
+pub mod stake_withdraw;
+pub mod system_state_read;
 pub mod transfer_object;
+pub mod type_args;
+pub mod upgrade;
+pub mod validator_lifecycle;
+pub mod vector_sum;
 pub mod workload;
+pub mod workload_config_file;
 pub mod workload_configuration;
 
 use std::sync::Arc;
@@ -15,6 +39,7 @@ use std::sync::Arc;
 use crate::workloads::payload::Payload;
 use sui_types::base_types::{ObjectRef, SuiAddress};
 use sui_types::crypto::AccountKeyPair;
+use thiserror::Error;
 use workload::*;
 
 #[derive(Debug)]
@@ -30,6 +55,21 @@ pub struct WorkloadBuilderInfo {
     pub workload_builder: Box<dyn WorkloadBuilder<dyn Payload>>,
 }
 
+/// Reasons a workload builder could not be created from its configuration
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum WorkloadConfigError {
+    #[error("target qps for the workload is zero, check the weight and --target-qps")]
+    ZeroTargetQps,
+    #[error("number of workers for the workload is zero, check the weight and --num-workers")]
+    ZeroWorkers,
+    #[error("workload would have no operations in flight, check --in-flight-ratio")]
+    ZeroMaxOps,
+    #[error("stake amount {amount} is below the minimum stake of {minimum} MIST")]
+    StakeBelowMinimum { amount: u64, minimum: u64 },
+    #[error("stake amount {amount} does not leave gas out of the {funding} MIST of a payload")]
+    StakeAboveFunding { amount: u64, funding: u64 },
+}
+
 #[derive(Debug)]
 pub struct WorkloadInfo {
     pub workload_params: WorkloadParams,
@@ -0,0 +1,272 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::in_memory_wallet::move_call_pt_impl;
+use crate::system_state_observer::SystemStateObserver;
+use crate::util::publish_package;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use sui_types::base_types::ObjectID;
+use sui_types::crypto::get_key_pair;
+use sui_types::messages::VerifiedTransaction;
+use test_utils::transaction::parse_package_ref;
+use tracing::{info, warn};
+
+const MODULE_NAME: &str = "move_abort";
+
+/// Outcomes of the calls of all payloads of the workload. Every call is expected to abort with
+/// the code of the workload and to be charged gas nonetheless.
+#[derive(Debug, Default)]
+struct MoveAbortStats {
+    calls: AtomicU64,
+    /// Calls which aborted with the expected code
+    aborted: AtomicU64,
+    /// Calls which failed, but not with the expected code, or with a code the effects do not tell
+    other_failures: AtomicU64,
+    /// Calls which did not fail at all
+    succeeded: AtomicU64,
+    /// Failed calls which were charged no gas
+    uncharged: AtomicU64,
+    gas_used: AtomicU64,
+}
+
+impl MoveAbortStats {
+    fn record(&self, effects: &ExecutionEffects, abort_code: u64) {
+        let gas_used = effects.gas_used();
+        self.gas_used.fetch_add(gas_used, Ordering::Relaxed);
+        match effects.failure_status() {
+            None => {
+                self.succeeded.fetch_add(1, Ordering::Relaxed);
+                warn!("Call expected to abort with code {} succeeded", abort_code);
+            }
+            Some(error) => {
+                if effects.move_abort_code() == Some(abort_code) {
+                    self.aborted.fetch_add(1, Ordering::Relaxed);
+                } else {
+                    self.other_failures.fetch_add(1, Ordering::Relaxed);
+                    warn!(
+                        "Call expected to abort with code {} failed otherwise: {}",
+                        abort_code, error
+                    );
+                }
+                if gas_used == 0 {
+                    self.uncharged.fetch_add(1, Ordering::Relaxed);
+                    warn!("Aborted call was charged no gas: {}", error);
+                }
+            }
+        }
+        self.calls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        vec![
+            ("calls".into(), self.calls.load(Ordering::Relaxed)),
+            ("aborted".into(), self.aborted.load(Ordering::Relaxed)),
+            (
+                "other failures".into(),
+                self.other_failures.load(Ordering::Relaxed),
+            ),
+            ("succeeded".into(), self.succeeded.load(Ordering::Relaxed)),
+            ("uncharged".into(), self.uncharged.load(Ordering::Relaxed)),
+            ("gas used".into(), self.gas_used.load(Ordering::Relaxed)),
+        ]
+    }
+}
+
+#[derive(Debug)]
+pub struct MoveAbortTestPayload {
+    package_id: ObjectID,
+    abort_code: u64,
+    gas: Gas,
+    stats: Arc<MoveAbortStats>,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for MoveAbortTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "move_abort")
+    }
+}
+
+impl Payload for MoveAbortTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        self.stats.record(effects, self.abort_code);
+        // Only the gas coin is touched, and it is at a new version whether the call aborted or not
+        self.gas.0 = effects.gas_object().0;
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let (gas_budget, gas_price) = {
+            let state = self.system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        move_call_pt_impl(
+            self.gas.1,
+            &self.gas.2,
+            self.package_id,
+            MODULE_NAME,
+            "abort_with",
+            vec![],
+            vec![self.abort_code.into()],
+            &self.gas.0,
+            gas_budget,
+            gas_price,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct MoveAbortWorkloadBuilder {
+    num_payloads: u64,
+    abort_code: u64,
+}
+
+impl MoveAbortWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        abort_code: u64,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder =
+                Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(MoveAbortWorkloadBuilder {
+                    num_payloads: max_ops,
+                    abort_code,
+                }));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for MoveAbortWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        // Gas coin for publishing package
+        let (address, keypair) = get_key_pair();
+        vec![GasCoinConfig {
+            amount: MAX_GAS_FOR_TESTING,
+            address,
+            keypair: Arc::new(keypair),
+        }]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        (0..self.num_payloads)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(MoveAbortWorkload {
+            package_id: None,
+            abort_code: self.abort_code,
+            init_gas,
+            payload_gas,
+            stats: Arc::default(),
+        }))
+    }
+}
+
+/// Calls an entry function which always aborts with a configurable code, to benchmark the path
+/// of transactions failing in execution, which the other workloads barely take: every call is
+/// charged gas and its changes are rolled back. The stats check that the abort code surfaces in
+/// the effects and that gas is charged.
+#[derive(Debug)]
+pub struct MoveAbortWorkload {
+    package_id: Option<ObjectID>,
+    abort_code: u64,
+    init_gas: Vec<Gas>,
+    payload_gas: Vec<Gas>,
+    stats: Arc<MoveAbortStats>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for MoveAbortWorkload {
+    async fn init(
+        &mut self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        if self.package_id.is_some() {
+            return Ok(());
+        }
+        let gas_price = system_state_observer.state.borrow().reference_gas_price;
+        let gas = self
+            .init_gas
+            .first()
+            .expect("Not enough gas to initialize move abort workload");
+        info!("Publishing move abort package");
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/workloads/data/move_abort");
+        let effects = publish_package(path, gas.0, proxy, gas.1, &gas.2, gas_price).await;
+        self.package_id = Some(
+            parse_package_ref(&effects.created())
+                .expect("Move abort package should be published")
+                .0,
+        );
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        self.payload_gas
+            .iter()
+            .map(|gas| {
+                Box::new(MoveAbortTestPayload {
+                    package_id: self.package_id.unwrap(),
+                    abort_code: self.abort_code,
+                    gas: gas.clone(),
+                    stats: self.stats.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        self.stats.counters()
+    }
+}
@@ -0,0 +1,399 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::system_state_observer::SystemStateObserver;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{convert_move_call_args, BenchMoveCallArg, ExecutionEffects, ValidatorProxy};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::TypeTag;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use sui_types::base_types::{ObjectID, ObjectRef, SuiAddress};
+use sui_types::crypto::get_key_pair;
+use sui_types::messages::{Argument, Command, TransactionData, VerifiedTransaction};
+use sui_types::object::Owner;
+use sui_types::parse_sui_type_tag;
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::utils::to_sender_signed_transaction;
+
+/// One argument of the templated Move call, filled in for every call of a payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoveCallArgTemplate {
+    /// `gas`: the gas coin of the payload. It can only be passed by reference.
+    GasCoin,
+    /// `created`: the latest version of the object created by an earlier call of the payload.
+    Created,
+    /// `sender`: the address of the payload.
+    Sender,
+    /// `object:<id>`: an existing shared or immutable object.
+    Object(ObjectID),
+    /// `bool:<b>`, `u8:<n>`, `u16:<n>`, `u32:<n>`, `u64:<n>`, `u128:<n>` or `address:<a>`: a
+    /// pure literal.
+    Pure(Vec<u8>),
+}
+
+impl FromStr for MoveCallArgTemplate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (kind, value) = match s.split_once(':') {
+            Some((kind, value)) => (kind, Some(value.trim())),
+            None => (s, None),
+        };
+        // unwraps safe because every value of these types is BCS-serializable
+        let pure = match (kind, value) {
+            ("gas", None) => return Ok(MoveCallArgTemplate::GasCoin),
+            ("created", None) => return Ok(MoveCallArgTemplate::Created),
+            ("sender", None) => return Ok(MoveCallArgTemplate::Sender),
+            ("object", Some(id)) => {
+                return Ok(MoveCallArgTemplate::Object(ObjectID::from_str(id)?))
+            }
+            ("bool", Some(b)) => bcs::to_bytes(&b.parse::<bool>()?).unwrap(),
+            ("u8", Some(n)) => bcs::to_bytes(&n.parse::<u8>()?).unwrap(),
+            ("u16", Some(n)) => bcs::to_bytes(&n.parse::<u16>()?).unwrap(),
+            ("u32", Some(n)) => bcs::to_bytes(&n.parse::<u32>()?).unwrap(),
+            ("u64", Some(n)) => bcs::to_bytes(&n.parse::<u64>()?).unwrap(),
+            ("u128", Some(n)) => bcs::to_bytes(&n.parse::<u128>()?).unwrap(),
+            ("address", Some(a)) => bcs::to_bytes(&SuiAddress::from_str(a)?).unwrap(),
+            _ => bail!("invalid move call argument {:?}", s),
+        };
+        Ok(MoveCallArgTemplate::Pure(pure))
+    }
+}
+
+/// Splits `s` at the commas which are not nested in type arguments
+fn split_type_args(s: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// A Move call made by every transaction of the move call workload, see `MoveCallArgTemplate`
+/// for the syntax of its arguments
+#[derive(Debug, Clone)]
+pub struct MoveCallTemplate {
+    package: ObjectID,
+    module: Identifier,
+    function: Identifier,
+    type_args: Vec<TypeTag>,
+    args: Vec<MoveCallArgTemplate>,
+    /// Function of the same module called without arguments before the first call of a payload
+    /// and whenever its created object is gone, to create the object `created` refers to
+    setup_function: Option<Identifier>,
+}
+
+impl MoveCallTemplate {
+    /// `target` is "{package}::{module}::{function}", `type_args` and `args` are comma
+    /// separated lists, e.g. "0x2::coin::Coin<0x2::sui::SUI>" and "created,u64:10,gas"
+    pub fn new(
+        target: &str,
+        type_args: &str,
+        args: &str,
+        setup_function: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let parts: Vec<&str> = target.trim().split("::").collect();
+        let (package, module, function) = match parts.as_slice() {
+            [package, module, function] => (
+                ObjectID::from_str(package)?,
+                Identifier::new(*module)?,
+                Identifier::new(*function)?,
+            ),
+            _ => bail!(
+                "invalid move call target {:?}, expected {{package}}::{{module}}::{{function}}",
+                target
+            ),
+        };
+        let type_args = if type_args.trim().is_empty() {
+            vec![]
+        } else {
+            split_type_args(type_args)
+                .into_iter()
+                .map(|t| parse_sui_type_tag(t.trim()))
+                .collect::<anyhow::Result<_>>()?
+        };
+        let args: Vec<MoveCallArgTemplate> = if args.trim().is_empty() {
+            vec![]
+        } else {
+            args.split(',')
+                .map(MoveCallArgTemplate::from_str)
+                .collect::<anyhow::Result<_>>()?
+        };
+        let setup_function = setup_function.map(Identifier::new).transpose()?;
+        let uses_created = args.contains(&MoveCallArgTemplate::Created);
+        match (uses_created, &setup_function) {
+            (true, None) => bail!("`created` argument needs a setup function creating it"),
+            (false, Some(_)) => bail!("setup function given without a `created` argument"),
+            _ => (),
+        }
+        Ok(MoveCallTemplate {
+            package,
+            module,
+            function,
+            type_args,
+            args,
+            setup_function,
+        })
+    }
+
+    fn objects(&self) -> impl Iterator<Item = ObjectID> + '_ {
+        self.args.iter().filter_map(|arg| match arg {
+            MoveCallArgTemplate::Object(id) => Some(*id),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct MoveCallTestPayload {
+    template: Arc<MoveCallTemplate>,
+    /// Arguments for the `object:` entries of the template, read in `init`
+    objects: Arc<BTreeMap<ObjectID, BenchMoveCallArg>>,
+    /// Object for `created` arguments, `None` until the setup call created it
+    created: Option<ObjectRef>,
+    gas: Gas,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for MoveCallTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "move_call")
+    }
+}
+
+impl MoveCallTestPayload {
+    fn arguments(&self, builder: &mut ProgrammableTransactionBuilder) -> Vec<Argument> {
+        self.template
+            .args
+            .iter()
+            .map(|arg| {
+                let arg = match arg {
+                    MoveCallArgTemplate::GasCoin => return Argument::GasCoin,
+                    // unwrap safe because the setup call is made while there is no object
+                    MoveCallArgTemplate::Created => {
+                        BenchMoveCallArg::ImmOrOwnedObject(self.created.unwrap())
+                    }
+                    // unwrap safe because every address is BCS-serializable
+                    MoveCallArgTemplate::Sender => {
+                        BenchMoveCallArg::Pure(bcs::to_bytes(&self.gas.1).unwrap())
+                    }
+                    MoveCallArgTemplate::Object(id) => self.objects[id].clone(),
+                    MoveCallArgTemplate::Pure(bytes) => BenchMoveCallArg::Pure(bytes.clone()),
+                };
+                convert_move_call_args(&[arg], builder).remove(0)
+            })
+            .collect()
+    }
+}
+
+impl Payload for MoveCallTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        if self.template.setup_function.is_some() {
+            // After the setup call, the newly created object. After a templated call, the new
+            // version of the object if it still belongs to the sender.
+            let sender = Owner::AddressOwner(self.gas.1);
+            self.created = match self.created {
+                None => effects.created_owned_by(self.gas.1).first().copied(),
+                Some((id, _, _)) => effects
+                    .mutated()
+                    .into_iter()
+                    .find(|(obj_ref, owner)| obj_ref.0 == id && *owner == sender)
+                    .map(|(obj_ref, _)| obj_ref),
+            };
+        }
+        self.gas.0 = effects.gas_object().0;
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let (gas_budget, gas_price) = {
+            let state = self.system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        let template = &self.template;
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let command = match (&template.setup_function, self.created) {
+            (Some(setup_function), None) => Command::move_call(
+                template.package,
+                template.module.clone(),
+                setup_function.clone(),
+                vec![],
+                vec![],
+            ),
+            _ => {
+                let args = self.arguments(&mut builder);
+                Command::move_call(
+                    template.package,
+                    template.module.clone(),
+                    template.function.clone(),
+                    template.type_args.clone(),
+                    args,
+                )
+            }
+        };
+        builder.command(command);
+        let data = TransactionData::new_programmable(
+            self.gas.1,
+            vec![self.gas.0],
+            builder.finish(),
+            gas_budget,
+            gas_price,
+        );
+        to_sender_signed_transaction(data, &self.gas.2)
+    }
+}
+
+#[derive(Debug)]
+pub struct MoveCallWorkloadBuilder {
+    num_payloads: u64,
+    template: MoveCallTemplate,
+}
+
+impl MoveCallWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        template: MoveCallTemplate,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder =
+                Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(MoveCallWorkloadBuilder {
+                    num_payloads: max_ops,
+                    template,
+                }));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for MoveCallWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        vec![]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        (0..self.num_payloads)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        _init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(MoveCallWorkload {
+            template: Arc::new(self.template.clone()),
+            objects: Arc::new(BTreeMap::new()),
+            payload_gas,
+        }))
+    }
+}
+
+/// Calls a Move function of an already published package, with arguments filled in from a
+/// template for every call, so that packages can be benchmarked without a dedicated workload.
+#[derive(Debug)]
+pub struct MoveCallWorkload {
+    template: Arc<MoveCallTemplate>,
+    objects: Arc<BTreeMap<ObjectID, BenchMoveCallArg>>,
+    payload_gas: Vec<Gas>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for MoveCallWorkload {
+    async fn init(
+        &mut self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        _system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        let mut objects = BTreeMap::new();
+        for id in self.template.objects() {
+            let object = proxy
+                .get_object(id)
+                .await
+                .with_context(|| format!("Failed to read move call argument {}", id))?;
+            let arg = match object.owner {
+                Owner::Shared {
+                    initial_shared_version,
+                } => BenchMoveCallArg::Shared((id, initial_shared_version, true)),
+                Owner::Immutable => {
+                    BenchMoveCallArg::ImmOrOwnedObject(object.compute_object_reference())
+                }
+                owner => bail!(
+                    "Move call argument {} must be shared or immutable, it is owned by {}",
+                    id,
+                    owner
+                ),
+            };
+            objects.insert(id, arg);
+        }
+        self.objects = Arc::new(objects);
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        self.payload_gas
+            .iter()
+            .map(|gas| {
+                Box::new(MoveCallTestPayload {
+                    template: self.template.clone(),
+                    objects: self.objects.clone(),
+                    created: None,
+                    gas: gas.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::in_memory_wallet::move_call_pt_impl;
+use crate::system_state_observer::SystemStateObserver;
+use crate::util::publish_package;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use sui_types::base_types::ObjectID;
+use sui_types::crypto::get_key_pair;
+use sui_types::messages::VerifiedTransaction;
+use test_utils::transaction::parse_package_ref;
+use tracing::{info, warn};
+
+const MODULE_NAME: &str = "object_size";
+
+/// Gas paid by the transactions of all payloads of the workload
+#[derive(Debug, Default)]
+struct ObjectSizeStats {
+    objects_created: AtomicU64,
+    storage_cost: AtomicU64,
+    computation_cost: AtomicU64,
+    /// Transactions which executed but created no object, e.g. because it was too large
+    failed: AtomicU64,
+}
+
+impl ObjectSizeStats {
+    fn record(&self, effects: &ExecutionEffects, size_bytes: u64) {
+        if effects.created().is_empty() {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+            warn!(
+                "Transaction creating an object of {} bytes failed: {}",
+                size_bytes,
+                effects
+                    .failure_status()
+                    .unwrap_or_else(|| "no object created".to_string())
+            );
+            return;
+        }
+        // The storage cost also covers the new version of the gas coin, which is the same for
+        // every transaction, so differences between runs come from the size of the object
+        let summary = effects.gas_cost_summary();
+        self.storage_cost
+            .fetch_add(summary.storage_cost, Ordering::Relaxed);
+        self.computation_cost
+            .fetch_add(summary.computation_cost, Ordering::Relaxed);
+        self.objects_created.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        vec![
+            (
+                "objects created".into(),
+                self.objects_created.load(Ordering::Relaxed),
+            ),
+            (
+                "storage cost".into(),
+                self.storage_cost.load(Ordering::Relaxed),
+            ),
+            (
+                "computation cost".into(),
+                self.computation_cost.load(Ordering::Relaxed),
+            ),
+            ("failed".into(), self.failed.load(Ordering::Relaxed)),
+        ]
+    }
+}
+
+#[derive(Debug)]
+pub struct ObjectSizeTestPayload {
+    package_id: ObjectID,
+    gas: Gas,
+    /// Number of bytes held by every created object
+    size_bytes: u64,
+    stats: Arc<ObjectSizeStats>,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for ObjectSizeTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "object_size")
+    }
+}
+
+impl Payload for ObjectSizeTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        self.stats.record(effects, self.size_bytes);
+        self.gas.0 = effects.gas_object().0;
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let (gas_budget, gas_price) = {
+            let state = self.system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        move_call_pt_impl(
+            self.gas.1,
+            &self.gas.2,
+            self.package_id,
+            MODULE_NAME,
+            "create",
+            vec![],
+            vec![self.size_bytes.into()],
+            &self.gas.0,
+            gas_budget,
+            gas_price,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct ObjectSizeWorkloadBuilder {
+    num_payloads: u64,
+    size_bytes: u64,
+}
+
+impl ObjectSizeWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        size_bytes: u64,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder = Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(
+                ObjectSizeWorkloadBuilder {
+                    num_payloads: max_ops,
+                    size_bytes,
+                },
+            ));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for ObjectSizeWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        // Gas coin for publishing package
+        let (address, keypair) = get_key_pair();
+        vec![GasCoinConfig {
+            amount: MAX_GAS_FOR_TESTING,
+            address,
+            keypair: Arc::new(keypair),
+        }]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        (0..self.num_payloads)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(ObjectSizeWorkload {
+            package_id: None,
+            size_bytes: self.size_bytes,
+            init_gas,
+            payload_gas,
+            stats: Arc::default(),
+        }))
+    }
+}
+
+/// Creates owned objects holding a configurable number of bytes, to measure how the size of
+/// objects affects storage gas and execution. The objects are never deleted, so the storage
+/// used by a run grows with its length.
+#[derive(Debug)]
+pub struct ObjectSizeWorkload {
+    package_id: Option<ObjectID>,
+    size_bytes: u64,
+    init_gas: Vec<Gas>,
+    payload_gas: Vec<Gas>,
+    stats: Arc<ObjectSizeStats>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for ObjectSizeWorkload {
+    async fn init(
+        &mut self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        if self.package_id.is_some() {
+            return Ok(());
+        }
+        let gas_price = system_state_observer.state.borrow().reference_gas_price;
+        let gas = self
+            .init_gas
+            .first()
+            .expect("Not enough gas to initialize object size workload");
+        info!("Publishing object size package");
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/workloads/data/object_size");
+        let effects = publish_package(path, gas.0, proxy, gas.1, &gas.2, gas_price).await;
+        self.package_id = Some(
+            parse_package_ref(&effects.created())
+                .expect("Object size package should be published")
+                .0,
+        );
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        self.payload_gas
+            .iter()
+            .map(|gas| {
+                Box::new(ObjectSizeTestPayload {
+                    package_id: self.package_id.unwrap(),
+                    gas: gas.clone(),
+                    size_bytes: self.size_bytes,
+                    stats: self.stats.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        self.stats.counters()
+    }
+}
@@ -3,6 +3,8 @@
 
 use crate::ExecutionEffects;
 use std::fmt::Display;
+use std::time::Duration;
+use sui_types::base_types::{ObjectID, ObjectRef};
 use sui_types::messages::VerifiedTransaction;
 
 /// A Payload is a transaction wrapper of a particular type (transfer object, shared counter, etc).
@@ -12,4 +14,64 @@ use sui_types::messages::VerifiedTransaction;
 pub trait Payload: Send + Sync + std::fmt::Debug + Display {
     fn make_new_payload(&mut self, effects: &ExecutionEffects);
     fn make_transaction(&mut self) -> VerifiedTransaction;
+    /// Called when the transaction from the last `make_transaction()` call could not be executed.
+    /// Returns whether the transaction should be retried. Payloads which expect some of their
+    /// transactions to be rejected can return false, in which case the payload is reused for a
+    /// new transaction instead.
+    fn handle_error(&mut self, _error: &anyhow::Error) -> bool {
+        true
+    }
+    /// Called when the transaction from the last `make_transaction()` call failed local
+    /// validation and was never submitted. Returns whether to keep the payload, in which case
+    /// its next `make_transaction()` call must not return the same transaction, which would
+    /// fail again. Payloads which return false are retired.
+    fn handle_invalid_transaction(&mut self, _error: &anyhow::Error) -> bool {
+        true
+    }
+    /// Transaction to submit again after `tx`, from the last `make_transaction()` call, got an
+    /// error which `handle_error()` chose to retry. Only the very same transaction is safe to
+    /// resubmit: a new one for the same objects could execute on top of the first.
+    fn retry_transaction(&mut self, tx: &VerifiedTransaction) -> VerifiedTransaction {
+        tx.clone()
+    }
+    /// Whether transactions of this payload are only dry run instead of executed. Nothing they
+    /// do is committed, so they never reach finality and their gas is not charged.
+    fn is_dry_run(&self) -> bool {
+        false
+    }
+    /// What the transaction from the last `make_transaction()` call targets, e.g. the validator
+    /// a stake goes to. The final stats break down the transactions of payloads which report a
+    /// target by their target.
+    fn stats_target(&self) -> Option<String> {
+        None
+    }
+    /// Time to wait after the response to the transaction from the last `make_transaction()`
+    /// call before making the next one, on top of any think time drawn by the driver, to model
+    /// users pausing between transactions.
+    fn think_time(&self) -> Duration {
+        Duration::ZERO
+    }
+    /// Balance the gas coins of the transaction from the last `make_transaction()` call need to
+    /// hold for it to go through: its gas budget, plus anything the transaction itself takes out
+    /// of the gas coins. Zero if the payload does not know, in which case the driver does not
+    /// check the funding of its transactions.
+    fn min_gas_required(&self) -> u64 {
+        0
+    }
+    /// Checks the invariants of the state of the payload, e.g. that it holds the objects its next
+    /// transaction needs, to catch payloads corrupted by unexpected effects. Only called by the
+    /// driver after every `make_new_payload()` call when it checks payload state.
+    fn validate_state(&self) -> Result<(), String> {
+        Ok(())
+    }
+    /// Objects to read from the proxy after a `make_new_payload()` call, e.g. those whose
+    /// latest versions the payload takes from the proxy rather than from effects. The driver
+    /// reads them before returning the payload to the pool, and hands their references to
+    /// `update_objects()`.
+    fn objects_to_read(&mut self) -> Vec<ObjectID> {
+        vec![]
+    }
+    /// Latest references of the objects of the last `objects_to_read()` call, in the same
+    /// order, with `None` for those which could not be read
+    fn update_objects(&mut self, _objects: Vec<Option<ObjectRef>>) {}
 }
@@ -0,0 +1,156 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::system_state_observer::SystemStateObserver;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+use sui_core::test_utils::make_transfer_sui_transaction;
+use sui_types::base_types::{ObjectRef, SuiAddress};
+use sui_types::crypto::{get_key_pair, AccountKeyPair};
+use sui_types::messages::VerifiedTransaction;
+
+#[derive(Debug)]
+pub struct PingPongTestPayload {
+    /// Coin passed back and forth, paying for its own transfers
+    coin: ObjectRef,
+    /// Current owner of the coin, then the other account
+    owner: (SuiAddress, Arc<AccountKeyPair>),
+    other: (SuiAddress, Arc<AccountKeyPair>),
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for PingPongTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "ping_pong")
+    }
+}
+
+impl Payload for PingPongTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        self.coin = effects.gas_object().0;
+        std::mem::swap(&mut self.owner, &mut self.other);
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        make_transfer_sui_transaction(
+            self.coin,
+            self.other.0,
+            None,
+            self.owner.0,
+            &self.owner.1,
+            Some(
+                self.system_state_observer
+                    .state
+                    .borrow()
+                    .reference_gas_price,
+            ),
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct PingPongWorkloadBuilder {
+    num_payloads: u64,
+}
+
+impl PingPongWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder =
+                Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(PingPongWorkloadBuilder {
+                    num_payloads: max_ops,
+                }));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for PingPongWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        vec![]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        (0..self.num_payloads)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        _init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(PingPongWorkload { payload_gas }))
+    }
+}
+
+/// The latency floor workload, for baselining: every payload passes a single coin back and
+/// forth between two accounts, each transfer using the coin as its own gas. Every transaction
+/// touches one owned object and depends on the previous one, which makes it the cheapest
+/// possible chain of transactions, so its latency is the overhead of any transaction.
+#[derive(Debug)]
+pub struct PingPongWorkload {
+    payload_gas: Vec<Gas>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for PingPongWorkload {
+    async fn init(
+        &mut self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        _system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        self.payload_gas
+            .iter()
+            .map(|(coin, address, keypair)| {
+                let (other_address, other_keypair) = get_key_pair();
+                Box::new(PingPongTestPayload {
+                    coin: *coin,
+                    owner: (*address, keypair.clone()),
+                    other: (other_address, Arc::new(other_keypair)),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+}
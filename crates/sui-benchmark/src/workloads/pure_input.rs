@@ -0,0 +1,285 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::in_memory_wallet::move_call_pt_impl;
+use crate::system_state_observer::SystemStateObserver;
+use crate::util::publish_package;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{BenchMoveCallArg, ExecutionEffects, ValidatorProxy};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use rand::Rng;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use sui_types::base_types::ObjectID;
+use sui_types::crypto::get_key_pair;
+use sui_types::messages::VerifiedTransaction;
+use test_utils::transaction::parse_package_ref;
+use tracing::{info, warn};
+
+const MODULE_NAME: &str = "pure_input";
+
+/// Lengths in bytes of the pure inputs of the workload, parsed from a comma separated list, e.g.
+/// "16,1024,16000". Every payload cycles through them, so that all lengths get the same share
+/// of the transactions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PureInputSizes {
+    sizes: Vec<u64>,
+}
+
+impl Default for PureInputSizes {
+    fn default() -> Self {
+        PureInputSizes { sizes: vec![1024] }
+    }
+}
+
+impl FromStr for PureInputSizes {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let sizes = s
+            .split(',')
+            .map(|size| Ok(size.trim().parse::<u64>()?))
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+        if sizes.iter().any(|size| *size == 0) {
+            return Err(anyhow!("pure input sizes must be positive"));
+        }
+        Ok(PureInputSizes { sizes })
+    }
+}
+
+/// Pure input of `size` random bytes, BCS-encoded as a `vector<u8>`
+fn make_input(size: u64) -> Vec<u8> {
+    let mut rng = rand::thread_rng();
+    let bytes: Vec<u8> = (0..size).map(|_| rng.gen()).collect();
+    // unwrap safe because a vector of bytes is BCS-serializable
+    bcs::to_bytes(&bytes).unwrap()
+}
+
+#[derive(Debug)]
+pub struct PureInputTestPayload {
+    package_id: ObjectID,
+    /// Encoded pure inputs of every configured size, with the size
+    inputs: Arc<Vec<(u64, Vec<u8>)>>,
+    /// Input of the next transaction
+    next: usize,
+    gas: Gas,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for PureInputTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "pure_input")
+    }
+}
+
+impl Payload for PureInputTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        if let Some(error) = effects.failure_status() {
+            warn!(
+                "Call with a pure input of {} bytes failed: {}",
+                self.inputs[self.next].0, error
+            );
+        }
+        self.next = (self.next + 1) % self.inputs.len();
+        self.gas.0 = effects.gas_object().0;
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let (gas_budget, gas_price) = {
+            let state = self.system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        move_call_pt_impl(
+            self.gas.1,
+            &self.gas.2,
+            self.package_id,
+            MODULE_NAME,
+            "read_length",
+            vec![],
+            vec![BenchMoveCallArg::Pure(self.inputs[self.next].1.clone())],
+            &self.gas.0,
+            gas_budget,
+            gas_price,
+        )
+    }
+
+    fn stats_target(&self) -> Option<String> {
+        // Breaks down the latency of the workload by input size
+        Some(format!("{} bytes", self.inputs[self.next].0))
+    }
+}
+
+#[derive(Debug)]
+pub struct PureInputWorkloadBuilder {
+    num_payloads: u64,
+    sizes: PureInputSizes,
+}
+
+impl PureInputWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        sizes: PureInputSizes,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder =
+                Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(PureInputWorkloadBuilder {
+                    num_payloads: max_ops,
+                    sizes,
+                }));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for PureInputWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        // Gas coin for publishing package
+        let (address, keypair) = get_key_pair();
+        vec![GasCoinConfig {
+            amount: MAX_GAS_FOR_TESTING,
+            address,
+            keypair: Arc::new(keypair),
+        }]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        (0..self.num_payloads)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(PureInputWorkload {
+            package_id: None,
+            inputs: Arc::new(
+                self.sizes
+                    .sizes
+                    .iter()
+                    .map(|size| (*size, make_input(*size)))
+                    .collect(),
+            ),
+            init_gas,
+            payload_gas,
+        }))
+    }
+}
+
+/// Calls an entry function which only reads the length of a pure `vector<u8>` input, to isolate
+/// the cost of deserializing and checking pure inputs from that of execution and of object
+/// inputs. Inputs of several sizes can be mixed, and the latency is broken down by input size.
+#[derive(Debug)]
+pub struct PureInputWorkload {
+    package_id: Option<ObjectID>,
+    inputs: Arc<Vec<(u64, Vec<u8>)>>,
+    init_gas: Vec<Gas>,
+    payload_gas: Vec<Gas>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for PureInputWorkload {
+    async fn init(
+        &mut self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        if self.package_id.is_some() {
+            return Ok(());
+        }
+        let max_pure_argument_size = system_state_observer
+            .state
+            .borrow()
+            .protocol_config
+            .as_ref()
+            .map(|protocol_config| protocol_config.max_pure_argument_size());
+        if let Some(max_pure_argument_size) = max_pure_argument_size {
+            // The limit applies to the encoded input, length prefix included
+            if let Some((size, input)) = self
+                .inputs
+                .iter()
+                .find(|(_, input)| input.len() > max_pure_argument_size as usize)
+            {
+                bail!(
+                    "Pure input of {} bytes is {} bytes encoded, over the limit of {} bytes",
+                    size,
+                    input.len(),
+                    max_pure_argument_size
+                );
+            }
+        }
+        let gas_price = system_state_observer.state.borrow().reference_gas_price;
+        let gas = self
+            .init_gas
+            .first()
+            .expect("Not enough gas to initialize pure input workload");
+        info!("Publishing pure input package");
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/workloads/data/pure_input");
+        let effects = publish_package(path, gas.0, proxy, gas.1, &gas.2, gas_price).await;
+        self.package_id = Some(
+            parse_package_ref(&effects.created())
+                .expect("Pure input package should be published")
+                .0,
+        );
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        self.payload_gas
+            .iter()
+            .enumerate()
+            .map(|(i, gas)| {
+                Box::new(PureInputTestPayload {
+                    package_id: self.package_id.unwrap(),
+                    inputs: self.inputs.clone(),
+                    // Payloads start at different sizes so that every size is in flight at once
+                    next: i % self.inputs.len(),
+                    gas: gas.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+}
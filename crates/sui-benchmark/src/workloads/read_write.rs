@@ -0,0 +1,201 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::system_state_observer::SystemStateObserver;
+use crate::workloads::payload::Payload;
+use crate::workloads::system_state_read::make_read_transaction;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use rand::Rng;
+use std::sync::Arc;
+use sui_types::crypto::get_key_pair;
+use sui_types::messages::{TransactionData, VerifiedTransaction};
+use sui_types::utils::to_sender_signed_transaction;
+use tracing::warn;
+
+#[derive(Debug)]
+pub struct ReadWriteTestPayload {
+    gas: Gas,
+    /// Probability for every transaction to be a read rather than a write
+    read_ratio: f32,
+    /// Whether the last transaction made is a read, which is dry run
+    is_read: bool,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for ReadWriteTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "read_write")
+    }
+}
+
+impl Payload for ReadWriteTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        if let Some(error) = effects.failure_status() {
+            warn!(
+                "{} of read/write workload failed: {}",
+                if self.is_read { "Read" } else { "Write" },
+                error
+            );
+        }
+        // Reads are never committed, the gas coin is unchanged
+        if !self.is_read {
+            self.gas.0 = effects.gas_object().0;
+        }
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        self.is_read = rand::thread_rng().gen::<f32>() < self.read_ratio;
+        if self.is_read {
+            return make_read_transaction(&self.gas, &self.system_state_observer);
+        }
+        let (gas_budget, gas_price) = {
+            let state = self.system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        let data = TransactionData::new_transfer_sui(
+            self.gas.1, self.gas.1, None, self.gas.0, gas_budget, gas_price,
+        );
+        to_sender_signed_transaction(data, &self.gas.2)
+    }
+
+    fn is_dry_run(&self) -> bool {
+        self.is_read
+    }
+
+    fn stats_target(&self) -> Option<String> {
+        // Breaks down the latency of the workload into that of reads and that of writes
+        Some(if self.is_read { "read" } else { "write" }.to_string())
+    }
+}
+
+#[derive(Debug)]
+pub struct ReadWriteWorkloadBuilder {
+    num_payloads: u64,
+    read_ratio: f32,
+}
+
+impl ReadWriteWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        read_ratio: f32,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder =
+                Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(ReadWriteWorkloadBuilder {
+                    num_payloads: max_ops,
+                    read_ratio: read_ratio.clamp(0.0, 1.0),
+                }));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for ReadWriteWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        vec![]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        (0..self.num_payloads)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        _init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(ReadWriteWorkload {
+            read_ratio: self.read_ratio,
+            payload_gas,
+        }))
+    }
+}
+
+/// Mixes reads and writes the way clients do: every transaction of a payload is, with a
+/// configurable probability, a dry run reading the system state instead of a transfer of the
+/// payload's coin to itself. Reads are never committed and leave the gas coin as it is. Running
+/// it with different read ratios shows how read load affects the latency of writes, which is
+/// reported separately from that of reads.
+#[derive(Debug)]
+pub struct ReadWriteWorkload {
+    read_ratio: f32,
+    payload_gas: Vec<Gas>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for ReadWriteWorkload {
+    async fn init(
+        &mut self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        if self.read_ratio == 0.0 {
+            return Ok(());
+        }
+        // Fail early rather than have every read retry forever
+        let gas = self
+            .payload_gas
+            .first()
+            .expect("Read/write workload has no payloads");
+        proxy
+            .dry_run_transaction_block(make_read_transaction(gas, &system_state_observer).into())
+            .await
+            .expect("Read/write workload needs a proxy which can dry run transactions");
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        self.payload_gas
+            .iter()
+            .map(|gas| {
+                Box::new(ReadWriteTestPayload {
+                    gas: gas.clone(),
+                    read_ratio: self.read_ratio,
+                    is_read: false,
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+}
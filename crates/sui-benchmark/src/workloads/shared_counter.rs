@@ -9,11 +9,14 @@ use crate::workloads::payload::Payload;
 use crate::workloads::workload::{WorkloadBuilder, MAX_GAS_FOR_TESTING};
 use crate::workloads::GasCoinConfig;
 use crate::{ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
 use async_trait::async_trait;
 use futures::future::join_all;
+use futures::stream::{self, BoxStream, StreamExt};
 use rand::seq::SliceRandom;
 use std::sync::Arc;
-use sui_types::crypto::get_key_pair;
+use sui_types::base_types::SuiAddress;
+use sui_types::crypto::{get_key_pair, AccountKeyPair};
 use sui_types::{
     base_types::{ObjectDigest, ObjectID, SequenceNumber},
     messages::VerifiedTransaction,
@@ -64,6 +67,32 @@ impl Payload for SharedCounterTestPayload {
 pub struct SharedCounterWorkloadBuilder {
     num_counters: u64,
     num_payloads: u64,
+    /// If set, payloads are owned by this many addresses in turn instead of one address each.
+    /// Every payload still has its own gas coin, so payloads sharing an address never conflict
+    /// on objects, but they do contend on whatever validators and the benchmark do per sender.
+    /// It also saves generating a keypair for every payload.
+    distinct_addresses: Option<u64>,
+}
+
+impl SharedCounterWorkloadBuilder {
+    /// Owners of the payload gas coins, generated up front if they are shared between payloads
+    fn payload_owners(&self) -> impl Iterator<Item = (SuiAddress, Arc<AccountKeyPair>)> {
+        let pool: Option<Vec<_>> = self.distinct_addresses.map(|n| {
+            (0..n)
+                .map(|_| {
+                    let (address, keypair) = get_key_pair();
+                    (address, Arc::new(keypair))
+                })
+                .collect()
+        });
+        (0..self.num_payloads as usize).map(move |i| match &pool {
+            Some(pool) => pool[i % pool.len()].clone(),
+            None => {
+                let (address, keypair) = get_key_pair();
+                (address, Arc::new(keypair))
+            }
+        })
+    }
 }
 
 impl SharedCounterWorkloadBuilder {
@@ -73,6 +102,7 @@ impl SharedCounterWorkloadBuilder {
         num_workers: u64,
         in_flight_ratio: u64,
         shared_counter_hotness_factor: u32,
+        distinct_addresses: Option<u64>,
     ) -> Option<WorkloadBuilderInfo> {
         let target_qps = (workload_weight * target_qps as f32) as u64;
         let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
@@ -92,6 +122,7 @@ impl SharedCounterWorkloadBuilder {
                 SharedCounterWorkloadBuilder {
                     num_counters: num_shared_counters,
                     num_payloads: max_ops,
+                    distinct_addresses: distinct_addresses.filter(|n| *n > 0),
                 },
             ));
             let builder_info = WorkloadBuilderInfo {
@@ -128,17 +159,23 @@ impl WorkloadBuilder<dyn Payload> for SharedCounterWorkloadBuilder {
         configs
     }
     async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
-        let mut configs = vec![];
         // Gas coins for running workload
-        for _i in 0..self.num_payloads {
-            let (address, keypair) = get_key_pair();
-            configs.push(GasCoinConfig {
+        self.payload_owners()
+            .map(|(address, keypair)| GasCoinConfig {
                 amount: MAX_GAS_FOR_TESTING,
                 address,
-                keypair: Arc::new(keypair),
-            });
-        }
-        configs
+                keypair,
+            })
+            .collect()
+    }
+    async fn stream_coin_config_for_payloads(&self) -> BoxStream<'_, GasCoinConfig> {
+        stream::iter(self.payload_owners())
+            .map(|(address, keypair)| GasCoinConfig {
+                amount: MAX_GAS_FOR_TESTING,
+                address,
+                keypair,
+            })
+            .boxed()
     }
     async fn build(
         &self,
@@ -168,9 +205,9 @@ impl Workload<dyn Payload> for SharedCounterWorkload {
         &mut self,
         proxy: Arc<dyn ValidatorProxy + Sync + Send>,
         system_state_observer: Arc<SystemStateObserver>,
-    ) {
+    ) -> Result<()> {
         if self.basics_package_id.is_some() {
-            return;
+            return Ok(());
         }
         let gas_price = system_state_observer.state.borrow().reference_gas_price;
         let (head, tail) = self
@@ -187,7 +224,7 @@ impl Workload<dyn Payload> for SharedCounterWorkload {
         );
         if !self.counters.is_empty() {
             // We already initialized the workload with some counters
-            return;
+            return Ok(());
         }
         let mut futures = vec![];
         for (gas, sender, keypair) in tail.iter() {
@@ -211,6 +248,7 @@ impl Workload<dyn Payload> for SharedCounterWorkload {
             });
         }
         self.counters = join_all(futures).await;
+        Ok(())
     }
     async fn make_test_payloads(
         &self,
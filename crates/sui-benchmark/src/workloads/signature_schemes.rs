@@ -0,0 +1,305 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::system_state_observer::SystemStateObserver;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{ExecutionEffects, ValidatorProxy};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use sui_types::base_types::{ObjectRef, SuiAddress};
+use sui_types::crypto::{get_key_pair, AccountKeyPair, SignatureScheme, SuiKeyPair};
+use sui_types::messages::{TransactionData, VerifiedTransaction};
+use sui_types::utils::to_sender_signed_transaction;
+use tracing::info;
+
+/// Signature schemes which Sui addresses can be derived from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyScheme {
+    Ed25519,
+    Secp256k1,
+    Secp256r1,
+}
+
+impl KeyScheme {
+    fn generate(&self) -> SuiKeyPair {
+        match self {
+            KeyScheme::Ed25519 => SuiKeyPair::Ed25519(get_key_pair().1),
+            KeyScheme::Secp256k1 => SuiKeyPair::Secp256k1(get_key_pair().1),
+            KeyScheme::Secp256r1 => SuiKeyPair::Secp256r1(get_key_pair().1),
+        }
+    }
+}
+
+impl std::fmt::Display for KeyScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            KeyScheme::Ed25519 => write!(f, "ed25519"),
+            KeyScheme::Secp256k1 => write!(f, "secp256k1"),
+            KeyScheme::Secp256r1 => write!(f, "secp256r1"),
+        }
+    }
+}
+
+/// Relative weights of the signature schemes of the payload owners of the signature schemes
+/// workload, parsed from a comma separated list of `{scheme}:{weight}` pairs, e.g.
+/// "ed25519:2,secp256k1:1,secp256r1:1". Schemes missing from the list get no owners.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemeMix {
+    weights: Vec<(KeyScheme, u32)>,
+}
+
+impl SchemeMix {
+    /// Scheme of every one of `count` owners, cycling through the schemes in proportion to their
+    /// weights, so that every scheme gets its share however few owners there are
+    fn assign(&self, count: u64) -> Vec<KeyScheme> {
+        self.weights
+            .iter()
+            .flat_map(|(scheme, weight)| std::iter::repeat(*scheme).take(*weight as usize))
+            .cycle()
+            .take(count as usize)
+            .collect()
+    }
+}
+
+impl Default for SchemeMix {
+    fn default() -> Self {
+        SchemeMix {
+            weights: vec![
+                (KeyScheme::Ed25519, 1),
+                (KeyScheme::Secp256k1, 1),
+                (KeyScheme::Secp256r1, 1),
+            ],
+        }
+    }
+}
+
+impl FromStr for SchemeMix {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let weights = s
+            .split(',')
+            .map(|entry| {
+                let (scheme, weight) = entry
+                    .split_once(':')
+                    .ok_or_else(|| anyhow!("invalid signature scheme entry {:?}", entry))?;
+                let scheme = match SignatureScheme::from_str(&scheme.trim().to_lowercase()) {
+                    Ok(SignatureScheme::ED25519) => KeyScheme::Ed25519,
+                    Ok(SignatureScheme::Secp256k1) => KeyScheme::Secp256k1,
+                    Ok(SignatureScheme::Secp256r1) => KeyScheme::Secp256r1,
+                    _ => {
+                        return Err(anyhow!(
+                            "signature scheme must be one of ed25519, secp256k1 or secp256r1, \
+                            got {:?}",
+                            scheme
+                        ))
+                    }
+                };
+                Ok((scheme, weight.trim().parse::<u32>()?))
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+        if !weights.iter().map(|(scheme, _)| scheme).all_unique() {
+            return Err(anyhow!("every signature scheme may only appear once"));
+        }
+        if weights.iter().all(|(_, weight)| *weight == 0) {
+            return Err(anyhow!(
+                "at least one signature scheme needs a positive weight"
+            ));
+        }
+        Ok(SchemeMix { weights })
+    }
+}
+
+#[derive(Debug)]
+pub struct SignatureSchemesTestPayload {
+    /// Coin transferred by every transaction to its own owner, paying for its own transfer
+    coin: ObjectRef,
+    owner: SuiAddress,
+    keypair: Arc<SuiKeyPair>,
+    scheme: KeyScheme,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for SignatureSchemesTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "signature_schemes")
+    }
+}
+
+impl Payload for SignatureSchemesTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        self.coin = effects.gas_object().0;
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let (gas_budget, gas_price) = {
+            let state = self.system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        let data = TransactionData::new_transfer_sui(
+            self.owner, self.owner, None, self.coin, gas_budget, gas_price,
+        );
+        to_sender_signed_transaction(data, self.keypair.as_ref())
+    }
+
+    fn stats_target(&self) -> Option<String> {
+        Some(self.scheme.to_string())
+    }
+}
+
+#[derive(Debug)]
+pub struct SignatureSchemesWorkloadBuilder {
+    /// Keypair of every payload owner, of the scheme assigned to it, generated upfront so that
+    /// the coins are created for addresses the payloads can sign for
+    payload_owners: Vec<(KeyScheme, Arc<SuiKeyPair>)>,
+}
+
+impl SignatureSchemesWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        mix: SchemeMix,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let payload_owners = mix
+                .assign(max_ops)
+                .into_iter()
+                .map(|scheme| (scheme, Arc::new(scheme.generate())))
+                .collect();
+            let workload_builder = Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(
+                SignatureSchemesWorkloadBuilder { payload_owners },
+            ));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for SignatureSchemesWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        vec![]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        self.payload_owners
+            .iter()
+            .map(|(_, keypair)| {
+                // Coin configs only hold Ed25519 keypairs, which nothing signs with here: the
+                // payloads sign with the keypairs of their owners instead
+                let (_, unused_keypair): (_, AccountKeyPair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address: SuiAddress::from(&keypair.public()),
+                    keypair: Arc::new(unused_keypair),
+                }
+            })
+            .collect()
+    }
+    fn required_addresses(&self) -> Vec<SuiAddress> {
+        self.payload_owners
+            .iter()
+            .map(|(_, keypair)| SuiAddress::from(&keypair.public()))
+            .collect()
+    }
+    async fn build(
+        &self,
+        _init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(SignatureSchemesWorkload {
+            owners: self
+                .payload_owners
+                .iter()
+                .map(|(scheme, keypair)| {
+                    (
+                        SuiAddress::from(&keypair.public()),
+                        (*scheme, keypair.clone()),
+                    )
+                })
+                .collect(),
+            payload_gas,
+        }))
+    }
+}
+
+/// Transfers coins signed with Ed25519, Secp256k1 and Secp256r1 keys in configurable
+/// proportions, to compare what verifying each of the signature schemes costs. Every payload
+/// owner has a key of one scheme and transfers its coin to itself, so that transactions only
+/// differ by their signatures. The stats are broken down by scheme.
+#[derive(Debug)]
+pub struct SignatureSchemesWorkload {
+    /// Scheme and keypair of the owner of every address of `payload_gas`
+    owners: HashMap<SuiAddress, (KeyScheme, Arc<SuiKeyPair>)>,
+    payload_gas: Vec<Gas>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for SignatureSchemesWorkload {
+    async fn init(
+        &mut self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        _system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        let counts = self.owners.values().map(|(scheme, _)| scheme).counts();
+        info!(
+            "Signature schemes workload owners (scheme: payloads): {}",
+            counts
+                .iter()
+                .map(|(scheme, count)| format!("{}: {}", scheme, count))
+                .join(", ")
+        );
+        self.payload_gas
+            .iter()
+            .map(|(coin, owner, _)| {
+                let (scheme, keypair) = self
+                    .owners
+                    .get(owner)
+                    .expect("Payload coin should belong to a payload owner");
+                Box::new(SignatureSchemesTestPayload {
+                    coin: *coin,
+                    owner: *owner,
+                    keypair: keypair.clone(),
+                    scheme: *scheme,
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+}
@@ -0,0 +1,329 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::system_state_observer::SystemStateObserver;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use rand::Rng;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use sui_types::crypto::get_key_pair;
+use sui_types::messages::{TransactionData, VerifiedTransaction};
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::utils::to_sender_signed_transaction;
+use tracing::{error, warn};
+
+/// Number of times the padding is corrected to get the transaction to its target size
+const MAX_PADDING_ITERATIONS: usize = 3;
+
+/// Whether `error` is the validators rejecting a transaction for being too large
+fn is_size_limit_error(error: &anyhow::Error) -> bool {
+    let error = format!("{:?}", error);
+    error.contains("SizeLimitExceeded") || error.contains("Size limit exceeded")
+}
+
+/// Outcomes of the transactions of all payloads of the workload
+#[derive(Debug, Default)]
+struct SizeLimitStats {
+    oversized_submitted: AtomicU64,
+    oversized_rejected: AtomicU64,
+    /// Oversized transactions which failed, but not because of their size
+    oversized_other_error: AtomicU64,
+    /// Oversized transactions which were executed, i.e. the limit was not enforced
+    oversized_accepted: AtomicU64,
+    /// Transactions under the limit which were rejected for their size
+    undersized_rejected: AtomicU64,
+}
+
+impl SizeLimitStats {
+    fn counters(&self) -> Vec<(String, u64)> {
+        vec![
+            (
+                "oversized submitted".into(),
+                self.oversized_submitted.load(Ordering::Relaxed),
+            ),
+            (
+                "oversized rejected".into(),
+                self.oversized_rejected.load(Ordering::Relaxed),
+            ),
+            (
+                "oversized other error".into(),
+                self.oversized_other_error.load(Ordering::Relaxed),
+            ),
+            (
+                "oversized accepted".into(),
+                self.oversized_accepted.load(Ordering::Relaxed),
+            ),
+            (
+                "undersized rejected".into(),
+                self.undersized_rejected.load(Ordering::Relaxed),
+            ),
+        ]
+    }
+}
+
+#[derive(Debug)]
+pub struct SizeLimitTestPayload {
+    gas: Gas,
+    /// Distance in bytes of every transaction from the size limit
+    offset_bytes: u64,
+    /// Fraction (0.0 to 1.0) of transactions which are over the size limit
+    oversized_ratio: f32,
+    /// Whether the last transaction was made larger than the size limit
+    last_was_oversized: bool,
+    stats: Arc<SizeLimitStats>,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for SizeLimitTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "size_limit")
+    }
+}
+
+impl SizeLimitTestPayload {
+    /// Transfers the gas coin back to the sender, padded with `padding_bytes` of unused pure
+    /// inputs each shorter than `max_pure_argument_size`
+    fn make_padded_transaction(
+        &self,
+        padding_bytes: u64,
+        max_pure_argument_size: u32,
+        gas_budget: u64,
+        gas_price: u64,
+    ) -> VerifiedTransaction {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let max_chunk = max_pure_argument_size as u64 - 1;
+        let mut remaining = padding_bytes;
+        while remaining > 0 {
+            let chunk = std::cmp::min(remaining, max_chunk);
+            builder.pure_bytes(vec![0; chunk as usize], true);
+            remaining -= chunk;
+        }
+        builder.transfer_sui(self.gas.1, None);
+        let data = TransactionData::new_programmable(
+            self.gas.1,
+            vec![self.gas.0],
+            builder.finish(),
+            gas_budget,
+            gas_price,
+        );
+        to_sender_signed_transaction(data, &self.gas.2)
+    }
+}
+
+impl Payload for SizeLimitTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        if self.last_was_oversized {
+            self.stats
+                .oversized_accepted
+                .fetch_add(1, Ordering::Relaxed);
+            error!("Transaction exceeding the size limit was executed");
+        }
+        self.gas.0 = effects.gas_object().0;
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let (gas_budget, gas_price, max_tx_size_bytes, max_pure_argument_size) = {
+            let state = self.system_state_observer.state.borrow();
+            let protocol_config = state
+                .protocol_config
+                .as_ref()
+                .expect("Protocol config not in system state");
+            (
+                protocol_config.max_tx_gas(),
+                state.reference_gas_price,
+                protocol_config.max_tx_size_bytes(),
+                protocol_config.max_pure_argument_size(),
+            )
+        };
+        self.last_was_oversized = rand::thread_rng().gen::<f32>() < self.oversized_ratio;
+        let target_size = if self.last_was_oversized {
+            self.stats
+                .oversized_submitted
+                .fetch_add(1, Ordering::Relaxed);
+            max_tx_size_bytes.saturating_add(std::cmp::max(self.offset_bytes, 1))
+        } else {
+            max_tx_size_bytes.saturating_sub(self.offset_bytes)
+        };
+        // Length prefixes make the serialized size only roughly linear in the padding, so start
+        // from no padding and correct by the measured difference a few times
+        let mut padding_bytes = 0;
+        let mut tx = self.make_padded_transaction(
+            padding_bytes,
+            max_pure_argument_size,
+            gas_budget,
+            gas_price,
+        );
+        for _ in 0..MAX_PADDING_ITERATIONS {
+            let size = bcs::serialized_size(tx.inner()).unwrap() as u64;
+            if size == target_size {
+                break;
+            }
+            padding_bytes = (padding_bytes + target_size).saturating_sub(size);
+            tx = self.make_padded_transaction(
+                padding_bytes,
+                max_pure_argument_size,
+                gas_budget,
+                gas_price,
+            );
+        }
+        tx
+    }
+
+    fn handle_error(&mut self, error: &anyhow::Error) -> bool {
+        match (self.last_was_oversized, is_size_limit_error(error)) {
+            (true, true) => {
+                self.stats
+                    .oversized_rejected
+                    .fetch_add(1, Ordering::Relaxed);
+                false
+            }
+            (true, false) => {
+                self.stats
+                    .oversized_other_error
+                    .fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    "Transaction exceeding the size limit failed for another reason: {}",
+                    error
+                );
+                false
+            }
+            (false, true) => {
+                self.stats
+                    .undersized_rejected
+                    .fetch_add(1, Ordering::Relaxed);
+                error!(
+                    "Transaction under the size limit was rejected for its size: {}",
+                    error
+                );
+                false
+            }
+            // Unrelated to the size of the transaction, retry as usual
+            (false, false) => true,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SizeLimitWorkloadBuilder {
+    num_payloads: u64,
+    offset_bytes: u64,
+    oversized_ratio: f32,
+}
+
+impl SizeLimitWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        offset_bytes: u64,
+        oversized_ratio: f32,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder =
+                Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(SizeLimitWorkloadBuilder {
+                    num_payloads: max_ops,
+                    offset_bytes,
+                    oversized_ratio: oversized_ratio.clamp(0.0, 1.0),
+                }));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for SizeLimitWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        vec![]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        (0..self.num_payloads)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        _init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(SizeLimitWorkload {
+            offset_bytes: self.offset_bytes,
+            oversized_ratio: self.oversized_ratio,
+            payload_gas,
+            stats: Arc::default(),
+        }))
+    }
+}
+
+/// Submits transactions padded to just under or just over the maximum transaction size.
+/// Oversized transactions are expected to be rejected by validators and are not retried; their
+/// gas coin is never locked, so the payload simply moves on to its next transaction.
+#[derive(Debug)]
+pub struct SizeLimitWorkload {
+    offset_bytes: u64,
+    oversized_ratio: f32,
+    payload_gas: Vec<Gas>,
+    stats: Arc<SizeLimitStats>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for SizeLimitWorkload {
+    async fn init(
+        &mut self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        _system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        self.payload_gas
+            .iter()
+            .map(|gas| {
+                Box::new(SizeLimitTestPayload {
+                    gas: gas.clone(),
+                    offset_bytes: self.offset_bytes,
+                    oversized_ratio: self.oversized_ratio,
+                    last_was_oversized: false,
+                    stats: self.stats.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        self.stats.counters()
+    }
+}
@@ -0,0 +1,331 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::system_state_observer::SystemStateObserver;
+use crate::util::get_validators_with_retry;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use rand::seq::SliceRandom;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use sui_types::base_types::{ObjectRef, SuiAddress};
+use sui_types::crypto::{get_key_pair, AccountKeyPair};
+use sui_types::governance::{ADD_STAKE_FUN_NAME, WITHDRAW_STAKE_FUN_NAME};
+use sui_types::messages::{Argument, Command, ObjectArg, TransactionData, VerifiedTransaction};
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::sui_system_state::SUI_SYSTEM_MODULE_NAME;
+use sui_types::utils::to_sender_signed_transaction;
+use sui_types::{
+    SUI_SYSTEM_PACKAGE_ID, SUI_SYSTEM_STATE_OBJECT_ID, SUI_SYSTEM_STATE_OBJECT_SHARED_VERSION,
+};
+use tracing::warn;
+
+/// Amount staked by every transaction, the smallest stake the system accepts
+const STAKE_AMOUNT: u64 = 1_000_000_000;
+
+/// Outcomes of the transactions of all payloads of the workload, split by whether they only
+/// staked or also withdrew, since only the latter mix the two system calls
+#[derive(Debug, Default)]
+struct StakeWithdrawStats {
+    stakes: AtomicU64,
+    combined: AtomicU64,
+    failed_stakes: AtomicU64,
+    failed_combined: AtomicU64,
+}
+
+impl StakeWithdrawStats {
+    fn record_failure(&self, combined: bool, error: &str) {
+        if combined {
+            let failed = self.failed_combined.fetch_add(1, Ordering::Relaxed) + 1;
+            warn!(
+                "Staking and withdrawing in a single programmable transaction failed ({} so far, \
+                {} succeeded): {}",
+                failed,
+                self.combined.load(Ordering::Relaxed),
+                error
+            );
+        } else {
+            self.failed_stakes.fetch_add(1, Ordering::Relaxed);
+            warn!("Staking the first coin of a payload failed: {}", error);
+        }
+    }
+
+    fn record_success(&self, combined: bool) {
+        if !combined {
+            self.stakes.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        self.combined.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        vec![
+            ("stakes".into(), self.stakes.load(Ordering::Relaxed)),
+            ("combined".into(), self.combined.load(Ordering::Relaxed)),
+            (
+                "failed stakes".into(),
+                self.failed_stakes.load(Ordering::Relaxed),
+            ),
+            (
+                "failed combined".into(),
+                self.failed_combined.load(Ordering::Relaxed),
+            ),
+        ]
+    }
+}
+
+#[derive(Debug)]
+pub struct StakeWithdrawTestPayload {
+    /// Stake requested by the previous transaction, withdrawn by the next one. Only the first
+    /// transaction of the payload has nothing to withdraw.
+    staked_sui: Option<ObjectRef>,
+    /// Coin paid out by the previous withdrawal, merged back into the gas coin by the next
+    /// transaction so that the payload does not run out of SUI to stake
+    withdrawn_coin: Option<ObjectRef>,
+    gas: ObjectRef,
+    validator: SuiAddress,
+    sender: SuiAddress,
+    keypair: Arc<AccountKeyPair>,
+    stats: Arc<StakeWithdrawStats>,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl StakeWithdrawTestPayload {
+    fn system_state_arg(builder: &mut ProgrammableTransactionBuilder) -> Argument {
+        builder
+            .obj(ObjectArg::SharedObject {
+                id: SUI_SYSTEM_STATE_OBJECT_ID,
+                initial_shared_version: SUI_SYSTEM_STATE_OBJECT_SHARED_VERSION,
+                mutable: true,
+            })
+            .unwrap()
+    }
+}
+
+impl std::fmt::Display for StakeWithdrawTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "stake_withdraw")
+    }
+}
+
+impl Payload for StakeWithdrawTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        let combined = self.staked_sui.is_some();
+        self.gas = effects.gas_object().0;
+        if let Some(error) = effects.failure_status() {
+            // Nothing was staked or withdrawn, the objects of the payload are only at new
+            // versions, and the same transaction is made again
+            self.stats.record_failure(combined, &error);
+            let mutated = effects.mutated();
+            let latest = |object_ref: ObjectRef| {
+                mutated
+                    .iter()
+                    .map(|(obj_ref, _)| *obj_ref)
+                    .find(|obj_ref| obj_ref.0 == object_ref.0)
+                    .unwrap_or(object_ref)
+            };
+            self.staked_sui = self.staked_sui.map(latest);
+            self.withdrawn_coin = self.withdrawn_coin.map(latest);
+            return;
+        }
+        self.stats.record_success(combined);
+        // Staking and withdrawing both create an object for the sender. The stake comes first
+        // in the transaction, so its `StakedSui` is created before the withdrawn coin, and the
+        // previous withdrawn coin, if any, was merged into the gas coin.
+        self.staked_sui = effects.created_staked_sui(self.sender);
+        self.withdrawn_coin = effects
+            .created_owned_by(self.sender)
+            .into_iter()
+            .find(|obj_ref| Some(*obj_ref) != self.staked_sui);
+    }
+
+    /// Every transaction stakes a coin split off the gas coin, and once there is a stake from
+    /// the previous transaction, withdraws it in the same programmable transaction block
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let (gas_budget, gas_price) = {
+            let state = self.system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        let mut builder = ProgrammableTransactionBuilder::new();
+        if let Some(coin) = self.withdrawn_coin {
+            let coin = builder.obj(ObjectArg::ImmOrOwnedObject(coin)).unwrap();
+            builder.command(Command::MergeCoins(Argument::GasCoin, vec![coin]));
+        }
+        let amount = builder.pure(STAKE_AMOUNT).unwrap();
+        let stake = builder.command(Command::SplitCoins(Argument::GasCoin, vec![amount]));
+        let system_state = Self::system_state_arg(&mut builder);
+        let validator = builder.pure(self.validator).unwrap();
+        builder.programmable_move_call(
+            SUI_SYSTEM_PACKAGE_ID,
+            SUI_SYSTEM_MODULE_NAME.to_owned(),
+            ADD_STAKE_FUN_NAME.to_owned(),
+            vec![],
+            vec![system_state, stake, validator],
+        );
+        if let Some(staked_sui) = self.staked_sui {
+            let staked_sui = builder
+                .obj(ObjectArg::ImmOrOwnedObject(staked_sui))
+                .unwrap();
+            builder.programmable_move_call(
+                SUI_SYSTEM_PACKAGE_ID,
+                SUI_SYSTEM_MODULE_NAME.to_owned(),
+                WITHDRAW_STAKE_FUN_NAME.to_owned(),
+                vec![],
+                vec![system_state, staked_sui],
+            );
+        }
+        let data = TransactionData::new_programmable(
+            self.sender,
+            vec![self.gas],
+            builder.finish(),
+            gas_budget,
+            gas_price,
+        );
+        to_sender_signed_transaction(data, self.keypair.as_ref())
+    }
+
+    fn handle_error(&mut self, error: &anyhow::Error) -> bool {
+        // Rejected before execution, e.g. if validators refuse transactions mixing these calls
+        self.stats
+            .record_failure(self.staked_sui.is_some(), &error.to_string());
+        true
+    }
+
+    fn stats_target(&self) -> Option<String> {
+        Some(self.validator.to_string())
+    }
+}
+
+#[derive(Debug)]
+pub struct StakeWithdrawWorkloadBuilder {
+    num_payloads: u64,
+}
+
+impl StakeWithdrawWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder = Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(
+                StakeWithdrawWorkloadBuilder {
+                    num_payloads: max_ops,
+                },
+            ));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for StakeWithdrawWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        vec![]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        (0..self.num_payloads)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    // At most one stake at a time is missing from the gas coin, the previous one
+                    // is paid back by its withdrawal
+                    amount: MAX_GAS_FOR_TESTING + STAKE_AMOUNT,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        _init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(StakeWithdrawWorkload {
+            payload_gas,
+            validators: vec![],
+            stats: Arc::default(),
+        }))
+    }
+}
+
+/// Stakes and withdraws in a single programmable transaction block, to benchmark the two system
+/// calls together and check that a transaction may mix them. Unlike the delegation workload,
+/// which prepares a coin and stakes it in separate transactions, every transaction of a payload
+/// stakes a fresh coin and withdraws the stake requested by its previous transaction at once.
+#[derive(Debug)]
+pub struct StakeWithdrawWorkload {
+    payload_gas: Vec<Gas>,
+    /// Committee read in `init` which payloads pick their validators from
+    validators: Vec<SuiAddress>,
+    stats: Arc<StakeWithdrawStats>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for StakeWithdrawWorkload {
+    async fn init(
+        &mut self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        _system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        if !self.validators.is_empty() {
+            return Ok(());
+        }
+        self.validators = get_validators_with_retry(&proxy).await?;
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        self.payload_gas
+            .iter()
+            .map(|(gas, sender, keypair)| {
+                Box::new(StakeWithdrawTestPayload {
+                    staked_sui: None,
+                    withdrawn_coin: None,
+                    gas: *gas,
+                    validator: *self.validators.choose(&mut rand::thread_rng()).unwrap(),
+                    sender: *sender,
+                    keypair: keypair.clone(),
+                    stats: self.stats.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        self.stats.counters()
+    }
+}
@@ -0,0 +1,205 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::in_memory_wallet::move_call_pt_impl;
+use crate::system_state_observer::SystemStateObserver;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{BenchMoveCallArg, ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use sui_types::crypto::get_key_pair;
+use sui_types::messages::VerifiedTransaction;
+use sui_types::{
+    SUI_SYSTEM_PACKAGE_ID, SUI_SYSTEM_STATE_OBJECT_ID, SUI_SYSTEM_STATE_OBJECT_SHARED_VERSION,
+};
+use tracing::info;
+
+/// Dry runs a read of the current epoch from the system state object
+pub fn make_read_transaction(
+    gas: &Gas,
+    system_state_observer: &SystemStateObserver,
+) -> VerifiedTransaction {
+    let (gas_budget, gas_price) = {
+        let state = system_state_observer.state.borrow();
+        (
+            state
+                .protocol_config
+                .as_ref()
+                .expect("Protocol config not in system state")
+                .max_tx_gas(),
+            state.reference_gas_price,
+        )
+    };
+    move_call_pt_impl(
+        gas.1,
+        &gas.2,
+        SUI_SYSTEM_PACKAGE_ID,
+        "sui_system",
+        "epoch",
+        vec![],
+        vec![BenchMoveCallArg::Shared((
+            SUI_SYSTEM_STATE_OBJECT_ID,
+            SUI_SYSTEM_STATE_OBJECT_SHARED_VERSION,
+            true,
+        ))],
+        &gas.0,
+        gas_budget,
+        gas_price,
+    )
+}
+
+#[derive(Debug)]
+pub struct SystemStateReadTestPayload {
+    gas: Gas,
+    /// Latest epoch in which reads ran before the system state observer caught up with it,
+    /// shared by all payloads so that every lag is only reported once
+    last_lagging_epoch: Arc<AtomicU64>,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for SystemStateReadTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "system_state_read")
+    }
+}
+
+impl Payload for SystemStateReadTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        // Nothing was committed, the gas coin is unchanged
+        let executed_epoch = effects.executed_epoch();
+        let observed_epoch = self.system_state_observer.state.borrow().epoch;
+        if executed_epoch > observed_epoch
+            && self
+                .last_lagging_epoch
+                .fetch_max(executed_epoch, Ordering::Relaxed)
+                < executed_epoch
+        {
+            info!(
+                "System state observer is at epoch {} while reads run in epoch {}",
+                observed_epoch, executed_epoch
+            );
+        }
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        make_read_transaction(&self.gas, &self.system_state_observer)
+    }
+
+    fn is_dry_run(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug)]
+pub struct SystemStateReadWorkloadBuilder {
+    num_payloads: u64,
+}
+
+impl SystemStateReadWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder = Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(
+                SystemStateReadWorkloadBuilder {
+                    num_payloads: max_ops,
+                },
+            ));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for SystemStateReadWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        vec![]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        // Dry runs still need a gas coin to pass input checks, but never spend it
+        (0..self.num_payloads)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        _init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(SystemStateReadWorkload { payload_gas }))
+    }
+}
+
+/// Reads the system state through dry runs which are never committed, to measure the read path
+/// separately from consensus and execution of transactions that write.
+#[derive(Debug)]
+pub struct SystemStateReadWorkload {
+    payload_gas: Vec<Gas>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for SystemStateReadWorkload {
+    async fn init(
+        &mut self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        // Fail early rather than have every payload retry forever
+        let gas = self
+            .payload_gas
+            .first()
+            .expect("System state read workload has no payloads");
+        proxy
+            .dry_run_transaction_block(make_read_transaction(gas, &system_state_observer).into())
+            .await
+            .expect("System state read workload needs a proxy which can dry run transactions");
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        let last_lagging_epoch = Arc::new(AtomicU64::new(0));
+        self.payload_gas
+            .iter()
+            .map(|gas| {
+                Box::new(SystemStateReadTestPayload {
+                    gas: gas.clone(),
+                    last_lagging_epoch: last_lagging_epoch.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+}
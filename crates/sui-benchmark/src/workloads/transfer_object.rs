@@ -1,6 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use anyhow::Result;
 use async_trait::async_trait;
 use rand::seq::IteratorRandom;
 
@@ -182,8 +183,8 @@ impl Workload<dyn Payload> for TransferObjectWorkload {
         &mut self,
         _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
         _system_state_observer: Arc<SystemStateObserver>,
-    ) {
-        return;
+    ) -> Result<()> {
+        Ok(())
     }
     async fn make_test_payloads(
         &self,
@@ -0,0 +1,310 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::in_memory_wallet::move_call_pt_impl;
+use crate::system_state_observer::SystemStateObserver;
+use crate::util::publish_package;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, TypeTag};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use sui_types::base_types::ObjectID;
+use sui_types::crypto::get_key_pair;
+use sui_types::messages::VerifiedTransaction;
+use test_utils::transaction::parse_package_ref;
+use tracing::{info, warn};
+
+const MODULE_NAME: &str = "type_args";
+/// Largest number of type arguments the package has a function for
+pub const MAX_TYPE_ARGS: u64 = 16;
+/// Types innermost in the type arguments, rotated between calls
+const PRIMITIVES: [TypeTag; 8] = [
+    TypeTag::Bool,
+    TypeTag::U8,
+    TypeTag::U16,
+    TypeTag::U32,
+    TypeTag::U64,
+    TypeTag::U128,
+    TypeTag::U256,
+    TypeTag::Address,
+];
+
+/// Type arguments of the calls of the type args workload: `count` arguments, each of them
+/// nesting the `Wrapper` and `Pair` structs of the package `depth` levels deep around a
+/// primitive type, e.g. `Pair<Wrapper<u64>, u64>` for a depth of 2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypeArgsTemplate {
+    pub count: u64,
+    pub depth: u64,
+}
+
+impl TypeArgsTemplate {
+    /// Generic function of the package taking `count` type arguments
+    fn function(&self) -> String {
+        format!("call_{}", self.count)
+    }
+
+    /// Type arguments of the `iteration`th call. The primitive types differ between calls, so
+    /// that consecutive calls do not instantiate the same types.
+    fn fill(&self, package_id: ObjectID, iteration: u64) -> Vec<TypeTag> {
+        (0..self.count)
+            .map(|i| {
+                let primitive =
+                    PRIMITIVES[((iteration + i) % PRIMITIVES.len() as u64) as usize].clone();
+                (0..self.depth).fold(primitive.clone(), |inner, level| {
+                    let (name, type_params) = if level % 2 == 0 {
+                        ("Wrapper", vec![inner])
+                    } else {
+                        ("Pair", vec![inner, primitive.clone()])
+                    };
+                    TypeTag::Struct(Box::new(StructTag {
+                        address: package_id.into(),
+                        module: Identifier::new(MODULE_NAME).unwrap(),
+                        name: Identifier::new(name).unwrap(),
+                        type_params,
+                    }))
+                })
+            })
+            .collect()
+    }
+}
+
+/// Gas paid by the calls of all payloads of the workload
+#[derive(Debug, Default)]
+struct TypeArgsStats {
+    calls: AtomicU64,
+    computation_cost: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl TypeArgsStats {
+    fn record(&self, effects: &ExecutionEffects, template: &TypeArgsTemplate) {
+        if let Some(error) = effects.failure_status() {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+            warn!(
+                "Call with {} type arguments nested {} deep failed: {}",
+                template.count, template.depth, error
+            );
+            return;
+        }
+        self.computation_cost.fetch_add(
+            effects.gas_cost_summary().computation_cost,
+            Ordering::Relaxed,
+        );
+        self.calls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        vec![
+            ("calls".into(), self.calls.load(Ordering::Relaxed)),
+            (
+                "computation cost".into(),
+                self.computation_cost.load(Ordering::Relaxed),
+            ),
+            ("failed".into(), self.failed.load(Ordering::Relaxed)),
+        ]
+    }
+}
+
+#[derive(Debug)]
+pub struct TypeArgsTestPayload {
+    package_id: ObjectID,
+    template: TypeArgsTemplate,
+    /// Number of calls made so far, which picks the types of the next one
+    iteration: u64,
+    gas: Gas,
+    stats: Arc<TypeArgsStats>,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for TypeArgsTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "type_args")
+    }
+}
+
+impl Payload for TypeArgsTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        self.stats.record(effects, &self.template);
+        self.iteration += 1;
+        self.gas.0 = effects.gas_object().0;
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let (gas_budget, gas_price) = {
+            let state = self.system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        move_call_pt_impl(
+            self.gas.1,
+            &self.gas.2,
+            self.package_id,
+            MODULE_NAME,
+            &self.template.function(),
+            self.template.fill(self.package_id, self.iteration),
+            vec![],
+            &self.gas.0,
+            gas_budget,
+            gas_price,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct TypeArgsWorkloadBuilder {
+    num_payloads: u64,
+    template: TypeArgsTemplate,
+}
+
+impl TypeArgsWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        template: TypeArgsTemplate,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder =
+                Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(TypeArgsWorkloadBuilder {
+                    num_payloads: max_ops,
+                    template,
+                }));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for TypeArgsWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        // Gas coin for publishing package
+        let (address, keypair) = get_key_pair();
+        vec![GasCoinConfig {
+            amount: MAX_GAS_FOR_TESTING,
+            address,
+            keypair: Arc::new(keypair),
+        }]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        (0..self.num_payloads)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(TypeArgsWorkload {
+            package_id: None,
+            template: self.template,
+            init_gas,
+            payload_gas,
+            stats: Arc::default(),
+        }))
+    }
+}
+
+/// Calls a generic function with a configurable number of type arguments nested to a
+/// configurable depth, to measure what loading and checking type arguments costs. The function
+/// does nothing, so its gas is mostly that of its type arguments.
+#[derive(Debug)]
+pub struct TypeArgsWorkload {
+    package_id: Option<ObjectID>,
+    template: TypeArgsTemplate,
+    init_gas: Vec<Gas>,
+    payload_gas: Vec<Gas>,
+    stats: Arc<TypeArgsStats>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for TypeArgsWorkload {
+    async fn init(
+        &mut self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        if self.package_id.is_some() {
+            return Ok(());
+        }
+        let gas_price = system_state_observer.state.borrow().reference_gas_price;
+        let gas = self
+            .init_gas
+            .first()
+            .expect("Not enough gas to initialize type args workload");
+        info!("Publishing type args package");
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/workloads/data/type_args");
+        let effects = publish_package(path, gas.0, proxy, gas.1, &gas.2, gas_price).await;
+        self.package_id = Some(
+            parse_package_ref(&effects.created())
+                .expect("Type args package should be published")
+                .0,
+        );
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        self.payload_gas
+            .iter()
+            .enumerate()
+            .map(|(i, gas)| {
+                Box::new(TypeArgsTestPayload {
+                    package_id: self.package_id.unwrap(),
+                    template: self.template,
+                    // Payloads start at different types, so that they do not all call with the
+                    // same ones at the same time
+                    iteration: i as u64,
+                    gas: gas.clone(),
+                    stats: self.stats.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        self.stats.counters()
+    }
+}
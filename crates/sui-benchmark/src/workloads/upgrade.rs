@@ -0,0 +1,353 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::in_memory_wallet::move_call_pt_impl;
+use crate::system_state_observer::SystemStateObserver;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::future::join_all;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use sui_framework_build::compiled_package::BuildConfig;
+use sui_types::base_types::{ObjectID, ObjectRef, SuiAddress};
+use sui_types::crypto::{get_key_pair, AccountKeyPair};
+use sui_types::messages::{TransactionData, VerifiedTransaction};
+use sui_types::move_package::UpgradePolicy;
+use sui_types::object::Owner;
+use sui_types::utils::to_sender_signed_transaction;
+use test_utils::transaction::parse_package_ref;
+use tracing::{info, warn};
+
+const MODULE_NAME: &str = "upgrade";
+
+/// Modules of a version of the upgrade package, with what publishing or upgrading to them needs
+#[derive(Debug)]
+struct CompiledUpgradePackage {
+    modules: Vec<Vec<u8>>,
+    dep_ids: Vec<ObjectID>,
+    digest: Vec<u8>,
+}
+
+impl CompiledUpgradePackage {
+    /// Compiles the package at `src/workloads/data/upgrade/{version}`
+    fn new(version: &str) -> Self {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.extend(["src", "workloads", "data", "upgrade", version]);
+        let with_unpublished_deps = false;
+        let package = sui_framework::build_move_package(&path, BuildConfig::new_for_testing())
+            .expect("Upgrade package should compile");
+        CompiledUpgradePackage {
+            modules: package.get_package_bytes(with_unpublished_deps),
+            dep_ids: package.get_dependency_original_package_ids(),
+            digest: package.get_package_digest(with_unpublished_deps).to_vec(),
+        }
+    }
+}
+
+/// Outcomes of the transactions of all payloads of the workload
+#[derive(Debug, Default)]
+struct UpgradeStats {
+    upgrades: AtomicU64,
+    calls: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl UpgradeStats {
+    fn record(&self, effects: &ExecutionEffects, upgrade: bool) {
+        if let Some(error) = effects.failure_status() {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+            warn!(
+                "{} of the upgrade package failed: {}",
+                if upgrade { "Upgrade" } else { "Call" },
+                error
+            );
+            return;
+        }
+        if !upgrade {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        self.upgrades.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        vec![
+            ("upgrades".into(), self.upgrades.load(Ordering::Relaxed)),
+            ("calls".into(), self.calls.load(Ordering::Relaxed)),
+            ("failed".into(), self.failed.load(Ordering::Relaxed)),
+        ]
+    }
+}
+
+#[derive(Debug)]
+pub struct UpgradeTestPayload {
+    /// Latest version of the package of this payload, which upgrades apply to and calls go to
+    package_id: ObjectID,
+    upgrade_cap: ObjectRef,
+    /// Whether the next transaction upgrades the package rather than calls it
+    upgrade_next: bool,
+    upgraded: Arc<CompiledUpgradePackage>,
+    gas: Gas,
+    stats: Arc<UpgradeStats>,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for UpgradeTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "upgrade")
+    }
+}
+
+impl Payload for UpgradeTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        self.stats.record(effects, self.upgrade_next);
+        if self.upgrade_next {
+            // The cap is at a new version even if the upgrade failed, and the package only
+            // changes if it succeeded
+            if let Some((upgrade_cap, _)) = effects
+                .mutated()
+                .into_iter()
+                .find(|(obj_ref, _)| obj_ref.0 == self.upgrade_cap.0)
+            {
+                self.upgrade_cap = upgrade_cap;
+            }
+            if let Some(package) = parse_package_ref(&effects.created()) {
+                self.package_id = package.0;
+            }
+        }
+        self.upgrade_next = !self.upgrade_next;
+        self.gas.0 = effects.gas_object().0;
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let (gas_budget, gas_price) = {
+            let state = self.system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        if !self.upgrade_next {
+            return move_call_pt_impl(
+                self.gas.1,
+                &self.gas.2,
+                self.package_id,
+                MODULE_NAME,
+                "call",
+                vec![],
+                vec![],
+                &self.gas.0,
+                gas_budget,
+                gas_price,
+            );
+        }
+        // Every upgrade after the first one upgrades to the same modules again, which is
+        // trivially compatible but still goes through verification and linkage
+        let data = TransactionData::new_upgrade(
+            self.gas.1,
+            self.gas.0,
+            self.package_id,
+            self.upgraded.modules.clone(),
+            self.upgraded.dep_ids.clone(),
+            (self.upgrade_cap, Owner::AddressOwner(self.gas.1)),
+            UpgradePolicy::COMPATIBLE,
+            self.upgraded.digest.clone(),
+            gas_budget,
+            gas_price,
+        )
+        .expect("Upgrade cap should be owned by the payload");
+        to_sender_signed_transaction(data, self.gas.2.as_ref())
+    }
+}
+
+#[derive(Debug)]
+pub struct UpgradeWorkloadBuilder {
+    /// Owner of the package and the gas coin of every payload, generated upfront so that the
+    /// upgrade cap of the package published in `init` goes to the payload using it
+    payload_owners: Vec<(SuiAddress, Arc<AccountKeyPair>)>,
+}
+
+impl UpgradeWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder =
+                Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(UpgradeWorkloadBuilder {
+                    payload_owners: (0..max_ops)
+                        .map(|_| {
+                            let (address, keypair) = get_key_pair();
+                            (address, Arc::new(keypair))
+                        })
+                        .collect(),
+                }));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+
+    fn coin_configs(&self) -> Vec<GasCoinConfig> {
+        self.payload_owners
+            .iter()
+            .map(|(address, keypair)| GasCoinConfig {
+                amount: MAX_GAS_FOR_TESTING,
+                address: *address,
+                keypair: keypair.clone(),
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for UpgradeWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        // Gas coin for publishing the package of every payload
+        self.coin_configs()
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        self.coin_configs()
+    }
+    fn required_addresses(&self) -> Vec<SuiAddress> {
+        self.payload_owners
+            .iter()
+            .map(|(address, _)| *address)
+            .collect()
+    }
+    async fn build(
+        &self,
+        init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(UpgradeWorkload {
+            original: CompiledUpgradePackage::new("v1"),
+            upgraded: Arc::new(CompiledUpgradePackage::new("v2")),
+            packages: vec![],
+            init_gas,
+            payload_gas,
+            stats: Arc::default(),
+        }))
+    }
+}
+
+/// Upgrades packages and calls the upgraded versions, to exercise upgrade verification and the
+/// linkage of upgraded packages, which publishing and calling packages alone do not. Every
+/// payload has a package of its own, published in `init`, and alternates between upgrading it
+/// and calling an entry function of its latest version.
+#[derive(Debug)]
+pub struct UpgradeWorkload {
+    original: CompiledUpgradePackage,
+    upgraded: Arc<CompiledUpgradePackage>,
+    /// Package and upgrade cap published with every coin of `init_gas`, in the same order
+    packages: Vec<(ObjectID, ObjectRef)>,
+    init_gas: Vec<Gas>,
+    payload_gas: Vec<Gas>,
+    stats: Arc<UpgradeStats>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for UpgradeWorkload {
+    async fn init(
+        &mut self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        if !self.packages.is_empty() {
+            return Ok(());
+        }
+        let (gas_budget, gas_price) = {
+            let state = system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        info!("Publishing {} upgradeable packages", self.init_gas.len());
+        let publishes = self.init_gas.iter().map(|(gas, sender, keypair)| {
+            let data = TransactionData::new_module(
+                *sender,
+                *gas,
+                self.original.modules.clone(),
+                self.original.dep_ids.clone(),
+                gas_budget,
+                gas_price,
+            );
+            let transaction = to_sender_signed_transaction(data, keypair.as_ref());
+            let proxy = proxy.clone();
+            async move {
+                let effects = proxy
+                    .execute_transaction_block(transaction.into())
+                    .await
+                    .unwrap();
+                let package = parse_package_ref(&effects.created())
+                    .expect("Upgrade package should be published");
+                // Publishing transfers the upgrade cap to the sender, and creates nothing else
+                // owned by it
+                let upgrade_cap = *effects
+                    .created_owned_by(*sender)
+                    .first()
+                    .expect("Publishing should create an upgrade cap");
+                (package.0, upgrade_cap)
+            }
+        });
+        self.packages = join_all(publishes).await;
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        // Coins of `payload_gas` belong to the same owners as those of `init_gas`, in the same
+        // order, so that every payload gets the package its owner published
+        self.payload_gas
+            .iter()
+            .zip(self.packages.iter())
+            .map(|(gas, (package_id, upgrade_cap))| {
+                Box::new(UpgradeTestPayload {
+                    package_id: *package_id,
+                    upgrade_cap: *upgrade_cap,
+                    upgrade_next: true,
+                    upgraded: self.upgraded.clone(),
+                    gas: gas.clone(),
+                    stats: self.stats.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        self.stats.counters()
+    }
+}
@@ -0,0 +1,395 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::in_memory_wallet::move_call_pt_impl;
+use crate::system_state_observer::SystemStateObserver;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{BenchMoveCallArg, ExecutionEffects, ValidatorProxy};
+use anyhow::Result;
+use async_trait::async_trait;
+use rand::Rng;
+use std::sync::Arc;
+use sui_core::test_utils::make_transfer_sui_transaction;
+use sui_types::base_types::{ObjectRef, SuiAddress};
+use sui_types::crypto::{
+    generate_proof_of_possession, get_key_pair, AccountKeyPair, AuthorityKeyPair, KeypairTraits,
+    NetworkKeyPair, ToFromBytes,
+};
+use sui_types::messages::VerifiedTransaction;
+use sui_types::{
+    SUI_SYSTEM_PACKAGE_ID, SUI_SYSTEM_STATE_OBJECT_ID, SUI_SYSTEM_STATE_OBJECT_SHARED_VERSION,
+};
+use test_utils::messages::make_staking_transaction;
+use tracing::warn;
+
+const MODULE_NAME: &str = "sui_system";
+
+/// Upper bound on the number of candidates cycling through the validator set at the same time.
+/// Every candidate which joins becomes a committee member without a running node, so this is
+/// kept small to not take a meaningful share of the voting power.
+const MAX_CONCURRENT_CANDIDATES: u64 = 2;
+
+/// Amount each candidate stakes with itself before requesting to join the committee
+const CANDIDATE_STAKE: u64 = MAX_GAS_FOR_TESTING;
+
+/// Steps of the validator lifecycle. Joining and leaving the committee only take effect at the
+/// next epoch boundary, so the payload waits for the epoch to change after each of those
+/// requests before moving on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LifecyclePhase {
+    RegisterCandidate,
+    SplitStake,
+    AddStake,
+    JoinCommittee,
+    WaitForActivation { requested_epoch: u64 },
+    LeaveCommittee,
+    WaitForRemoval { requested_epoch: u64 },
+    RemoveCandidate,
+    WithdrawStake,
+}
+
+#[derive(Debug)]
+pub struct ValidatorLifecycleTestPayload {
+    phase: LifecyclePhase,
+    /// Coin to stake with the candidate, once split out of the gas coin
+    stake_coin: Option<ObjectRef>,
+    /// Stake of the candidate in its own staking pool
+    staked_sui: Option<ObjectRef>,
+    /// First of the four consecutive ports advertised by this candidate. No node ever listens on
+    /// them, they only need to differ from the ports of other validators.
+    base_port: u64,
+    protocol_keypair: AuthorityKeyPair,
+    network_keypair: NetworkKeyPair,
+    worker_keypair: NetworkKeyPair,
+    gas: ObjectRef,
+    sender: SuiAddress,
+    keypair: Arc<AccountKeyPair>,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for ValidatorLifecycleTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "validator_lifecycle")
+    }
+}
+
+impl ValidatorLifecycleTestPayload {
+    fn current_epoch(&self) -> u64 {
+        self.system_state_observer.state.borrow().epoch
+    }
+
+    fn register_candidate_args(&self) -> Vec<BenchMoveCallArg> {
+        let pure = |bytes: &[u8]| BenchMoveCallArg::from(&bytes.to_vec());
+        let proof_of_possession = generate_proof_of_possession(&self.protocol_keypair, self.sender);
+        vec![
+            self.system_state_arg(),
+            pure(self.protocol_keypair.public().as_bytes()),
+            pure(self.network_keypair.public().as_bytes()),
+            pure(self.worker_keypair.public().as_bytes()),
+            pure(proof_of_possession.as_ref()),
+            pure(format!("bench-candidate-{}", self.sender).as_bytes()),
+            pure(b"validator lifecycle benchmark candidate"),
+            pure(b""),
+            pure(b""),
+            pure(format!("/ip4/127.0.0.1/tcp/{}/http", self.base_port).as_bytes()),
+            pure(format!("/ip4/127.0.0.1/udp/{}", self.base_port + 1).as_bytes()),
+            pure(format!("/ip4/127.0.0.1/udp/{}", self.base_port + 2).as_bytes()),
+            pure(format!("/ip4/127.0.0.1/udp/{}", self.base_port + 3).as_bytes()),
+            self.system_state_observer
+                .state
+                .borrow()
+                .reference_gas_price
+                .into(),
+            0u64.into(),
+        ]
+    }
+
+    fn system_state_arg(&self) -> BenchMoveCallArg {
+        BenchMoveCallArg::Shared((
+            SUI_SYSTEM_STATE_OBJECT_ID,
+            SUI_SYSTEM_STATE_OBJECT_SHARED_VERSION,
+            true,
+        ))
+    }
+
+    fn system_call(&self, function: &str, args: Vec<BenchMoveCallArg>) -> VerifiedTransaction {
+        let (gas_budget, gas_price) = {
+            let state = self.system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        move_call_pt_impl(
+            self.sender,
+            &self.keypair,
+            SUI_SYSTEM_PACKAGE_ID,
+            MODULE_NAME,
+            function,
+            vec![],
+            args,
+            &self.gas,
+            gas_budget,
+            gas_price,
+        )
+    }
+
+    /// Transaction which only touches the gas coin, used while waiting for an epoch change
+    fn noop_transaction(&self) -> VerifiedTransaction {
+        make_transfer_sui_transaction(
+            self.gas,
+            self.sender,
+            None,
+            self.sender,
+            &self.keypair,
+            Some(
+                self.system_state_observer
+                    .state
+                    .borrow()
+                    .reference_gas_price,
+            ),
+        )
+    }
+}
+
+impl Payload for ValidatorLifecycleTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        let created = effects.created_single();
+        self.phase = match (self.phase, effects.is_ok()) {
+            (LifecyclePhase::RegisterCandidate, true) if self.stake_coin.is_some() => {
+                LifecyclePhase::AddStake
+            }
+            (LifecyclePhase::RegisterCandidate, true) => LifecyclePhase::SplitStake,
+            (LifecyclePhase::SplitStake, true) => {
+                self.stake_coin = created;
+                LifecyclePhase::AddStake
+            }
+            (LifecyclePhase::AddStake, true) => {
+                self.stake_coin = None;
+                self.staked_sui = created;
+                LifecyclePhase::JoinCommittee
+            }
+            (LifecyclePhase::JoinCommittee, true) => LifecyclePhase::WaitForActivation {
+                requested_epoch: self.current_epoch(),
+            },
+            // Most likely not enough stake to join, leave as a candidate instead
+            (LifecyclePhase::JoinCommittee, false) => LifecyclePhase::RemoveCandidate,
+            (LifecyclePhase::LeaveCommittee, true) => LifecyclePhase::WaitForRemoval {
+                requested_epoch: self.current_epoch(),
+            },
+            // The epoch changed between the join request and our observation of it, so we are
+            // still pending. Wait for another epoch change.
+            (LifecyclePhase::LeaveCommittee, false) => LifecyclePhase::WaitForActivation {
+                requested_epoch: self.current_epoch(),
+            },
+            (LifecyclePhase::RemoveCandidate, _) => LifecyclePhase::WithdrawStake,
+            (LifecyclePhase::WithdrawStake, ok) => {
+                // Withdrawn stake is reused for the next cycle
+                self.stake_coin = if ok { created } else { None };
+                self.staked_sui = None;
+                LifecyclePhase::RegisterCandidate
+            }
+            (phase, _) => phase,
+        };
+        self.gas = effects.gas_object().0;
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let current_epoch = self.current_epoch();
+        match self.phase {
+            LifecyclePhase::WaitForActivation { requested_epoch }
+                if current_epoch > requested_epoch =>
+            {
+                self.phase = LifecyclePhase::LeaveCommittee
+            }
+            LifecyclePhase::WaitForRemoval { requested_epoch }
+                if current_epoch > requested_epoch =>
+            {
+                self.phase = LifecyclePhase::WithdrawStake
+            }
+            _ => (),
+        }
+        match self.phase {
+            LifecyclePhase::RegisterCandidate => self.system_call(
+                "request_add_validator_candidate",
+                self.register_candidate_args(),
+            ),
+            LifecyclePhase::SplitStake => make_transfer_sui_transaction(
+                self.gas,
+                self.sender,
+                Some(CANDIDATE_STAKE),
+                self.sender,
+                &self.keypair,
+                Some(
+                    self.system_state_observer
+                        .state
+                        .borrow()
+                        .reference_gas_price,
+                ),
+            ),
+            LifecyclePhase::AddStake => make_staking_transaction(
+                self.gas,
+                self.stake_coin.unwrap(),
+                self.sender,
+                self.sender,
+                &self.keypair,
+                Some(
+                    self.system_state_observer
+                        .state
+                        .borrow()
+                        .reference_gas_price,
+                ),
+            ),
+            LifecyclePhase::JoinCommittee => {
+                self.system_call("request_add_validator", vec![self.system_state_arg()])
+            }
+            LifecyclePhase::LeaveCommittee => {
+                self.system_call("request_remove_validator", vec![self.system_state_arg()])
+            }
+            LifecyclePhase::RemoveCandidate => self.system_call(
+                "request_remove_validator_candidate",
+                vec![self.system_state_arg()],
+            ),
+            LifecyclePhase::WithdrawStake => match self.staked_sui {
+                Some(staked_sui) => self.system_call(
+                    "request_withdraw_stake",
+                    vec![self.system_state_arg(), staked_sui.into()],
+                ),
+                None => self.noop_transaction(),
+            },
+            LifecyclePhase::WaitForActivation { .. } | LifecyclePhase::WaitForRemoval { .. } => {
+                self.noop_transaction()
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ValidatorLifecycleWorkloadBuilder {
+    num_candidates: u64,
+}
+
+impl ValidatorLifecycleWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = std::cmp::min(target_qps * in_flight_ratio, MAX_CONCURRENT_CANDIDATES);
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            // Every candidate moves through its lifecycle one transaction at a time and a single
+            // worker drives all of them, so no two requests for the same candidate race
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers: 1,
+                max_ops,
+            };
+            let workload_builder = Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(
+                ValidatorLifecycleWorkloadBuilder {
+                    num_candidates: max_ops,
+                },
+            ));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for ValidatorLifecycleWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        vec![]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        // Candidates pay for gas and their own stake from the same coin
+        (0..self.num_candidates)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING + CANDIDATE_STAKE,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        _init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(ValidatorLifecycleWorkload { payload_gas }))
+    }
+}
+
+/// Repeatedly registers funded candidates, stakes with them, joins them to the committee and
+/// removes them again. Joining requires `CANDIDATE_STAKE` to be at least the minimum validator
+/// joining stake of the network, otherwise candidates are removed right after registering.
+#[derive(Debug)]
+pub struct ValidatorLifecycleWorkload {
+    payload_gas: Vec<Gas>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for ValidatorLifecycleWorkload {
+    async fn init(
+        &mut self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        _system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        match proxy.get_latest_system_state_object().await {
+            Ok(system_state) if system_state.min_validator_joining_stake > CANDIDATE_STAKE => {
+                warn!(
+                    "Candidate stake {} is below the minimum joining stake {}, candidates will not join the committee",
+                    CANDIDATE_STAKE, system_state.min_validator_joining_stake
+                );
+            }
+            Ok(_) => (),
+            Err(e) => warn!("Failed to read minimum validator joining stake: {}", e),
+        }
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        self.payload_gas
+            .iter()
+            .map(|gas| {
+                let (_, protocol_keypair) = get_key_pair();
+                let (_, network_keypair) = get_key_pair();
+                let (_, worker_keypair) = get_key_pair();
+                Box::new(ValidatorLifecycleTestPayload {
+                    phase: LifecyclePhase::RegisterCandidate,
+                    stake_coin: None,
+                    staked_sui: None,
+                    base_port: rand::thread_rng().gen_range(10_000..60_000),
+                    protocol_keypair,
+                    network_keypair,
+                    worker_keypair,
+                    gas: gas.0,
+                    sender: gas.1,
+                    keypair: gas.2.clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+}
@@ -0,0 +1,348 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::in_memory_wallet::move_call_pt_impl;
+use crate::system_state_observer::SystemStateObserver;
+use crate::util::publish_package;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{Workload, WorkloadBuilder, MAX_GAS_FOR_TESTING};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::{BenchMoveCallArg, ExecutionEffects, ValidatorProxy};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use sui_types::base_types::{ObjectID, ObjectRef};
+use sui_types::crypto::get_key_pair;
+use sui_types::messages::VerifiedTransaction;
+use test_utils::transaction::parse_package_ref;
+use tracing::{info, warn};
+
+const MODULE_NAME: &str = "vector_sum";
+/// Bytes taken by every element of the vectors, which are `vector<u64>`
+const ELEMENT_SIZE: u64 = 8;
+
+/// Lengths of the vectors of the workload, parsed from a comma separated list, e.g.
+/// "10,1000,10000". Payloads are spread evenly across them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VectorLengths {
+    lengths: Vec<u64>,
+}
+
+impl Default for VectorLengths {
+    fn default() -> Self {
+        VectorLengths {
+            lengths: vec![1000],
+        }
+    }
+}
+
+impl FromStr for VectorLengths {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lengths = s
+            .split(',')
+            .map(|length| Ok(length.trim().parse::<u64>()?))
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+        if lengths.iter().any(|length| *length == 0) {
+            return Err(anyhow!("vector lengths must be positive"));
+        }
+        Ok(VectorLengths { lengths })
+    }
+}
+
+/// Gas paid by the sums of all payloads with vectors of the same length
+#[derive(Debug)]
+struct VectorSumStats {
+    length: u64,
+    sums: AtomicU64,
+    computation_cost: AtomicU64,
+}
+
+impl VectorSumStats {
+    fn new(length: u64) -> Self {
+        VectorSumStats {
+            length,
+            sums: AtomicU64::new(0),
+            computation_cost: AtomicU64::new(0),
+        }
+    }
+
+    fn record(&self, effects: &ExecutionEffects) {
+        self.computation_cost.fetch_add(
+            effects.gas_cost_summary().computation_cost,
+            Ordering::Relaxed,
+        );
+        self.sums.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        vec![
+            (
+                format!("sums: {} elements", self.length),
+                self.sums.load(Ordering::Relaxed),
+            ),
+            (
+                format!("computation cost: {} elements", self.length),
+                self.computation_cost.load(Ordering::Relaxed),
+            ),
+        ]
+    }
+}
+
+#[derive(Debug)]
+pub struct VectorSumTestPayload {
+    package_id: ObjectID,
+    gas: Gas,
+    /// Object holding the vector, once created
+    numbers: Option<ObjectRef>,
+    stats: Arc<VectorSumStats>,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl std::fmt::Display for VectorSumTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "vector_sum")
+    }
+}
+
+impl Payload for VectorSumTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        match (self.numbers, effects.failure_status()) {
+            (None, Some(error)) => warn!(
+                "Creating a vector of {} elements failed: {}",
+                self.stats.length, error
+            ),
+            (None, None) => self.numbers = effects.created_single(),
+            (Some(numbers), failure) => {
+                if let Some(error) = failure {
+                    warn!(
+                        "Summing a vector of {} elements failed: {}",
+                        self.stats.length, error
+                    );
+                } else {
+                    self.stats.record(effects);
+                }
+                // The object is an input of the sum whether it failed or not, so it has a new
+                // version either way
+                self.numbers = effects
+                    .mutated()
+                    .into_iter()
+                    .map(|(obj_ref, _)| obj_ref)
+                    .find(|obj_ref| obj_ref.0 == numbers.0);
+            }
+        }
+        self.gas.0 = effects.gas_object().0;
+    }
+
+    fn make_transaction(&mut self) -> VerifiedTransaction {
+        let (gas_budget, gas_price) = {
+            let state = self.system_state_observer.state.borrow();
+            (
+                state
+                    .protocol_config
+                    .as_ref()
+                    .expect("Protocol config not in system state")
+                    .max_tx_gas(),
+                state.reference_gas_price,
+            )
+        };
+        let (function, args) = match self.numbers {
+            None => ("create", vec![self.stats.length.into()]),
+            Some(numbers) => ("sum", vec![BenchMoveCallArg::ImmOrOwnedObject(numbers)]),
+        };
+        move_call_pt_impl(
+            self.gas.1,
+            &self.gas.2,
+            self.package_id,
+            MODULE_NAME,
+            function,
+            vec![],
+            args,
+            &self.gas.0,
+            gas_budget,
+            gas_price,
+        )
+    }
+
+    fn stats_target(&self) -> Option<String> {
+        // Breaks down the latency of sums by length, creating the vectors is a one-off
+        self.numbers
+            .map(|_| format!("{} elements", self.stats.length))
+    }
+}
+
+#[derive(Debug)]
+pub struct VectorSumWorkloadBuilder {
+    num_payloads: u64,
+    lengths: VectorLengths,
+}
+
+impl VectorSumWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        lengths: VectorLengths,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32) as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+            };
+            let workload_builder =
+                Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(VectorSumWorkloadBuilder {
+                    num_payloads: max_ops,
+                    lengths,
+                }));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for VectorSumWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        // Gas coin for publishing package
+        let (address, keypair) = get_key_pair();
+        vec![GasCoinConfig {
+            amount: MAX_GAS_FOR_TESTING,
+            address,
+            keypair: Arc::new(keypair),
+        }]
+    }
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        (0..self.num_payloads)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING,
+                    address,
+                    keypair: Arc::new(keypair),
+                }
+            })
+            .collect()
+    }
+    async fn build(
+        &self,
+        init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(VectorSumWorkload {
+            package_id: None,
+            lengths: self.lengths.clone(),
+            init_gas,
+            payload_gas,
+            stats: self
+                .lengths
+                .lengths
+                .iter()
+                .map(|length| Arc::new(VectorSumStats::new(*length)))
+                .collect(),
+        }))
+    }
+}
+
+/// Creates owned objects holding a `vector<u64>` of a configurable length, then repeatedly calls
+/// a function iterating over the vector to sum it, to measure how the computation cost of Move
+/// code scales with the size of the collections it goes through. Several lengths can be mixed,
+/// and the average computation cost of a sum is reported by length.
+#[derive(Debug)]
+pub struct VectorSumWorkload {
+    package_id: Option<ObjectID>,
+    lengths: VectorLengths,
+    init_gas: Vec<Gas>,
+    payload_gas: Vec<Gas>,
+    /// Stats of the payloads with vectors of every length, in the order of `lengths`
+    stats: Vec<Arc<VectorSumStats>>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for VectorSumWorkload {
+    async fn init(
+        &mut self,
+        proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Result<()> {
+        if self.package_id.is_some() {
+            return Ok(());
+        }
+        let max_move_object_size = system_state_observer
+            .state
+            .borrow()
+            .protocol_config
+            .as_ref()
+            .map(|protocol_config| protocol_config.max_move_object_size());
+        if let Some(max_move_object_size) = max_move_object_size {
+            if let Some(length) = self
+                .lengths
+                .lengths
+                .iter()
+                .find(|length| **length * ELEMENT_SIZE > max_move_object_size)
+            {
+                bail!(
+                    "A vector of {} elements takes more than the maximum object size of {} bytes",
+                    length,
+                    max_move_object_size
+                );
+            }
+        }
+        let gas_price = system_state_observer.state.borrow().reference_gas_price;
+        let gas = self
+            .init_gas
+            .first()
+            .expect("Not enough gas to initialize vector sum workload");
+        info!("Publishing vector sum package");
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/workloads/data/vector_sum");
+        let effects = publish_package(path, gas.0, proxy, gas.1, &gas.2, gas_price).await;
+        self.package_id = Some(
+            parse_package_ref(&effects.created())
+                .expect("Vector sum package should be published")
+                .0,
+        );
+        Ok(())
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        self.payload_gas
+            .iter()
+            .enumerate()
+            .map(|(i, gas)| {
+                Box::new(VectorSumTestPayload {
+                    package_id: self.package_id.unwrap(),
+                    gas: gas.clone(),
+                    numbers: None,
+                    stats: self.stats[i % self.stats.len()].clone(),
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+
+    fn counters(&self) -> Vec<(String, u64)> {
+        self.stats
+            .iter()
+            .flat_map(|stats| stats.counters())
+            .collect()
+    }
+}
@@ -1,8 +1,11 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use anyhow::Result;
 use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
 use std::sync::Arc;
+use sui_types::base_types::SuiAddress;
 
 use crate::system_state_observer::SystemStateObserver;
 use crate::workloads::{Gas, GasCoinConfig};
@@ -18,6 +21,22 @@ pub const MAX_GAS_FOR_TESTING: u64 = 10_000_000_000_000;
 pub trait WorkloadBuilder<T: Payload + ?Sized>: Send + Sync + std::fmt::Debug {
     async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig>;
     async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig>;
+    /// Same coin configs as `generate_coin_config_for_payloads`, but consumed lazily by the bank
+    /// chunk by chunk. Builders with a large number of payloads can override this to avoid
+    /// creating all configs (and their keypairs) upfront.
+    async fn stream_coin_config_for_payloads(&self) -> BoxStream<'_, GasCoinConfig> {
+        stream::iter(self.generate_coin_config_for_payloads().await).boxed()
+    }
+    /// Addresses receiving the coins of `generate_coin_config_for_init` and
+    /// `stream_coin_config_for_payloads`, derived from the same owners as the configs so that
+    /// they can be funded in bulk before the workload is built, e.g. through a rate limited
+    /// faucet. The bank refuses to fund a config sent to an address missing from them. Builders
+    /// must report them whenever the owners of their coins are fixed upfront; the default of
+    /// none only suits builders creating a new keypair as every config is generated, which have
+    /// no addresses before the configs exist.
+    fn required_addresses(&self) -> Vec<SuiAddress> {
+        vec![]
+    }
     async fn build(&self, init_gas: Vec<Gas>, payload_gas: Vec<Gas>) -> Box<dyn Workload<T>>;
 }
 
@@ -31,14 +50,27 @@ pub trait WorkloadBuilder<T: Payload + ?Sized>: Send + Sync + std::fmt::Debug {
 /// payloads are created.
 #[async_trait]
 pub trait Workload<T: Payload + ?Sized>: Send + Sync + std::fmt::Debug {
+    /// Sets up what the payloads of the workload need, e.g. publishes packages. An error aborts
+    /// the construction of the workloads.
     async fn init(
         &mut self,
         proxy: Arc<dyn ValidatorProxy + Sync + Send>,
         system_state_observer: Arc<SystemStateObserver>,
-    );
+    ) -> Result<()>;
     async fn make_test_payloads(
         &self,
         proxy: Arc<dyn ValidatorProxy + Sync + Send>,
         system_state_observer: Arc<SystemStateObserver>,
     ) -> Vec<Box<T>>;
+    /// Fraction (0.0 to 1.0) of the transactions of the workload which must succeed for the run
+    /// to pass, checked once the run is over. Transactions fail when they get an error or fail
+    /// in execution, as for the failure gate of the driver, and those of the warmup don't count.
+    fn min_success_rate(&self) -> Option<f64> {
+        None
+    }
+    /// Counters the payloads of the workload kept while running, e.g. how often they saw an
+    /// expected rejection, read once the run is over and added to the stats of the run.
+    fn counters(&self) -> Vec<(String, u64)> {
+        vec![]
+    }
 }
@@ -0,0 +1,624 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::options::RunSpec;
+use crate::workloads::adversarial::AdversarialPayloadCfg;
+use crate::workloads::delegation::StakeAmountDistribution;
+use crate::workloads::pure_input::PureInputSizes;
+use crate::workloads::signature_schemes::SchemeMix;
+use crate::workloads::vector_sum::VectorLengths;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::str::FromStr;
+use sui_types::base_types::ObjectID;
+
+/// Mix of workloads of a benchmark run read from a TOML file, as an alternative to passing the
+/// weight and parameters of every workload on the command line. Workloads missing from the file
+/// do not run; parameters missing from the file keep their command line value. E.g.
+///
+/// ```toml
+/// target_qps = 500
+///
+/// [[workloads]]
+/// name = "delegation"
+/// weight = 3
+/// stake_amounts = "1000000000:70,10000000000:30"
+/// track_committee = true
+///
+/// [[workloads]]
+/// name = "shared_counter"
+/// weight = 1
+/// hotness_factor = 90
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkloadConfigFile {
+    target_qps: Option<u64>,
+    num_workers: Option<u64>,
+    in_flight_ratio: Option<u64>,
+    workloads: Vec<WorkloadConfig>,
+}
+
+/// A workload of the file with its weight and its own parameters, named like the command line
+/// options of the workload without their prefix
+#[derive(Debug, Deserialize)]
+#[serde(tag = "name", rename_all = "snake_case", deny_unknown_fields)]
+enum WorkloadConfig {
+    SharedCounter {
+        weight: u32,
+        hotness_factor: Option<u32>,
+        distinct_addresses: Option<u64>,
+    },
+    TransferObject {
+        weight: u32,
+    },
+    Delegation {
+        weight: u32,
+        stake_amounts: Option<String>,
+        seed: Option<u64>,
+        track_committee: Option<bool>,
+        refetch_ratio: Option<f32>,
+        check_events: Option<bool>,
+        min_success_rate: Option<f32>,
+    },
+    BatchPayment {
+        weight: u32,
+        size: Option<u32>,
+    },
+    Adversarial {
+        weight: u32,
+        cfg: Option<String>,
+    },
+    Governance {
+        weight: u32,
+        proposal_ratio: Option<u32>,
+    },
+    ValidatorLifecycle {
+        weight: u32,
+    },
+    Hotspot {
+        weight: u32,
+        object: Option<ObjectID>,
+    },
+    GasSmash {
+        weight: u32,
+        coins_per_tx: Option<u32>,
+    },
+    SizeLimit {
+        weight: u32,
+        offset_bytes: Option<u64>,
+        oversized_ratio: Option<f32>,
+    },
+    SystemStateRead {
+        weight: u32,
+    },
+    MoveCall {
+        weight: u32,
+        target: Option<String>,
+        type_args: Option<String>,
+        args: Option<String>,
+        setup_function: Option<String>,
+    },
+    Equivocation {
+        weight: u32,
+        ratio: Option<f32>,
+    },
+    Clock {
+        weight: u32,
+    },
+    ObjectSize {
+        weight: u32,
+        bytes: Option<u64>,
+    },
+    GasStation {
+        weight: u32,
+        senders_per_sponsor: Option<u64>,
+        coins_per_sponsor: Option<u64>,
+    },
+    PingPong {
+        weight: u32,
+    },
+    Upgrade {
+        weight: u32,
+    },
+    TypeArgs {
+        weight: u32,
+        count: Option<u64>,
+        depth: Option<u64>,
+    },
+    StakeWithdraw {
+        weight: u32,
+    },
+    SignatureSchemes {
+        weight: u32,
+        mix: Option<String>,
+    },
+    MoveAbort {
+        weight: u32,
+        code: Option<u64>,
+    },
+    InsufficientGas {
+        weight: u32,
+        ratio: Option<f32>,
+        margin: Option<f32>,
+    },
+    CustomCoin {
+        weight: u32,
+        mint_amount: Option<u64>,
+        ring_size: Option<u64>,
+    },
+    PureInput {
+        weight: u32,
+        sizes: Option<String>,
+    },
+    Freeze {
+        weight: u32,
+        objects: Option<u64>,
+    },
+    VectorSum {
+        weight: u32,
+        lengths: Option<String>,
+    },
+    ReadWrite {
+        weight: u32,
+        read_ratio: Option<f32>,
+    },
+}
+
+impl WorkloadConfig {
+    fn name(&self) -> &'static str {
+        match self {
+            WorkloadConfig::SharedCounter { .. } => "shared_counter",
+            WorkloadConfig::TransferObject { .. } => "transfer_object",
+            WorkloadConfig::Delegation { .. } => "delegation",
+            WorkloadConfig::BatchPayment { .. } => "batch_payment",
+            WorkloadConfig::Adversarial { .. } => "adversarial",
+            WorkloadConfig::Governance { .. } => "governance",
+            WorkloadConfig::ValidatorLifecycle { .. } => "validator_lifecycle",
+            WorkloadConfig::Hotspot { .. } => "hotspot",
+            WorkloadConfig::GasSmash { .. } => "gas_smash",
+            WorkloadConfig::SizeLimit { .. } => "size_limit",
+            WorkloadConfig::SystemStateRead { .. } => "system_state_read",
+            WorkloadConfig::MoveCall { .. } => "move_call",
+            WorkloadConfig::Equivocation { .. } => "equivocation",
+            WorkloadConfig::Clock { .. } => "clock",
+            WorkloadConfig::ObjectSize { .. } => "object_size",
+            WorkloadConfig::GasStation { .. } => "gas_station",
+            WorkloadConfig::PingPong { .. } => "ping_pong",
+            WorkloadConfig::Upgrade { .. } => "upgrade",
+            WorkloadConfig::TypeArgs { .. } => "type_args",
+            WorkloadConfig::StakeWithdraw { .. } => "stake_withdraw",
+            WorkloadConfig::SignatureSchemes { .. } => "signature_schemes",
+            WorkloadConfig::MoveAbort { .. } => "move_abort",
+            WorkloadConfig::InsufficientGas { .. } => "insufficient_gas",
+            WorkloadConfig::CustomCoin { .. } => "custom_coin",
+            WorkloadConfig::PureInput { .. } => "pure_input",
+            WorkloadConfig::Freeze { .. } => "freeze",
+            WorkloadConfig::VectorSum { .. } => "vector_sum",
+            WorkloadConfig::ReadWrite { .. } => "read_write",
+        }
+    }
+
+    fn weight(&self) -> u32 {
+        match self {
+            WorkloadConfig::SharedCounter { weight, .. }
+            | WorkloadConfig::TransferObject { weight }
+            | WorkloadConfig::Delegation { weight, .. }
+            | WorkloadConfig::BatchPayment { weight, .. }
+            | WorkloadConfig::Adversarial { weight, .. }
+            | WorkloadConfig::Governance { weight, .. }
+            | WorkloadConfig::ValidatorLifecycle { weight }
+            | WorkloadConfig::Hotspot { weight, .. }
+            | WorkloadConfig::GasSmash { weight, .. }
+            | WorkloadConfig::SizeLimit { weight, .. }
+            | WorkloadConfig::SystemStateRead { weight }
+            | WorkloadConfig::MoveCall { weight, .. }
+            | WorkloadConfig::Equivocation { weight, .. }
+            | WorkloadConfig::Clock { weight }
+            | WorkloadConfig::ObjectSize { weight, .. }
+            | WorkloadConfig::GasStation { weight, .. }
+            | WorkloadConfig::PingPong { weight }
+            | WorkloadConfig::Upgrade { weight }
+            | WorkloadConfig::TypeArgs { weight, .. }
+            | WorkloadConfig::StakeWithdraw { weight }
+            | WorkloadConfig::SignatureSchemes { weight, .. }
+            | WorkloadConfig::MoveAbort { weight, .. }
+            | WorkloadConfig::InsufficientGas { weight, .. }
+            | WorkloadConfig::CustomCoin { weight, .. }
+            | WorkloadConfig::PureInput { weight, .. }
+            | WorkloadConfig::Freeze { weight, .. }
+            | WorkloadConfig::VectorSum { weight, .. }
+            | WorkloadConfig::ReadWrite { weight, .. } => *weight,
+        }
+    }
+}
+
+/// Overwrites `value` with the value from the file, if any
+fn set<T>(value: &mut T, from_file: &Option<T>)
+where
+    T: Clone,
+{
+    if let Some(from_file) = from_file {
+        *value = from_file.clone();
+    }
+}
+
+/// Checks that a fraction from the file is between 0.0 and 1.0
+fn check_ratio(workload: &str, param: &str, ratio: &Option<f32>) -> Result<()> {
+    match ratio {
+        Some(ratio) if !(0.0..=1.0).contains(ratio) => bail!(
+            "{} of workload {} must be between 0.0 and 1.0, got {}",
+            param,
+            workload,
+            ratio
+        ),
+        _ => Ok(()),
+    }
+}
+
+impl WorkloadConfigFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read workload config {}", path.display()))?;
+        let config: Self = toml::from_str(&contents)
+            .with_context(|| format!("Invalid workload config {}", path.display()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks what deserialization cannot, so that a bad file fails before any gas is spent
+    fn validate(&self) -> Result<()> {
+        if self.workloads.iter().all(|w| w.weight() == 0) {
+            bail!("Workload config must give at least one workload a weight above 0");
+        }
+        let mut names = BTreeSet::new();
+        for workload in &self.workloads {
+            let name = workload.name();
+            if !names.insert(name) {
+                bail!(
+                    "Workload {} appears more than once in the workload config",
+                    name
+                );
+            }
+            match workload {
+                WorkloadConfig::Delegation {
+                    stake_amounts,
+                    refetch_ratio,
+                    min_success_rate,
+                    ..
+                } => {
+                    if let Some(stake_amounts) = stake_amounts {
+                        StakeAmountDistribution::from_str(stake_amounts)
+                            .and_then(|amounts| Ok(amounts.check()?))
+                            .context("Invalid stake_amounts of workload delegation")?;
+                    }
+                    check_ratio(name, "refetch_ratio", refetch_ratio)?;
+                    check_ratio(name, "min_success_rate", min_success_rate)?;
+                }
+                WorkloadConfig::Adversarial { cfg: Some(cfg), .. } => {
+                    AdversarialPayloadCfg::from_str(cfg)
+                        .context("Invalid cfg of workload adversarial")?;
+                }
+                WorkloadConfig::Governance {
+                    proposal_ratio: Some(proposal_ratio),
+                    ..
+                } if *proposal_ratio > 100 => bail!(
+                    "proposal_ratio of workload governance is a percentage, got {}",
+                    proposal_ratio
+                ),
+                WorkloadConfig::SizeLimit {
+                    oversized_ratio, ..
+                } => check_ratio(name, "oversized_ratio", oversized_ratio)?,
+                WorkloadConfig::Equivocation { ratio, .. } => check_ratio(name, "ratio", ratio)?,
+                WorkloadConfig::CustomCoin {
+                    ring_size: Some(0), ..
+                } => bail!("ring_size of workload custom_coin must be at least 1"),
+                WorkloadConfig::InsufficientGas { ratio, margin, .. } => {
+                    check_ratio(name, "ratio", ratio)?;
+                    check_ratio(name, "margin", margin)?;
+                }
+                WorkloadConfig::SignatureSchemes { mix: Some(mix), .. } => {
+                    SchemeMix::from_str(mix)
+                        .context("Invalid mix of workload signature_schemes")?;
+                }
+                WorkloadConfig::PureInput {
+                    sizes: Some(sizes), ..
+                } => {
+                    PureInputSizes::from_str(sizes)
+                        .context("Invalid sizes of workload pure_input")?;
+                }
+                WorkloadConfig::VectorSum {
+                    lengths: Some(lengths),
+                    ..
+                } => {
+                    VectorLengths::from_str(lengths)
+                        .context("Invalid lengths of workload vector_sum")?;
+                }
+                WorkloadConfig::ReadWrite { read_ratio, .. } => {
+                    check_ratio(name, "read_ratio", read_ratio)?
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the workload mix of `run_spec` with the one of the file
+    pub fn apply(&self, run_spec: &mut RunSpec) {
+        match run_spec {
+            RunSpec::Bench {
+                target_qps,
+                num_workers,
+                in_flight_ratio,
+                shared_counter,
+                transfer_object,
+                delegation,
+                batch_payment,
+                adversarial,
+                governance,
+                validator_lifecycle,
+                hotspot,
+                gas_smash,
+                size_limit,
+                system_state_read,
+                move_call,
+                equivocation,
+                clock,
+                object_size,
+                gas_station,
+                ping_pong,
+                upgrade,
+                type_args,
+                stake_withdraw,
+                signature_schemes,
+                move_abort,
+                insufficient_gas,
+                custom_coin,
+                pure_input,
+                freeze,
+                vector_sum,
+                read_write,
+                adversarial_cfg,
+                batch_payment_size,
+                shared_counter_hotness_factor,
+                shared_counter_distinct_addresses,
+                governance_proposal_ratio,
+                delegation_stake_amounts,
+                delegation_seed,
+                delegation_track_committee,
+                delegation_refetch_ratio,
+                delegation_check_events,
+                delegation_min_success_rate,
+                hotspot_object,
+                gas_smash_coins_per_tx,
+                size_limit_offset_bytes,
+                size_limit_oversized_ratio,
+                move_call_target,
+                move_call_type_args,
+                move_call_args,
+                move_call_setup_function,
+                equivocation_ratio,
+                object_size_bytes,
+                gas_station_senders_per_sponsor,
+                gas_station_coins_per_sponsor,
+                type_args_count,
+                type_args_depth,
+                signature_schemes_mix,
+                move_abort_code,
+                insufficient_gas_ratio,
+                insufficient_gas_margin,
+                custom_coin_mint_amount,
+                custom_coin_ring_size,
+                pure_input_sizes,
+                freeze_objects,
+                vector_sum_lengths,
+                read_write_read_ratio,
+            } => {
+                set(target_qps, &self.target_qps);
+                set(num_workers, &self.num_workers);
+                set(in_flight_ratio, &self.in_flight_ratio);
+                for weight in [
+                    &mut *shared_counter,
+                    &mut *transfer_object,
+                    &mut *delegation,
+                    &mut *batch_payment,
+                    &mut *adversarial,
+                    &mut *governance,
+                    &mut *validator_lifecycle,
+                    &mut *hotspot,
+                    &mut *gas_smash,
+                    &mut *size_limit,
+                    &mut *system_state_read,
+                    &mut *move_call,
+                    &mut *equivocation,
+                    &mut *clock,
+                    &mut *object_size,
+                    &mut *gas_station,
+                    &mut *ping_pong,
+                    &mut *upgrade,
+                    &mut *type_args,
+                    &mut *stake_withdraw,
+                    &mut *signature_schemes,
+                    &mut *move_abort,
+                    &mut *insufficient_gas,
+                    &mut *custom_coin,
+                    &mut *pure_input,
+                    &mut *freeze,
+                    &mut *vector_sum,
+                    &mut *read_write,
+                ] {
+                    *weight = 0;
+                }
+                for workload in &self.workloads {
+                    match workload {
+                        WorkloadConfig::SharedCounter {
+                            weight,
+                            hotness_factor,
+                            distinct_addresses,
+                        } => {
+                            *shared_counter = *weight;
+                            set(shared_counter_hotness_factor, hotness_factor);
+                            if distinct_addresses.is_some() {
+                                *shared_counter_distinct_addresses = *distinct_addresses;
+                            }
+                        }
+                        WorkloadConfig::TransferObject { weight } => *transfer_object = *weight,
+                        WorkloadConfig::Delegation {
+                            weight,
+                            stake_amounts,
+                            seed,
+                            track_committee,
+                            refetch_ratio,
+                            check_events,
+                            min_success_rate,
+                        } => {
+                            *delegation = *weight;
+                            set(delegation_stake_amounts, stake_amounts);
+                            if seed.is_some() {
+                                *delegation_seed = *seed;
+                            }
+                            set(delegation_track_committee, track_committee);
+                            set(delegation_refetch_ratio, refetch_ratio);
+                            set(delegation_check_events, check_events);
+                            if min_success_rate.is_some() {
+                                *delegation_min_success_rate = *min_success_rate;
+                            }
+                        }
+                        WorkloadConfig::BatchPayment { weight, size } => {
+                            *batch_payment = *weight;
+                            set(batch_payment_size, size);
+                        }
+                        WorkloadConfig::Adversarial { weight, cfg } => {
+                            *adversarial = *weight;
+                            set(adversarial_cfg, cfg);
+                        }
+                        WorkloadConfig::Governance {
+                            weight,
+                            proposal_ratio,
+                        } => {
+                            *governance = *weight;
+                            set(governance_proposal_ratio, proposal_ratio);
+                        }
+                        WorkloadConfig::ValidatorLifecycle { weight } => {
+                            *validator_lifecycle = *weight
+                        }
+                        WorkloadConfig::Hotspot { weight, object } => {
+                            *hotspot = *weight;
+                            if object.is_some() {
+                                *hotspot_object = *object;
+                            }
+                        }
+                        WorkloadConfig::GasSmash {
+                            weight,
+                            coins_per_tx,
+                        } => {
+                            *gas_smash = *weight;
+                            set(gas_smash_coins_per_tx, coins_per_tx);
+                        }
+                        WorkloadConfig::SizeLimit {
+                            weight,
+                            offset_bytes,
+                            oversized_ratio,
+                        } => {
+                            *size_limit = *weight;
+                            set(size_limit_offset_bytes, offset_bytes);
+                            set(size_limit_oversized_ratio, oversized_ratio);
+                        }
+                        WorkloadConfig::SystemStateRead { weight } => *system_state_read = *weight,
+                        WorkloadConfig::MoveCall {
+                            weight,
+                            target,
+                            type_args,
+                            args,
+                            setup_function,
+                        } => {
+                            *move_call = *weight;
+                            if target.is_some() {
+                                *move_call_target = target.clone();
+                            }
+                            set(move_call_type_args, type_args);
+                            set(move_call_args, args);
+                            if setup_function.is_some() {
+                                *move_call_setup_function = setup_function.clone();
+                            }
+                        }
+                        WorkloadConfig::Equivocation { weight, ratio } => {
+                            *equivocation = *weight;
+                            set(equivocation_ratio, ratio);
+                        }
+                        WorkloadConfig::Clock { weight } => *clock = *weight,
+                        WorkloadConfig::ObjectSize { weight, bytes } => {
+                            *object_size = *weight;
+                            set(object_size_bytes, bytes);
+                        }
+                        WorkloadConfig::GasStation {
+                            weight,
+                            senders_per_sponsor,
+                            coins_per_sponsor,
+                        } => {
+                            *gas_station = *weight;
+                            set(gas_station_senders_per_sponsor, senders_per_sponsor);
+                            set(gas_station_coins_per_sponsor, coins_per_sponsor);
+                        }
+                        WorkloadConfig::PingPong { weight } => *ping_pong = *weight,
+                        WorkloadConfig::Upgrade { weight } => *upgrade = *weight,
+                        WorkloadConfig::TypeArgs {
+                            weight,
+                            count,
+                            depth,
+                        } => {
+                            *type_args = *weight;
+                            set(type_args_count, count);
+                            set(type_args_depth, depth);
+                        }
+                        WorkloadConfig::StakeWithdraw { weight } => *stake_withdraw = *weight,
+                        WorkloadConfig::SignatureSchemes { weight, mix } => {
+                            *signature_schemes = *weight;
+                            set(signature_schemes_mix, mix);
+                        }
+                        WorkloadConfig::MoveAbort { weight, code } => {
+                            *move_abort = *weight;
+                            set(move_abort_code, code);
+                        }
+                        WorkloadConfig::InsufficientGas {
+                            weight,
+                            ratio,
+                            margin,
+                        } => {
+                            *insufficient_gas = *weight;
+                            set(insufficient_gas_ratio, ratio);
+                            set(insufficient_gas_margin, margin);
+                        }
+                        WorkloadConfig::CustomCoin {
+                            weight,
+                            mint_amount,
+                            ring_size,
+                        } => {
+                            *custom_coin = *weight;
+                            set(custom_coin_mint_amount, mint_amount);
+                            set(custom_coin_ring_size, ring_size);
+                        }
+                        WorkloadConfig::PureInput { weight, sizes } => {
+                            *pure_input = *weight;
+                            set(pure_input_sizes, sizes);
+                        }
+                        WorkloadConfig::Freeze { weight, objects } => {
+                            *freeze = *weight;
+                            set(freeze_objects, objects);
+                        }
+                        WorkloadConfig::VectorSum { weight, lengths } => {
+                            *vector_sum = *weight;
+                            set(vector_sum_lengths, lengths);
+                        }
+                        WorkloadConfig::ReadWrite { weight, read_ratio } => {
+                            *read_write = *weight;
+                            set(read_write_read_ratio, read_ratio);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -5,16 +5,172 @@ use crate::bank::BenchmarkBank;
 use crate::options::{Opts, RunSpec};
 use crate::system_state_observer::SystemStateObserver;
 use crate::workloads::batch_payment::BatchPaymentWorkloadBuilder;
-use crate::workloads::delegation::DelegationWorkloadBuilder;
+use crate::workloads::clock::ClockWorkloadBuilder;
+use crate::workloads::custom_coin::CustomCoinWorkloadBuilder;
+use crate::workloads::delegation::{
+    DelegationWorkloadBuilder, StakeAmountDistribution, ValidatorSelection,
+};
+use crate::workloads::equivocation::EquivocationWorkloadBuilder;
+use crate::workloads::freeze::FreezeWorkloadBuilder;
+use crate::workloads::gas_smash::GasSmashWorkloadBuilder;
+use crate::workloads::gas_station::GasStationWorkloadBuilder;
+use crate::workloads::governance::GovernanceWorkloadBuilder;
+use crate::workloads::hotspot::HotspotWorkloadBuilder;
+use crate::workloads::insufficient_gas::InsufficientGasWorkloadBuilder;
+use crate::workloads::move_abort::MoveAbortWorkloadBuilder;
+use crate::workloads::move_call::{MoveCallTemplate, MoveCallWorkloadBuilder};
+use crate::workloads::object_size::ObjectSizeWorkloadBuilder;
+use crate::workloads::ping_pong::PingPongWorkloadBuilder;
+use crate::workloads::pure_input::{PureInputSizes, PureInputWorkloadBuilder};
+use crate::workloads::read_write::ReadWriteWorkloadBuilder;
 use crate::workloads::shared_counter::SharedCounterWorkloadBuilder;
+use crate::workloads::signature_schemes::{SchemeMix, SignatureSchemesWorkloadBuilder};
+use crate::workloads::size_limit::SizeLimitWorkloadBuilder;
+use crate::workloads::stake_withdraw::StakeWithdrawWorkloadBuilder;
+use crate::workloads::system_state_read::SystemStateReadWorkloadBuilder;
 use crate::workloads::transfer_object::TransferObjectWorkloadBuilder;
+use crate::workloads::type_args::{TypeArgsTemplate, TypeArgsWorkloadBuilder, MAX_TYPE_ARGS};
+use crate::workloads::upgrade::UpgradeWorkloadBuilder;
+use crate::workloads::validator_lifecycle::ValidatorLifecycleWorkloadBuilder;
+use crate::workloads::vector_sum::{VectorLengths, VectorSumWorkloadBuilder};
+use crate::workloads::workload_config_file::WorkloadConfigFile;
 use crate::workloads::WorkloadInfo;
-use anyhow::Result;
+use anyhow::{bail, Result};
 use std::str::FromStr;
 use std::sync::Arc;
+use sui_types::base_types::ObjectID;
+use tracing::warn;
 
 use super::adversarial::{AdversarialPayloadCfg, AdversarialWorkloadBuilder};
 
+/// Ceiling on the operations in flight across all workloads, `target_qps * in_flight_ratio`.
+/// Every operation in flight is a payload with its own funded coins held in memory, so the ratio
+/// is lowered to stay under it.
+const MAX_OPS_IN_FLIGHT: u64 = 100_000;
+
+/// `in_flight_ratio` lowered so that `target_qps * in_flight_ratio` stays under
+/// `MAX_OPS_IN_FLIGHT`, keeping at least one operation in flight per workload at any target qps
+fn clamp_in_flight_ratio(target_qps: u64, in_flight_ratio: u64) -> Result<u64> {
+    if in_flight_ratio == 0 {
+        bail!("--in-flight-ratio must be at least 1, no workload would have operations in flight");
+    }
+    let max_ops = target_qps.saturating_mul(in_flight_ratio);
+    if max_ops <= MAX_OPS_IN_FLIGHT {
+        return Ok(in_flight_ratio);
+    }
+    let clamped = (MAX_OPS_IN_FLIGHT / target_qps).max(1);
+    warn!(
+        "--target-qps {} with --in-flight-ratio {} would keep {} operations in flight, \
+        more than the maximum of {}: lowering --in-flight-ratio to {}",
+        target_qps, in_flight_ratio, max_ops, MAX_OPS_IN_FLIGHT, clamped
+    );
+    Ok(clamped)
+}
+
+/// Relative weights of the workloads in a benchmark run, a weight of 0 disables the workload
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WorkloadWeights {
+    pub shared_counter: u32,
+    pub transfer_object: u32,
+    pub delegation: u32,
+    pub batch_payment: u32,
+    pub adversarial: u32,
+    pub governance: u32,
+    pub validator_lifecycle: u32,
+    pub hotspot: u32,
+    pub gas_smash: u32,
+    pub size_limit: u32,
+    pub system_state_read: u32,
+    pub move_call: u32,
+    pub equivocation: u32,
+    pub clock: u32,
+    pub object_size: u32,
+    pub gas_station: u32,
+    pub ping_pong: u32,
+    pub upgrade: u32,
+    pub type_args: u32,
+    pub stake_withdraw: u32,
+    pub signature_schemes: u32,
+    pub move_abort: u32,
+    pub insufficient_gas: u32,
+    pub custom_coin: u32,
+    pub pure_input: u32,
+    pub freeze: u32,
+    pub vector_sum: u32,
+    pub read_write: u32,
+}
+
+impl WorkloadWeights {
+    pub fn total(&self) -> u32 {
+        self.shared_counter
+            + self.transfer_object
+            + self.delegation
+            + self.batch_payment
+            + self.adversarial
+            + self.governance
+            + self.validator_lifecycle
+            + self.hotspot
+            + self.gas_smash
+            + self.size_limit
+            + self.system_state_read
+            + self.move_call
+            + self.equivocation
+            + self.clock
+            + self.object_size
+            + self.gas_station
+            + self.ping_pong
+            + self.upgrade
+            + self.type_args
+            + self.stake_withdraw
+            + self.signature_schemes
+            + self.move_abort
+            + self.insufficient_gas
+            + self.custom_coin
+            + self.pure_input
+            + self.freeze
+            + self.vector_sum
+            + self.read_write
+    }
+}
+
+/// Parameters of the workloads in a benchmark run, each only used by the workload it is named
+/// after
+#[derive(Debug, Clone)]
+pub struct WorkloadOptions {
+    pub num_transfer_accounts: u64,
+    pub adversarial_cfg: AdversarialPayloadCfg,
+    pub batch_payment_size: u32,
+    pub shared_counter_hotness_factor: u32,
+    pub shared_counter_distinct_addresses: Option<u64>,
+    pub governance_proposal_ratio: u32,
+    pub delegation_stake_amounts: StakeAmountDistribution,
+    pub delegation_seed: Option<u64>,
+    pub delegation_validator_selection: ValidatorSelection,
+    pub delegation_refetch_ratio: f32,
+    pub delegation_check_events: bool,
+    pub delegation_min_success_rate: Option<f32>,
+    pub hotspot_object: Option<ObjectID>,
+    pub gas_smash_coins_per_tx: u32,
+    pub size_limit_offset_bytes: u64,
+    pub size_limit_oversized_ratio: f32,
+    pub move_call_template: Option<MoveCallTemplate>,
+    pub equivocation_ratio: f32,
+    pub object_size_bytes: u64,
+    pub gas_station_senders_per_sponsor: u64,
+    pub gas_station_coins_per_sponsor: u64,
+    pub type_args_template: TypeArgsTemplate,
+    pub signature_scheme_mix: SchemeMix,
+    pub move_abort_code: u64,
+    pub insufficient_gas_ratio: f32,
+    pub insufficient_gas_margin: f32,
+    pub custom_coin_mint_amount: u64,
+    pub custom_coin_ring_size: u64,
+    pub pure_input_sizes: PureInputSizes,
+    pub freeze_objects: u64,
+    pub vector_sum_lengths: VectorLengths,
+    pub read_write_read_ratio: f32,
+}
+
 pub struct WorkloadConfiguration;
 
 impl WorkloadConfiguration {
@@ -23,7 +179,20 @@ impl WorkloadConfiguration {
         opts: &Opts,
         system_state_observer: Arc<SystemStateObserver>,
     ) -> Result<Vec<WorkloadInfo>> {
-        match opts.run_spec.clone() {
+        let bank = match &opts.gas_keystore {
+            Some(path) if !opts.insecure_plaintext_keystore => bail!(
+                "Gas keystore {} would hold unencrypted private keys, pass \
+                --insecure-plaintext-keystore to use it",
+                path.display()
+            ),
+            Some(path) => bank.with_gas_keystore(path.clone()),
+            None => bank,
+        };
+        let mut run_spec = opts.run_spec.clone();
+        if let Some(path) = &opts.workload_config {
+            WorkloadConfigFile::load(path)?.apply(&mut run_spec);
+        }
+        match run_spec {
             RunSpec::Bench {
                 target_qps,
                 num_workers,
@@ -33,22 +202,152 @@ impl WorkloadConfiguration {
                 delegation,
                 batch_payment,
                 adversarial,
+                governance,
+                validator_lifecycle,
+                hotspot,
+                gas_smash,
+                size_limit,
+                system_state_read,
+                move_call,
+                equivocation,
+                clock,
+                object_size,
+                gas_station,
+                ping_pong,
+                upgrade,
+                type_args,
+                stake_withdraw,
+                signature_schemes,
+                move_abort,
+                insufficient_gas,
+                custom_coin,
+                pure_input,
+                freeze,
+                vector_sum,
+                read_write,
                 adversarial_cfg,
                 batch_payment_size,
                 shared_counter_hotness_factor,
+                shared_counter_distinct_addresses,
+                governance_proposal_ratio,
+                delegation_stake_amounts,
+                delegation_seed,
+                delegation_track_committee,
+                delegation_refetch_ratio,
+                delegation_check_events,
+                delegation_min_success_rate,
+                hotspot_object,
+                gas_smash_coins_per_tx,
+                size_limit_offset_bytes,
+                size_limit_oversized_ratio,
+                move_call_target,
+                move_call_type_args,
+                move_call_args,
+                move_call_setup_function,
+                equivocation_ratio,
+                object_size_bytes,
+                gas_station_senders_per_sponsor,
+                gas_station_coins_per_sponsor,
+                type_args_count,
+                type_args_depth,
+                signature_schemes_mix,
+                move_abort_code,
+                insufficient_gas_ratio,
+                insufficient_gas_margin,
+                custom_coin_mint_amount,
+                custom_coin_ring_size,
+                pure_input_sizes,
+                freeze_objects,
+                vector_sum_lengths,
+                read_write_read_ratio,
                 ..
             } => {
+                // Validate the template up front rather than fail in the middle of the run
+                let move_call_template = move_call_target
+                    .map(|target| {
+                        MoveCallTemplate::new(
+                            &target,
+                            &move_call_type_args,
+                            &move_call_args,
+                            move_call_setup_function.as_deref(),
+                        )
+                    })
+                    .transpose()?;
                 Self::build_workloads(
                     num_workers,
-                    opts.num_transfer_accounts,
-                    shared_counter,
-                    transfer_object,
-                    delegation,
-                    batch_payment,
-                    adversarial,
-                    AdversarialPayloadCfg::from_str(&adversarial_cfg).unwrap(),
-                    batch_payment_size,
-                    shared_counter_hotness_factor,
+                    WorkloadWeights {
+                        shared_counter,
+                        transfer_object,
+                        delegation,
+                        batch_payment,
+                        adversarial,
+                        governance,
+                        validator_lifecycle,
+                        hotspot,
+                        gas_smash,
+                        size_limit,
+                        system_state_read,
+                        move_call,
+                        equivocation,
+                        clock,
+                        object_size,
+                        gas_station,
+                        ping_pong,
+                        upgrade,
+                        type_args,
+                        stake_withdraw,
+                        signature_schemes,
+                        move_abort,
+                        insufficient_gas,
+                        custom_coin,
+                        pure_input,
+                        freeze,
+                        vector_sum,
+                        read_write,
+                    },
+                    WorkloadOptions {
+                        num_transfer_accounts: opts.num_transfer_accounts,
+                        adversarial_cfg: AdversarialPayloadCfg::from_str(&adversarial_cfg).unwrap(),
+                        batch_payment_size,
+                        shared_counter_hotness_factor,
+                        shared_counter_distinct_addresses,
+                        governance_proposal_ratio,
+                        delegation_stake_amounts: StakeAmountDistribution::from_str(
+                            &delegation_stake_amounts,
+                        )?,
+                        delegation_seed,
+                        delegation_validator_selection: if delegation_track_committee {
+                            ValidatorSelection::Live
+                        } else {
+                            ValidatorSelection::Pinned
+                        },
+                        delegation_refetch_ratio,
+                        delegation_check_events,
+                        delegation_min_success_rate,
+                        hotspot_object,
+                        gas_smash_coins_per_tx,
+                        size_limit_offset_bytes,
+                        size_limit_oversized_ratio,
+                        move_call_template,
+                        equivocation_ratio,
+                        object_size_bytes,
+                        gas_station_senders_per_sponsor,
+                        gas_station_coins_per_sponsor,
+                        type_args_template: TypeArgsTemplate {
+                            count: type_args_count,
+                            depth: type_args_depth,
+                        },
+                        signature_scheme_mix: SchemeMix::from_str(&signature_schemes_mix)?,
+                        move_abort_code,
+                        insufficient_gas_ratio,
+                        insufficient_gas_margin,
+                        custom_coin_mint_amount,
+                        custom_coin_ring_size,
+                        pure_input_sizes: PureInputSizes::from_str(&pure_input_sizes)?,
+                        freeze_objects,
+                        vector_sum_lengths: VectorLengths::from_str(&vector_sum_lengths)?,
+                        read_write_read_ratio,
+                    },
                     target_qps,
                     in_flight_ratio,
                     bank,
@@ -62,26 +361,96 @@ impl WorkloadConfiguration {
 
     pub async fn build_workloads(
         num_workers: u64,
-        num_transfer_accounts: u64,
-        shared_counter_weight: u32,
-        transfer_object_weight: u32,
-        delegation_weight: u32,
-        batch_payment_weight: u32,
-        adversarial_weight: u32,
-        adversarial_cfg: AdversarialPayloadCfg,
-        batch_payment_size: u32,
-        shared_counter_hotness_factor: u32,
+        weights: WorkloadWeights,
+        options: WorkloadOptions,
         target_qps: u64,
         in_flight_ratio: u64,
         mut bank: BenchmarkBank,
         system_state_observer: Arc<SystemStateObserver>,
         chunk_size: u64,
     ) -> Result<Vec<WorkloadInfo>> {
-        let total_weight = shared_counter_weight
-            + transfer_object_weight
-            + delegation_weight
-            + batch_payment_weight
-            + adversarial_weight;
+        let total_weight = weights.total();
+        let WorkloadWeights {
+            shared_counter: shared_counter_weight,
+            transfer_object: transfer_object_weight,
+            delegation: delegation_weight,
+            batch_payment: batch_payment_weight,
+            adversarial: adversarial_weight,
+            governance: governance_weight,
+            validator_lifecycle: validator_lifecycle_weight,
+            hotspot: hotspot_weight,
+            gas_smash: gas_smash_weight,
+            size_limit: size_limit_weight,
+            system_state_read: system_state_read_weight,
+            move_call: move_call_weight,
+            equivocation: equivocation_weight,
+            clock: clock_weight,
+            object_size: object_size_weight,
+            gas_station: gas_station_weight,
+            ping_pong: ping_pong_weight,
+            upgrade: upgrade_weight,
+            type_args: type_args_weight,
+            stake_withdraw: stake_withdraw_weight,
+            signature_schemes: signature_schemes_weight,
+            move_abort: move_abort_weight,
+            insufficient_gas: insufficient_gas_weight,
+            custom_coin: custom_coin_weight,
+            pure_input: pure_input_weight,
+            freeze: freeze_weight,
+            vector_sum: vector_sum_weight,
+            read_write: read_write_weight,
+        } = weights;
+        let WorkloadOptions {
+            num_transfer_accounts,
+            adversarial_cfg,
+            batch_payment_size,
+            shared_counter_hotness_factor,
+            shared_counter_distinct_addresses,
+            governance_proposal_ratio,
+            delegation_stake_amounts,
+            delegation_seed,
+            delegation_validator_selection,
+            delegation_refetch_ratio,
+            delegation_check_events,
+            delegation_min_success_rate,
+            hotspot_object,
+            gas_smash_coins_per_tx,
+            size_limit_offset_bytes,
+            size_limit_oversized_ratio,
+            move_call_template,
+            equivocation_ratio,
+            object_size_bytes,
+            gas_station_senders_per_sponsor,
+            gas_station_coins_per_sponsor,
+            type_args_template,
+            signature_scheme_mix,
+            move_abort_code,
+            insufficient_gas_ratio,
+            insufficient_gas_margin,
+            custom_coin_mint_amount,
+            custom_coin_ring_size,
+            pure_input_sizes,
+            freeze_objects,
+            vector_sum_lengths,
+            read_write_read_ratio,
+        } = options;
+        if hotspot_weight > 0 && hotspot_object.is_none() {
+            bail!("--hotspot-object is required to run the hotspot workload");
+        }
+        if move_call_weight > 0 && move_call_template.is_none() {
+            bail!("--move-call-target is required to run the move call workload");
+        }
+        if type_args_weight > 0 && !(1..=MAX_TYPE_ARGS).contains(&type_args_template.count) {
+            bail!(
+                "--type-args-count must be between 1 and {}, got {}",
+                MAX_TYPE_ARGS,
+                type_args_template.count
+            );
+        }
+        if custom_coin_weight > 0 && custom_coin_ring_size == 0 {
+            bail!("--custom-coin-ring-size must be at least 1");
+        }
+        let in_flight_ratio = clamp_in_flight_ratio(target_qps, in_flight_ratio)?;
         let mut workload_builders = vec![];
         let shared_workload = SharedCounterWorkloadBuilder::from(
             shared_counter_weight as f32 / total_weight as f32,
@@ -89,6 +458,7 @@ impl WorkloadConfiguration {
             num_workers,
             in_flight_ratio,
             shared_counter_hotness_factor,
+            shared_counter_distinct_addresses,
         );
         workload_builders.push(shared_workload);
         let transfer_workload = TransferObjectWorkloadBuilder::from(
@@ -99,13 +469,23 @@ impl WorkloadConfiguration {
             num_transfer_accounts,
         );
         workload_builders.push(transfer_workload);
-        let delegation_workload = DelegationWorkloadBuilder::from(
+        match DelegationWorkloadBuilder::from(
             delegation_weight as f32 / total_weight as f32,
             target_qps,
             num_workers,
             in_flight_ratio,
-        );
-        workload_builders.push(delegation_workload);
+            delegation_stake_amounts,
+            delegation_seed,
+            delegation_validator_selection,
+            delegation_refetch_ratio,
+            delegation_check_events,
+            delegation_min_success_rate,
+        ) {
+            Ok(delegation_workload) => workload_builders.push(Some(delegation_workload)),
+            // A zero weight disables the workload on purpose, only explain other cases
+            Err(e) if delegation_weight > 0 => warn!("Not running delegation workload: {}", e),
+            Err(_) => (),
+        }
         let batch_payment_workload = BatchPaymentWorkloadBuilder::from(
             batch_payment_weight as f32 / total_weight as f32,
             target_qps,
@@ -122,6 +502,192 @@ impl WorkloadConfiguration {
             adversarial_cfg,
         );
         workload_builders.push(adversarial_workload);
+        let governance_workload = GovernanceWorkloadBuilder::from(
+            governance_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+            governance_proposal_ratio,
+        );
+        workload_builders.push(governance_workload);
+        let validator_lifecycle_workload = ValidatorLifecycleWorkloadBuilder::from(
+            validator_lifecycle_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+        );
+        workload_builders.push(validator_lifecycle_workload);
+        if let Some(hotspot_object) = hotspot_object {
+            let hotspot_workload = HotspotWorkloadBuilder::from(
+                hotspot_weight as f32 / total_weight as f32,
+                target_qps,
+                num_workers,
+                in_flight_ratio,
+                hotspot_object,
+            );
+            workload_builders.push(hotspot_workload);
+        }
+        let gas_smash_workload = GasSmashWorkloadBuilder::from(
+            gas_smash_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+            gas_smash_coins_per_tx,
+        );
+        workload_builders.push(gas_smash_workload);
+        let size_limit_workload = SizeLimitWorkloadBuilder::from(
+            size_limit_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+            size_limit_offset_bytes,
+            size_limit_oversized_ratio,
+        );
+        workload_builders.push(size_limit_workload);
+        let system_state_read_workload = SystemStateReadWorkloadBuilder::from(
+            system_state_read_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+        );
+        workload_builders.push(system_state_read_workload);
+        if let Some(move_call_template) = move_call_template {
+            let move_call_workload = MoveCallWorkloadBuilder::from(
+                move_call_weight as f32 / total_weight as f32,
+                target_qps,
+                num_workers,
+                in_flight_ratio,
+                move_call_template,
+            );
+            workload_builders.push(move_call_workload);
+        }
+        let equivocation_workload = EquivocationWorkloadBuilder::from(
+            equivocation_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+            equivocation_ratio,
+        );
+        workload_builders.push(equivocation_workload);
+        let clock_workload = ClockWorkloadBuilder::from(
+            clock_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+        );
+        workload_builders.push(clock_workload);
+        let object_size_workload = ObjectSizeWorkloadBuilder::from(
+            object_size_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+            object_size_bytes,
+        );
+        workload_builders.push(object_size_workload);
+        let gas_station_workload = GasStationWorkloadBuilder::from(
+            gas_station_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+            gas_station_senders_per_sponsor,
+            gas_station_coins_per_sponsor,
+        );
+        workload_builders.push(gas_station_workload);
+        let ping_pong_workload = PingPongWorkloadBuilder::from(
+            ping_pong_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+        );
+        workload_builders.push(ping_pong_workload);
+        let upgrade_workload = UpgradeWorkloadBuilder::from(
+            upgrade_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+        );
+        workload_builders.push(upgrade_workload);
+        let type_args_workload = TypeArgsWorkloadBuilder::from(
+            type_args_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+            type_args_template,
+        );
+        workload_builders.push(type_args_workload);
+        let stake_withdraw_workload = StakeWithdrawWorkloadBuilder::from(
+            stake_withdraw_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+        );
+        workload_builders.push(stake_withdraw_workload);
+        let signature_schemes_workload = SignatureSchemesWorkloadBuilder::from(
+            signature_schemes_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+            signature_scheme_mix,
+        );
+        workload_builders.push(signature_schemes_workload);
+        let move_abort_workload = MoveAbortWorkloadBuilder::from(
+            move_abort_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+            move_abort_code,
+        );
+        workload_builders.push(move_abort_workload);
+        let insufficient_gas_workload = InsufficientGasWorkloadBuilder::from(
+            insufficient_gas_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+            insufficient_gas_ratio,
+            insufficient_gas_margin,
+        );
+        workload_builders.push(insufficient_gas_workload);
+        let custom_coin_workload = CustomCoinWorkloadBuilder::from(
+            custom_coin_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+            custom_coin_mint_amount,
+            custom_coin_ring_size,
+        );
+        workload_builders.push(custom_coin_workload);
+        let pure_input_workload = PureInputWorkloadBuilder::from(
+            pure_input_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+            pure_input_sizes,
+        );
+        workload_builders.push(pure_input_workload);
+        let freeze_workload = FreezeWorkloadBuilder::from(
+            freeze_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+            freeze_objects,
+        );
+        workload_builders.push(freeze_workload);
+        let vector_sum_workload = VectorSumWorkloadBuilder::from(
+            vector_sum_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+            vector_sum_lengths,
+        );
+        workload_builders.push(vector_sum_workload);
+        let read_write_workload = ReadWriteWorkloadBuilder::from(
+            read_write_weight as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+            read_write_read_ratio,
+        );
+        workload_builders.push(read_write_workload);
         let (workload_params, workload_builders): (Vec<_>, Vec<_>) = workload_builders
             .into_iter()
             .flatten()
@@ -134,7 +700,7 @@ impl WorkloadConfiguration {
         for workload in workloads.iter_mut() {
             workload
                 .init(bank.proxy.clone(), system_state_observer.clone())
-                .await;
+                .await?;
         }
         Ok(workloads
             .into_iter()
@@ -146,3 +712,24 @@ impl WorkloadConfiguration {
             .collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp_in_flight_ratio() {
+        assert!(clamp_in_flight_ratio(100, 0).is_err());
+        assert_eq!(clamp_in_flight_ratio(100, 5).unwrap(), 5);
+        assert_eq!(
+            clamp_in_flight_ratio(1000, MAX_OPS_IN_FLIGHT / 1000).unwrap(),
+            MAX_OPS_IN_FLIGHT / 1000
+        );
+        assert_eq!(clamp_in_flight_ratio(1000, 1000).unwrap(), 100);
+        // Overflowing products are clamped rather than wrapping around
+        assert_eq!(clamp_in_flight_ratio(1000, u64::MAX).unwrap(), 100);
+        // A target qps above the ceiling still keeps one operation in flight
+        assert_eq!(clamp_in_flight_ratio(MAX_OPS_IN_FLIGHT * 2, 3).unwrap(), 1);
+        assert_eq!(clamp_in_flight_ratio(0, 10).unwrap(), 10);
+    }
+}
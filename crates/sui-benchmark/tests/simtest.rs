@@ -11,7 +11,14 @@ mod test {
     use sui_benchmark::bank::BenchmarkBank;
     use sui_benchmark::system_state_observer::SystemStateObserver;
     use sui_benchmark::workloads::adversarial::AdversarialPayloadCfg;
-    use sui_benchmark::workloads::workload_configuration::WorkloadConfiguration;
+    use sui_benchmark::workloads::delegation::{StakeAmountDistribution, ValidatorSelection};
+    use sui_benchmark::workloads::pure_input::PureInputSizes;
+    use sui_benchmark::workloads::signature_schemes::SchemeMix;
+    use sui_benchmark::workloads::type_args::TypeArgsTemplate;
+    use sui_benchmark::workloads::vector_sum::VectorLengths;
+    use sui_benchmark::workloads::workload_configuration::{
+        WorkloadConfiguration, WorkloadOptions, WorkloadWeights,
+    };
     use sui_benchmark::{
         drivers::{bench_driver::BenchDriver, driver::Driver, Interval},
         util::get_ed25519_keypair_from_keystore,
@@ -261,32 +268,96 @@ mod test {
         let num_workers = get_var("SIM_STRESS_TEST_WORKERS", 10);
         let in_flight_ratio = get_var("SIM_STRESS_TEST_IFR", 2);
         let batch_payment_size = get_var("SIM_BATCH_PAYMENT_SIZE", 15);
-        let shared_counter_weight = 1;
-        let transfer_object_weight = 1;
         let num_transfer_accounts = 2;
-        let delegation_weight = 1;
-        let batch_payment_weight = 1;
 
         // Run random payloads at 100% load
         let adversarial_cfg = AdversarialPayloadCfg::from_str("0-1.0").unwrap();
 
-        // TODO: re-enable this when we figure out why it is causing connection errors and making
-        // tests run for ever
-        let adversarial_weight = 0;
+        let weights = WorkloadWeights {
+            shared_counter: 1,
+            transfer_object: 1,
+            delegation: 1,
+            batch_payment: 1,
+            // TODO: re-enable this when we figure out why it is causing connection errors and
+            // making tests run for ever
+            adversarial: 0,
+            governance: 1,
+            // Changing the committee membership is not meaningful for the simulated cluster
+            validator_lifecycle: 0,
+            // There is no pre-existing object to target
+            hotspot: 0,
+            gas_smash: 1,
+            // Rejected transactions would show up as errors of the run
+            size_limit: 0,
+            // Validators cannot dry run transactions
+            system_state_read: 0,
+            // There is no package to call into
+            move_call: 0,
+            // Coins locked by equivocation would show up as errors of the run
+            equivocation: 0,
+            clock: 1,
+            object_size: 1,
+            gas_station: 1,
+            ping_pong: 1,
+            upgrade: 1,
+            type_args: 1,
+            stake_withdraw: 1,
+            signature_schemes: 1,
+            move_abort: 1,
+            insufficient_gas: 1,
+            custom_coin: 1,
+            pure_input: 1,
+            freeze: 1,
+            vector_sum: 1,
+            read_write: 1,
+        };
 
         let shared_counter_hotness_factor = 50;
+        let governance_proposal_ratio = 10;
+        let delegation_stake_amounts = StakeAmountDistribution::from_str("1000000000:1").unwrap();
+        // The cluster reconfigures during the test, so follow the committee
+        let delegation_validator_selection = ValidatorSelection::Live;
 
-        let workloads = WorkloadConfiguration::build_workloads(
-            num_workers,
+        let options = WorkloadOptions {
             num_transfer_accounts,
-            shared_counter_weight,
-            transfer_object_weight,
-            delegation_weight,
-            batch_payment_weight,
-            adversarial_weight,
             adversarial_cfg,
             batch_payment_size,
             shared_counter_hotness_factor,
+            shared_counter_distinct_addresses: None,
+            governance_proposal_ratio,
+            delegation_stake_amounts,
+            delegation_seed: None,
+            delegation_validator_selection,
+            delegation_refetch_ratio: 0.1,
+            delegation_check_events: true,
+            delegation_min_success_rate: None,
+            hotspot_object: None,
+            gas_smash_coins_per_tx: 10,
+            size_limit_offset_bytes: 100,
+            size_limit_oversized_ratio: 0.5,
+            move_call_template: None,
+            equivocation_ratio: 0.5,
+            object_size_bytes: 1024,
+            gas_station_senders_per_sponsor: 10,
+            gas_station_coins_per_sponsor: 5,
+            type_args_template: TypeArgsTemplate { count: 4, depth: 2 },
+            signature_scheme_mix: SchemeMix::default(),
+            move_abort_code: 1,
+            insufficient_gas_ratio: 0.5,
+            insufficient_gas_margin: 0.5,
+            custom_coin_mint_amount: 1_000_000,
+            custom_coin_ring_size: 2,
+            pure_input_sizes: PureInputSizes::default(),
+            freeze_objects: 100,
+            vector_sum_lengths: VectorLengths::default(),
+            // Validators cannot dry run transactions, so only write
+            read_write_read_ratio: 0.0,
+        };
+
+        let workloads = WorkloadConfiguration::build_workloads(
+            num_workers,
+            weights,
+            options,
             target_qps,
             in_flight_ratio,
             bank,
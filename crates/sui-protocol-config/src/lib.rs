@@ -10,7 +10,7 @@ use tracing::{info, warn};
 
 /// The minimum and maximum protocol versions supported by this build.
 const MIN_PROTOCOL_VERSION: u64 = 1;
-const MAX_PROTOCOL_VERSION: u64 = 3;
+const MAX_PROTOCOL_VERSION: u64 = 4;
 
 // Record history of protocol version allocations here:
 //
@@ -130,6 +130,10 @@ struct FeatureFlags {
     // object runtime.
     #[serde(skip_serializing_if = "is_false")]
     loaded_child_objects_fixed: bool,
+    // If true, validators refuse to sign transactions whose signatures are over another intent
+    // than that of Sui transactions.
+    #[serde(skip_serializing_if = "is_false")]
+    reject_non_transaction_intents: bool,
 }
 
 fn is_false(b: &bool) -> bool {
@@ -588,6 +592,10 @@ impl ProtocolConfig {
     pub fn loaded_child_objects_fixed(&self) -> bool {
         self.feature_flags.loaded_child_objects_fixed
     }
+
+    pub fn reject_non_transaction_intents(&self) -> bool {
+        self.feature_flags.reject_non_transaction_intents
+    }
 }
 
 // getters
@@ -1541,6 +1549,11 @@ impl ProtocolConfig {
                 cfg.feature_flags.package_upgrades = true;
                 cfg
             }
+            4 => {
+                let mut cfg = Self::get_for_version_impl(version - 1);
+                cfg.feature_flags.reject_non_transaction_intents = true;
+                cfg
+            }
             // Use this template when making changes:
             //
             //     // modify an existing constant.
@@ -0,0 +1,80 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#[macro_use]
+extern crate criterion;
+
+use rand::{rngs::StdRng, SeedableRng};
+use shared_crypto::intent::{Intent, IntentMessage, PersonalMessage};
+use sui_types::base_types::SuiAddress;
+use sui_types::crypto::{get_key_pair_from_rng, Signature, SuiKeyPair};
+use sui_types::multisig::{MultiSig, MultiSigPublicKey};
+use sui_types::signature::AuthenticatorTrait;
+
+use criterion::{Criterion, Throughput};
+
+/// One keypair of every signature scheme of user addresses
+fn keys() -> Vec<(&'static str, SuiKeyPair)> {
+    let mut seed = StdRng::from_seed([0; 32]);
+    vec![
+        (
+            "ed25519",
+            SuiKeyPair::Ed25519(get_key_pair_from_rng(&mut seed).1),
+        ),
+        (
+            "secp256k1",
+            SuiKeyPair::Secp256k1(get_key_pair_from_rng(&mut seed).1),
+        ),
+        (
+            "secp256r1",
+            SuiKeyPair::Secp256r1(get_key_pair_from_rng(&mut seed).1),
+        ),
+    ]
+}
+
+fn signature_benchmark(c: &mut Criterion) {
+    let keys = keys();
+    let msg = IntentMessage::new(
+        Intent::sui_transaction(),
+        PersonalMessage {
+            message: "Hello".as_bytes().to_vec(),
+        },
+    );
+
+    let mut group = c.benchmark_group("verify_secure_generic");
+    // Reported as verifications per second
+    group.throughput(Throughput::Elements(1));
+    for (scheme, kp) in &keys {
+        let signature = Signature::new_secure(&msg, kp);
+        let address = SuiAddress::from(&kp.public());
+        signature.verify_secure_generic(&msg, address).unwrap();
+        group.bench_function(*scheme, |b| {
+            b.iter(|| signature.verify_secure_generic(&msg, address))
+        });
+    }
+
+    // 2 of 3 multisig over the keys of all schemes, signed with the ed25519 and secp256k1 keys
+    let multisig_pk = MultiSigPublicKey::new(
+        keys.iter().map(|(_, kp)| kp.public()).collect(),
+        vec![1, 1, 1],
+        2,
+    )
+    .unwrap();
+    let address = SuiAddress::from(multisig_pk.clone());
+    let multisig = MultiSig::combine(
+        keys.iter()
+            .take(2)
+            .map(|(_, kp)| Signature::new_secure(&msg, kp))
+            .collect(),
+        multisig_pk,
+    )
+    .unwrap();
+    multisig.verify_secure_generic(&msg, address).unwrap();
+    group.bench_function("multisig_2_of_3", |b| {
+        b.iter(|| multisig.verify_secure_generic(&msg, address))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, signature_benchmark);
+criterion_main!(benches);
@@ -74,6 +74,27 @@ pub type NetworkPrivateKey = Ed25519PrivateKey;
 
 pub type DefaultHash = Blake2b256;
 
+/// Digest of the BCS bytes of an [struct IntentMessage], which is what user signatures commit to.
+/// It can only be computed from the message itself, so verifying a signature against the digest
+/// is equivalent to verifying it against the message. Callers verifying several signatures over
+/// the same message can hash it once and share the digest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IntentMessageDigest([u8; 32]);
+
+impl IntentMessageDigest {
+    pub fn new<T: Serialize>(value: &IntentMessage<T>) -> Self {
+        let mut hasher = DefaultHash::default();
+        hasher.update(&bcs::to_bytes(&value).expect("Message serialization should not fail"));
+        Self(hasher.finalize().digest)
+    }
+}
+
+impl AsRef<[u8]> for IntentMessageDigest {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
 pub const DEFAULT_EPOCH_ID: EpochId = 0;
 
 /// Creates a proof of that the authority account address is owned by the
@@ -967,6 +988,13 @@ pub trait SuiSignature: Sized + ToFromBytes {
     fn verify_secure<T>(&self, value: &IntentMessage<T>, author: SuiAddress) -> SuiResult<()>
     where
         T: Serialize;
+
+    /// Same as `verify_secure`, for a message which was already hashed
+    fn verify_secure_prehashed(
+        &self,
+        digest: &IntentMessageDigest,
+        author: SuiAddress,
+    ) -> SuiResult<()>;
 }
 
 impl<S: SuiSignatureInner + Sized> SuiSignature for S {
@@ -990,12 +1018,16 @@ impl<S: SuiSignatureInner + Sized> SuiSignature for S {
     where
         T: Serialize,
     {
-        let mut hasher = DefaultHash::default();
-        hasher.update(&bcs::to_bytes(&value).expect("Message serialization should not fail"));
-        let digest = hasher.finalize().digest;
+        self.verify_secure_prehashed(&IntentMessageDigest::new(value), author)
+    }
 
+    fn verify_secure_prehashed(
+        &self,
+        digest: &IntentMessageDigest,
+        author: SuiAddress,
+    ) -> Result<(), SuiError> {
         let (sig, pk) = &self.get_verification_inputs(author)?;
-        pk.verify(&digest, sig)
+        pk.verify(digest.as_ref(), sig)
             .map_err(|e| SuiError::InvalidSignature {
                 error: format!("{}", e),
             })
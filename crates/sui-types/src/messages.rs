@@ -7,8 +7,8 @@ use crate::certificate_proof::CertificateProof;
 use crate::committee::{EpochId, ProtocolVersion};
 use crate::crypto::{
     default_hash, AuthoritySignInfo, AuthoritySignature, AuthorityStrongQuorumSignInfo,
-    DefaultHash, Ed25519SuiSignature, EmptySignInfo, Signature, Signer, SuiSignatureInner,
-    ToFromBytes,
+    DefaultHash, Ed25519SuiSignature, EmptySignInfo, IntentMessageDigest, Signature, Signer,
+    SuiSignatureInner, ToFromBytes,
 };
 use crate::digests::{CertificateDigest, SenderSignedDataDigest, TransactionEventsDigest};
 use crate::gas::GasCostSummary;
@@ -1759,6 +1759,25 @@ impl SenderSignedData {
         &self.inner().tx_signatures
     }
 
+    /// Signatures are verified over the intent of the data, so a signature made for another
+    /// intent scope, e.g. a personal message, signs a transaction with that intent. Validators
+    /// refuse to sign such transactions from the protocol version which rejects them, and this
+    /// is not part of `verify` so that transactions certified before then still verify.
+    pub fn check_intent(&self, protocol_config: &ProtocolConfig) -> SuiResult {
+        if !protocol_config.reject_non_transaction_intents() {
+            return Ok(());
+        }
+        fp_ensure!(
+            self.intent_message().intent == Intent::sui_transaction(),
+            SuiError::UserInputError {
+                error: UserInputError::Unsupported(
+                    "SenderSignedData must have the intent of a Sui transaction".to_string()
+                )
+            }
+        );
+        Ok(())
+    }
+
     #[cfg(test)]
     pub fn intent_message_mut_for_testing(&mut self) -> &mut IntentMessage<TransactionData> {
         &mut self.inner_mut().intent_message
@@ -1843,9 +1862,10 @@ impl Message for SenderSignedData {
             }
         }
 
-        // Verify all present signatures.
+        // Verify all present signatures, which all sign the same message.
+        let digest = IntentMessageDigest::new(self.intent_message());
         for (signer, signature) in present_sigs {
-            signature.verify_secure_generic(self.intent_message(), signer)?;
+            signature.verify_secure_generic_prehashed(&digest, signer)?;
         }
         Ok(())
     }
@@ -3,7 +3,7 @@
 
 use crate::{base_types::SuiAddress, crypto::Signature, error::SuiError, multisig::MultiSig};
 use crate::{
-    crypto::{SignatureScheme, SuiSignature},
+    crypto::{IntentMessageDigest, SignatureScheme, SuiSignature},
     serde_to_from_bytes,
 };
 pub use enum_dispatch::enum_dispatch;
@@ -26,6 +26,14 @@ pub trait AuthenticatorTrait {
     ) -> Result<(), SuiError>
     where
         T: Serialize;
+
+    /// Same as `verify_secure_generic`, for a message which was already hashed. Lets callers
+    /// verifying many signatures over the same message hash it only once.
+    fn verify_secure_generic_prehashed(
+        &self,
+        digest: &IntentMessageDigest,
+        author: SuiAddress,
+    ) -> Result<(), SuiError>;
 }
 
 /// Due to the incompatibility of [enum Signature] (which dispatches a trait that
@@ -93,4 +101,12 @@ impl AuthenticatorTrait for Signature {
     {
         self.verify_secure(value, author)
     }
+
+    fn verify_secure_generic_prehashed(
+        &self,
+        digest: &IntentMessageDigest,
+        author: SuiAddress,
+    ) -> Result<(), SuiError> {
+        self.verify_secure_prehashed(digest, author)
+    }
 }
@@ -4,16 +4,19 @@
 use fastcrypto::traits::KeyPair;
 
 use crate::{
-    base_types::{dbg_addr, ObjectID},
+    base_types::{dbg_addr, ObjectID, SuiAddress},
     crypto::{
         AccountKeyPair, AuthorityKeyPair, AuthoritySignature, Signature, SuiAuthoritySignature,
-        SuiSignature,
+        SuiKeyPair, SuiSignature,
     },
     messages::{Transaction, TransactionData},
+    multisig::{MultiSig, MultiSigPublicKey},
     object::Object,
+    signature::AuthenticatorTrait,
 };
 
 use crate::crypto::get_key_pair;
+use sui_protocol_config::{ProtocolConfig, ProtocolVersion};
 
 use shared_crypto::intent::{
     AppId, Intent, IntentMessage, IntentScope, IntentVersion, PersonalMessage,
@@ -98,3 +101,74 @@ fn test_authority_signature_intent() {
     let verification = s.verify_secure(tx1.data().intent_message(), 0, kp.public().into());
     assert!(verification.is_ok())
 }
+
+#[test]
+fn test_user_signature_bound_to_intent_scope() {
+    let (sender, sender_key): (_, AccountKeyPair) = get_key_pair();
+    let object = Object::immutable_with_id_for_testing(ObjectID::random());
+    let data = TransactionData::new_transfer_sui_with_dummy_gas_price(
+        dbg_addr(2),
+        sender,
+        None,
+        object.compute_object_reference(),
+        10000,
+    );
+    let tx_intent_msg = IntentMessage::new(Intent::sui_transaction(), data.clone());
+    let other_intent = Intent::sui_app(IntentScope::PersonalMessage);
+
+    // The same transaction data signed under another intent scope does not sign the transaction.
+    let signature = Signature::new_secure(
+        &IntentMessage::new(other_intent.clone(), data.clone()),
+        &sender_key,
+    );
+    assert!(signature
+        .verify_secure_generic(&tx_intent_msg, sender)
+        .is_err());
+    let tx = Transaction::from_data(
+        data.clone(),
+        Intent::sui_transaction(),
+        vec![signature.clone()],
+    );
+    assert!(tx.verify().is_err());
+
+    // Nor can the transaction claim the intent the data was signed under, once the protocol
+    // rejects other intents. Its signature is valid, so that it still verifies.
+    let tx = Transaction::from_data(data.clone(), other_intent.clone(), vec![signature]);
+    let allowed = ProtocolConfig::get_for_version(ProtocolVersion::new(3));
+    let rejected = ProtocolConfig::get_for_version(ProtocolVersion::new(4));
+    assert!(tx.data().check_intent(&allowed).is_ok());
+    assert!(tx.data().check_intent(&rejected).is_err());
+    let tx = tx.verify().unwrap();
+    assert!(tx.data().check_intent(&rejected).is_err());
+    let tx = Transaction::from_data(
+        data.clone(),
+        Intent::sui_transaction(),
+        vec![Signature::new_secure(&tx_intent_msg, &sender_key)],
+    );
+    assert!(tx.data().check_intent(&rejected).is_ok());
+
+    // Same for a multisig, whose signatures are all over the other intent scope.
+    let keys: Vec<SuiKeyPair> = (0..2)
+        .map(|_| SuiKeyPair::Ed25519(get_key_pair().1))
+        .collect();
+    let multisig_pk =
+        MultiSigPublicKey::new(keys.iter().map(|kp| kp.public()).collect(), vec![1, 1], 2).unwrap();
+    let multisig_addr = SuiAddress::from(multisig_pk.clone());
+    let sign_all = |msg: &IntentMessage<TransactionData>| {
+        MultiSig::combine(
+            keys.iter()
+                .map(|kp| Signature::new_secure(msg, kp))
+                .collect(),
+            multisig_pk.clone(),
+        )
+        .unwrap()
+    };
+    let multisig = sign_all(&tx_intent_msg);
+    assert!(multisig
+        .verify_secure_generic(&tx_intent_msg, multisig_addr)
+        .is_ok());
+    let multisig = sign_all(&IntentMessage::new(other_intent, data));
+    assert!(multisig
+        .verify_secure_generic(&tx_intent_msg, multisig_addr)
+        .is_err());
+}
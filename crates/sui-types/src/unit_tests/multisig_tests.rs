@@ -12,8 +12,8 @@ use super::{MultiSigPublicKey, ThresholdUnit, WeightUnit};
 use crate::{
     base_types::SuiAddress,
     crypto::{
-        get_key_pair, get_key_pair_from_rng, Ed25519SuiSignature, Signature, SuiKeyPair,
-        SuiSignatureInner,
+        get_key_pair, get_key_pair_from_rng, Ed25519SuiSignature, IntentMessageDigest, Signature,
+        SuiKeyPair, SuiSignatureInner,
     },
     multisig::{MultiSig, MAX_SIGNER_IN_MULTISIG},
     signature::{AuthenticatorTrait, GenericSignature},
@@ -252,6 +252,77 @@ fn single_sig_port_works() {
     assert!(sig.verify_secure_generic(&msg, addr).is_ok());
 }
 
+#[test]
+fn prehashed_verification_matches_full_verification() {
+    let keys = keys();
+    let multisig_pk = MultiSigPublicKey::new(
+        keys.iter().map(|kp| kp.public()).collect(),
+        vec![1, 1, 1],
+        2,
+    )
+    .unwrap();
+    let multisig_addr = SuiAddress::from(multisig_pk.clone());
+    let single_addr = SuiAddress::from(&keys[0].public());
+    let msg = IntentMessage::new(
+        Intent::sui_transaction(),
+        PersonalMessage {
+            message: "Hello".as_bytes().to_vec(),
+        },
+    );
+    let other_msg = IntentMessage::new(
+        Intent::sui_transaction(),
+        PersonalMessage {
+            message: "Goodbye".as_bytes().to_vec(),
+        },
+    );
+    let sigs: Vec<Signature> = keys
+        .iter()
+        .map(|kp| Signature::new_secure(&msg, kp))
+        .collect();
+
+    // Valid and invalid signatures, each with the address it is checked against
+    let cases: Vec<(GenericSignature, SuiAddress)> = vec![
+        (sigs[0].clone().into(), single_addr),
+        (sigs[0].clone().into(), multisig_addr),
+        (sigs[1].clone().into(), single_addr),
+        (
+            MultiSig::combine(vec![sigs[0].clone(), sigs[1].clone()], multisig_pk.clone())
+                .unwrap()
+                .into(),
+            multisig_addr,
+        ),
+        (
+            MultiSig::combine(vec![sigs[0].clone()], multisig_pk.clone())
+                .unwrap()
+                .into(),
+            multisig_addr,
+        ),
+        (
+            MultiSig::combine(vec![sigs[1].clone(), sigs[2].clone()], multisig_pk)
+                .unwrap()
+                .into(),
+            single_addr,
+        ),
+    ];
+    let mut outcomes = vec![];
+    for value in [&msg, &other_msg] {
+        let digest = IntentMessageDigest::new(value);
+        for (sig, addr) in &cases {
+            let verified = sig.verify_secure_generic(value, *addr).is_ok();
+            assert_eq!(
+                verified,
+                sig.verify_secure_generic_prehashed(&digest, *addr).is_ok()
+            );
+            outcomes.push(verified);
+        }
+    }
+    // Both the single signature and the multisig cases include valid signatures
+    assert_eq!(
+        outcomes,
+        [vec![true, false, false, true, false, false], vec![false; 6]].concat()
+    );
+}
+
 #[test]
 fn test_multisig_pk_failure() {
     let keys = keys();